@@ -1,4 +1,6 @@
+use crate::files::{parse_glob, parse_time, parse_until};
 use clap::{Parser, Subcommand};
+use glob::Pattern;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -58,16 +60,101 @@ pub enum Command {
         #[arg(long, help = "Enable AI deep inspection (slower but accurate)")]
         no_skip_deep_inspect: bool,
     },
+    /// Propose categories for a folder with AI, then edit and save them
+    #[command(name = "discover")]
+    Discover {
+        #[arg(help = "Path to sample (defaults to configured download folder)")]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Recursively search files in subdirectory")]
+        recursive: bool,
+        #[arg(long, help = "Save the categories for this folder only")]
+        folder: bool,
+        #[arg(
+            long,
+            default_value_t = 200,
+            help = "Maximum number of filenames sent to the AI"
+        )]
+        sample_size: usize,
+    },
     /// Undo the last file organization
     Undo {
         #[arg(long, help = "Preview changes without moving files")]
         dry_run: bool,
         #[arg(help = "Path to undo (defaults to configured download folder)")]
         path: Option<PathBuf>,
+        #[arg(long, help = "Undo only the most recent organize session")]
+        last: bool,
+        #[arg(
+            long,
+            conflicts_with = "last",
+            help = "Undo only the session with this ID (see `noentropy history`)"
+        )]
+        session: Option<u64>,
+        #[arg(
+            long,
+            value_parser = parse_glob,
+            help = "Undo only files whose original path matches this glob"
+        )]
+        source: Option<Pattern>,
+        #[arg(
+            long = "dest",
+            value_parser = parse_glob,
+            help = "Undo only files whose organized path matches this glob"
+        )]
+        destination: Option<Pattern>,
+        #[arg(long, help = "Undo only files moved into this category folder")]
+        category: Option<String>,
+        #[arg(
+            long,
+            value_parser = parse_time,
+            help = "Undo only moves made since this time (YYYY-MM-DD[ HH:MM] UTC, or an age like 2h or 3d)"
+        )]
+        since: Option<u64>,
+        #[arg(
+            long,
+            value_parser = parse_until,
+            help = "Undo only moves made until this time (same formats as --since; a date includes the whole day)"
+        )]
+        until: Option<u64>,
+    },
+    /// Re-apply moves that were undone
+    #[command(name = "redo")]
+    Redo {
+        #[arg(long, help = "Preview changes without moving files")]
+        dry_run: bool,
+        #[arg(help = "Path to redo (defaults to configured download folder)")]
+        path: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Redo only the session with this ID (see `noentropy history`)"
+        )]
+        session: Option<u64>,
+    },
+    /// List past organize sessions that can be undone
+    #[command(name = "history")]
+    History {
+        #[arg(
+            long,
+            default_value_t = 20,
+            help = "Maximum number of sessions to show"
+        )]
+        limit: usize,
     },
     /// Change the API key
     #[command(name = "key")]
     ChangeKey,
+    /// Show Gemini API usage by day and by folder
+    #[command(name = "usage")]
+    Usage {
+        #[arg(long, default_value_t = 7, help = "Number of days to include")]
+        days: u64,
+    },
+    /// Inspect and manage the categorization cache
+    #[command(name = "cache")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
     /// Detect and delete duplicate files
     #[command(name = "duplicates")]
     Duplicates {
@@ -75,3 +162,35 @@ pub enum Command {
         recursive: bool,
     },
 }
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum CacheAction {
+    /// Show entry counts, ages, folders and hit rates
+    Stats,
+    /// List cached categorizations, newest first
+    List {
+        #[arg(long, default_value_t = 50, help = "Maximum number of entries to show")]
+        limit: usize,
+    },
+    /// Remove every cached entry
+    Clear,
+    /// Remove entries older than the given number of days
+    Prune {
+        #[arg(
+            long,
+            default_value_t = 7,
+            help = "Keep entries newer than this many days"
+        )]
+        days: u64,
+    },
+    /// Write the cache to a file that can be shared and imported
+    Export {
+        #[arg(help = "File to write")]
+        file: PathBuf,
+    },
+    /// Add the entries from an exported cache file
+    Import {
+        #[arg(help = "File written by `noentropy cache export`")]
+        file: PathBuf,
+    },
+}
@@ -25,7 +25,7 @@ pub fn handle_gemini_error(error: crate::gemini::GeminiError) {
         }
         crate::gemini::GeminiError::InvalidApiKey => {
             println!(
-                "{} Invalid API key. Please check your GEMINI_API_KEY environment variable.",
+                "{} Invalid API key. Please check GEMINI_API_KEY, api_key_command or api_key in your config.",
                 "ERROR:".red()
             );
         }
@@ -63,6 +63,19 @@ pub fn handle_gemini_error(error: crate::gemini::GeminiError) {
         crate::gemini::GeminiError::InvalidResponse(msg) => {
             println!("{} Invalid response from Gemini: {}", "ERROR:".red(), msg);
         }
+        crate::gemini::GeminiError::ResponseTruncated => {
+            println!(
+                "{} Gemini's response was truncated. Try organizing fewer files at once.",
+                "ERROR:".red()
+            );
+        }
+        crate::gemini::GeminiError::UsageLimitReached(details) => {
+            println!(
+                "{} Daily usage limit reached ({}). Raise [usage_limits] in the config or try again tomorrow.",
+                "ERROR:".red(),
+                details
+            );
+        }
         crate::gemini::GeminiError::InternalError { details } => {
             println!("{} Internal server error: {}", "ERROR:".red(), details);
         }
@@ -72,7 +85,7 @@ pub fn handle_gemini_error(error: crate::gemini::GeminiError) {
     }
 
     println!("\n{} Check the following:", "HINT:".yellow());
-    println!("  - Your GEMINI_API_KEY is correctly set");
+    println!("  - Your API key is correctly set (GEMINI_API_KEY, api_key_command or config)");
     println!("  - Your internet connection is working");
     println!("  - Gemini API service is available");
     println!("  - You haven't exceeded your API quota");
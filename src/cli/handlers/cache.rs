@@ -0,0 +1,207 @@
+use crate::cli::Command;
+use crate::cli::args::CacheAction;
+use crate::cli::handlers::usage::truncate_left;
+use crate::error::Result;
+use crate::files::context::format_date;
+use crate::settings::Config;
+use crate::storage::{Cache, CacheExport, SessionLock, UsageLog};
+use colored::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Days of usage history used for hit rates in `cache stats`.
+const HIT_RATE_DAYS: u64 = 30;
+
+/// Upper bounds, in days, of the age groups shown by `cache stats`.
+const AGE_BUCKETS: [(u64, &str); 3] = [(1, "< 1 day"), (7, "1-7 days"), (30, "7-30 days")];
+
+pub fn handle_cache(command: &Command) -> Result<()> {
+    let action = match command {
+        Command::Cache { action } => action,
+        _ => unreachable!(),
+    };
+
+    run_cache_action(
+        action,
+        &Config::get_cache_path()?,
+        &Config::get_usage_log_path()?,
+    )
+}
+
+/// Runs `action` against the cache at `cache_path`. Hit rates for `stats`
+/// come from the usage log at `usage_log_path`.
+pub fn run_cache_action(
+    action: &CacheAction,
+    cache_path: &Path,
+    usage_log_path: &Path,
+) -> Result<()> {
+    // Actions that save the cache hold its session lock until they do
+    let _lock = match action {
+        CacheAction::Clear | CacheAction::Prune { .. } | CacheAction::Import { .. } => {
+            Some(SessionLock::acquire(cache_path, false)?)
+        }
+        _ => None,
+    };
+    let mut cache = Cache::load_or_create(cache_path, true);
+
+    match action {
+        CacheAction::Stats => {
+            print_stats(&cache);
+            if usage_log_path.exists() {
+                match UsageLog::load_or_create(usage_log_path, true) {
+                    Ok(usage_log) => print_hit_rates(&usage_log),
+                    Err(e) => eprintln!("Warning: {}", e),
+                }
+            }
+        }
+        CacheAction::List { limit } => print_entries(&cache, *limit),
+        CacheAction::Clear => {
+            let removed = cache.clear();
+            cache.save(cache_path)?;
+            println!("{} Removed {} cache entries.", "✓".green(), removed);
+        }
+        CacheAction::Prune { days } => {
+            let removed = cache.cleanup_old_entries(days.saturating_mul(SECONDS_PER_DAY));
+            cache.save(cache_path)?;
+            println!(
+                "{} Removed {} entries older than {} day(s); {} left.",
+                "✓".green(),
+                removed,
+                days,
+                cache.len() + cache.sub_category_len()
+            );
+        }
+        CacheAction::Export { file } => {
+            let export = cache.export();
+            fs::write(file, serde_json::to_string_pretty(&export)?)?;
+            println!(
+                "{} Exported {} categorizations and {} sub-categories to {}",
+                "✓".green(),
+                export.files.len(),
+                export.sub_categories.len(),
+                file.display()
+            );
+        }
+        CacheAction::Import { file } => {
+            let export: CacheExport = serde_json::from_str(&fs::read_to_string(file)?)?;
+            let imported = cache.import(export);
+            cache.save(cache_path)?;
+            println!(
+                "{} Imported {} entries from {}",
+                "✓".green(),
+                imported,
+                file.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn print_stats(cache: &Cache) {
+    let now = current_time();
+
+    println!("\n{}", "--- CACHE ---".bold().underline());
+    println!("Categorized files: {}", cache.len());
+    println!("Sub-categories:    {}", cache.sub_category_len());
+    if cache.is_empty() && cache.sub_category_len() == 0 {
+        return;
+    }
+
+    println!("\n{}", "--- AGE ---".bold().underline());
+    let ages = cache
+        .entries()
+        .map(|e| e.timestamp)
+        .chain(cache.sub_category_entries().map(|e| e.timestamp));
+    let mut counts = [0usize; AGE_BUCKETS.len() + 1];
+    for timestamp in ages {
+        let days = now.saturating_sub(timestamp) / SECONDS_PER_DAY;
+        let bucket = AGE_BUCKETS
+            .iter()
+            .position(|(max, _)| days < *max)
+            .unwrap_or(AGE_BUCKETS.len());
+        counts[bucket] += 1;
+    }
+    let labels = AGE_BUCKETS.iter().map(|(_, label)| *label).chain(["older"]);
+    for (label, count) in labels.zip(counts) {
+        println!("{:<12} {:>8}", label, count);
+    }
+
+    let mut folders: BTreeMap<&str, usize> = BTreeMap::new();
+    for entry in cache.entries() {
+        *folders
+            .entry(entry.folder.as_deref().unwrap_or("(imported)"))
+            .or_default() += 1;
+    }
+    println!("\n{}", "--- FOLDERS ---".bold().underline());
+    println!("{:<50} {:>8}", "Folder", "Files");
+    for (folder, count) in folders {
+        println!("{:<50} {:>8}", truncate_left(folder, 50), count);
+    }
+}
+
+fn print_hit_rates(usage_log: &UsageLog) {
+    let since = current_time().saturating_sub(HIT_RATE_DAYS * SECONDS_PER_DAY);
+    let by_folder = usage_log.by_folder(since);
+    if by_folder.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{}",
+        format!("--- HIT RATE (last {} days) ---", HIT_RATE_DAYS)
+            .bold()
+            .underline()
+    );
+    println!(
+        "{:<50} {:>12} {:>10} {:>9}",
+        "Folder", "Cached files", "Sent files", "Hit rate"
+    );
+    for (folder, totals) in by_folder {
+        println!(
+            "{:<50} {:>12} {:>10} {:>8.0}%",
+            truncate_left(&folder, 50),
+            totals.cached_files,
+            totals.requested_files,
+            totals.file_hit_rate()
+        );
+    }
+}
+
+fn print_entries(cache: &Cache, limit: usize) {
+    let mut entries: Vec<_> = cache.entries().collect();
+    if entries.is_empty() {
+        println!("{}", "The cache is empty.".yellow());
+        return;
+    }
+    entries.sort_by(|a, b| {
+        b.timestamp
+            .cmp(&a.timestamp)
+            .then_with(|| a.category.filename.cmp(&b.category.filename))
+    });
+
+    println!("{:<10} {:<40} {:<20} Folder", "Cached", "File", "Category");
+    for entry in entries.iter().take(limit) {
+        println!(
+            "{:<10} {:<40} {:<20} {}",
+            format_date(entry.timestamp),
+            truncate_left(&entry.category.filename, 40),
+            entry.category.category,
+            entry.folder.as_deref().unwrap_or("(imported)")
+        );
+    }
+    if entries.len() > limit {
+        println!("... and {} more", entries.len() - limit);
+    }
+}
+
+fn current_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
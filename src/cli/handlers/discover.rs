@@ -0,0 +1,280 @@
+use crate::cli::Command;
+use crate::cli::errors::handle_gemini_error;
+use crate::cli::path_utils::validate_and_normalize_path;
+use crate::error::Result;
+use crate::files::{FileBatch, sanitize_segment};
+use crate::gemini::discovery::MAX_PROPOSED_CATEGORIES;
+use crate::gemini::{CategoryProposal, GeminiClient, GeminiError};
+use crate::settings::Config;
+use crate::storage::{SessionLock, USAGE_LOG_RETENTION_SECONDS, UsageLog};
+use colored::*;
+use std::io::Write;
+use std::path::Path;
+
+/// What to do after an edit command has been applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditOutcome {
+    /// Show the list again and wait for another command.
+    Continue,
+    /// Save the list as it is.
+    Accept,
+    /// Leave the configuration unchanged.
+    Cancel,
+}
+
+/// Applies one line of input from the review prompt to `categories`.
+///
+/// An empty line or `y` accepts the list and `q` cancels. `a <name>` adds a
+/// category, `r <n> <name>` renames the n-th one, `d <n>` deletes it and
+/// `e <n> <text>` replaces its description. Returns an error message for
+/// input that could not be applied.
+pub fn apply_category_edit(
+    categories: &mut Vec<CategoryProposal>,
+    input: &str,
+) -> std::result::Result<EditOutcome, String> {
+    let input = input.trim();
+    let (command, rest) = input
+        .split_once(char::is_whitespace)
+        .map(|(command, rest)| (command, rest.trim()))
+        .unwrap_or((input, ""));
+
+    match command.to_lowercase().as_str() {
+        "" | "y" | "yes" => {
+            if categories.is_empty() {
+                return Err("Keep at least one category".to_string());
+            }
+            Ok(EditOutcome::Accept)
+        }
+        "q" | "quit" => Ok(EditOutcome::Cancel),
+        "a" | "add" => {
+            if categories.len() >= MAX_PROPOSED_CATEGORIES {
+                return Err(format!(
+                    "At most {} categories are allowed",
+                    MAX_PROPOSED_CATEGORIES
+                ));
+            }
+            let name = checked_name(categories, rest, None)?;
+            categories.push(CategoryProposal {
+                name,
+                description: String::new(),
+            });
+            Ok(EditOutcome::Continue)
+        }
+        "r" | "rename" => {
+            let (index, name) = split_index(categories, rest)?;
+            categories[index].name = checked_name(categories, name, Some(index))?;
+            Ok(EditOutcome::Continue)
+        }
+        "d" | "delete" => {
+            let (index, _) = split_index(categories, rest)?;
+            categories.remove(index);
+            Ok(EditOutcome::Continue)
+        }
+        "e" | "edit" => {
+            let (index, description) = split_index(categories, rest)?;
+            categories[index].description = description.to_string();
+            Ok(EditOutcome::Continue)
+        }
+        _ => Err(format!("Unknown command '{}'", command)),
+    }
+}
+
+/// Parses a 1-based category number at the start of `input`, returning its
+/// index and the rest of the input.
+fn split_index<'a>(
+    categories: &[CategoryProposal],
+    input: &'a str,
+) -> std::result::Result<(usize, &'a str), String> {
+    let (number, rest) = input
+        .split_once(char::is_whitespace)
+        .map(|(number, rest)| (number, rest.trim()))
+        .unwrap_or((input, ""));
+    match number.parse::<usize>() {
+        Ok(n) if (1..=categories.len()).contains(&n) => Ok((n - 1, rest)),
+        _ => Err(format!(
+            "Expected a category number between 1 and {}",
+            categories.len()
+        )),
+    }
+}
+
+/// Sanitizes a category name typed by the user and rejects duplicates,
+/// ignoring the category being renamed.
+fn checked_name(
+    categories: &[CategoryProposal],
+    name: &str,
+    renaming: Option<usize>,
+) -> std::result::Result<String, String> {
+    let name =
+        sanitize_segment(name).map_err(|e| format!("Cannot use {:?} as a folder: {}", name, e))?;
+    let taken = categories
+        .iter()
+        .enumerate()
+        .any(|(i, c)| Some(i) != renaming && c.name.eq_ignore_ascii_case(&name));
+    if taken {
+        return Err(format!("'{}' is already in the list", name));
+    }
+    Ok(name)
+}
+
+pub async fn handle_discover(command: &Command, mut config: Config) -> Result<()> {
+    let (path, recursive, per_folder, sample_size) = match command {
+        Command::Discover {
+            path,
+            recursive,
+            folder,
+            sample_size,
+        } => (path.clone(), *recursive, *folder, (*sample_size).max(1)),
+        _ => unreachable!(),
+    };
+
+    let target_path = path.unwrap_or_else(|| config.download_folder.clone());
+    let target_path = match validate_and_normalize_path(&target_path).await {
+        Ok(normalized) => normalized,
+        Err(e) => {
+            println!("{}", format!("ERROR: {}", e).red());
+            return Ok(());
+        }
+    };
+
+    let batch = FileBatch::from_path(&target_path, recursive);
+    if batch.filenames.is_empty() {
+        println!("{}", "No files found to sample!".yellow());
+        return Ok(());
+    }
+
+    let client = GeminiClient::from_config(&config)?;
+    if !client.has_api_key() {
+        println!(
+            "{} Discovering categories needs a Gemini API key. Run 'noentropy key' to add one.",
+            "ERROR:".red()
+        );
+        return Ok(());
+    }
+
+    let usage_log_path = Config::get_usage_log_path()?;
+    let usage_log_lock = SessionLock::acquire(&usage_log_path, false)?;
+    let mut usage_log = UsageLog::load_or_create(&usage_log_path, false)?;
+    usage_log.cleanup_old_entries(USAGE_LOG_RETENTION_SECONDS);
+
+    let result = request_proposals(
+        &client,
+        &config,
+        &target_path,
+        &batch,
+        sample_size,
+        &mut usage_log,
+    )
+    .await;
+    if let Err(e) = usage_log.save(&usage_log_path) {
+        eprintln!("Warning: Failed to save usage log: {}", e);
+    }
+    drop(usage_log_lock);
+
+    let mut categories = match result {
+        Ok(categories) if !categories.is_empty() => categories,
+        Ok(_) => {
+            println!(
+                "{}",
+                "Gemini did not propose any usable categories.".yellow()
+            );
+            return Ok(());
+        }
+        Err(e) => {
+            handle_gemini_error(e);
+            return Ok(());
+        }
+    };
+
+    if !review_categories(&mut categories)? {
+        println!("{}", "Discarded the proposed categories.".yellow());
+        return Ok(());
+    }
+
+    let names: Vec<String> = categories.into_iter().map(|c| c.name).collect();
+    if per_folder {
+        config.set_folder_categories(&target_path, names);
+    } else {
+        config.categories = names;
+    }
+    config.save()?;
+
+    println!(
+        "Run {} to organize with these categories.",
+        "noentropy organize --online".cyan()
+    );
+    Ok(())
+}
+
+/// Asks Gemini for categories from a sample of `batch`, holding the request
+/// to the configured daily limits and adding its usage to `usage_log`.
+pub async fn request_proposals(
+    client: &GeminiClient,
+    config: &Config,
+    target_path: &Path,
+    batch: &FileBatch,
+    sample_size: usize,
+    usage_log: &mut UsageLog,
+) -> std::result::Result<Vec<CategoryProposal>, GeminiError> {
+    client.usage().set_folder(target_path);
+    client
+        .usage()
+        .set_limits(config.usage_limits.clone(), usage_log.today());
+
+    let sample = batch.sample(sample_size);
+    println!(
+        "Asking Gemini to propose categories from {} of {} files...",
+        sample.len(),
+        batch.count()
+    );
+    let result = client.propose_categories(&sample, batch.count()).await;
+    usage_log.extend(client.usage().take_records());
+    result
+}
+
+/// Shows the proposals and applies edit commands until the user accepts or
+/// cancels. Returns whether the list was accepted.
+fn review_categories(categories: &mut Vec<CategoryProposal>) -> Result<bool> {
+    loop {
+        print_categories(categories);
+        println!(
+            "Press Enter to accept, or edit: {} <name>, {} <n> <name>, {} <n>, {} <n> <description>, {} to cancel",
+            "a".cyan(),
+            "r".cyan(),
+            "d".cyan(),
+            "e".cyan(),
+            "q".cyan()
+        );
+        print!("> ");
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input)? == 0 {
+            return Ok(false);
+        }
+
+        match apply_category_edit(categories, &input) {
+            Ok(EditOutcome::Continue) => {}
+            Ok(EditOutcome::Accept) => return Ok(true),
+            Ok(EditOutcome::Cancel) => return Ok(false),
+            Err(message) => println!("{} {}", "WARN:".yellow(), message),
+        }
+    }
+}
+
+fn print_categories(categories: &[CategoryProposal]) {
+    println!("\n{}", "Proposed categories:".bold());
+    for (i, category) in categories.iter().enumerate() {
+        if category.description.is_empty() {
+            println!("  {:>2}. {}", i + 1, category.name.green());
+        } else {
+            println!(
+                "  {:>2}. {} - {}",
+                i + 1,
+                category.name.green(),
+                category.description
+            );
+        }
+    }
+    println!();
+}
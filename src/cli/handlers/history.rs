@@ -0,0 +1,83 @@
+use crate::cli::Command;
+use crate::cli::handlers::usage::truncate_left;
+use crate::error::Result;
+use crate::files::context::format_date;
+use crate::models::MoveStatus;
+use crate::settings::Config;
+use crate::storage::UndoLog;
+use colored::*;
+use std::path::Path;
+
+pub fn handle_history(command: &Command) -> Result<()> {
+    let limit = match command {
+        Command::History { limit } => *limit,
+        _ => unreachable!(),
+    };
+
+    print_history(&Config::get_undo_log_path()?, limit);
+    Ok(())
+}
+
+/// Prints the newest `limit` sessions in the undo log at `undo_log_path`.
+pub fn print_history(undo_log_path: &Path, limit: usize) {
+    if !undo_log_path.exists() {
+        println!(
+            "{}",
+            "No undo log found. Nothing has been organized yet.".yellow()
+        );
+        return;
+    }
+    let undo_log = UndoLog::load_or_create(undo_log_path, true);
+
+    let mut sessions: Vec<_> = undo_log.sessions().iter().collect();
+    if sessions.is_empty() {
+        println!("{}", "No organize sessions recorded.".yellow());
+    } else {
+        sessions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+
+        println!("\n{}", "--- HISTORY (UTC) ---".bold().underline());
+        println!(
+            "{:>4}  {:<16}  {:<7}  {:>5}  {:>6}  {:<30}  Summary",
+            "ID", "Date", "Mode", "Moved", "Undone", "Folder"
+        );
+        for session in sessions.iter().take(limit) {
+            let records = undo_log.session_records(session.id);
+            let count = |status| records.iter().filter(|r| r.status == status).count();
+            println!(
+                "{:>4}  {:<16}  {:<7}  {:>5}  {:>6}  {:<30}  {}",
+                session.id,
+                format_time(session.timestamp),
+                session.mode.to_string(),
+                count(MoveStatus::Completed),
+                count(MoveStatus::Undone),
+                truncate_left(&session.target_path.to_string_lossy(), 30),
+                session.summary
+            );
+        }
+        if sessions.len() > limit {
+            println!("... and {} older sessions", sessions.len() - limit);
+        }
+    }
+
+    let legacy = undo_log
+        .get_completed_moves()
+        .iter()
+        .filter(|r| r.session.is_none())
+        .count();
+    if legacy > 0 {
+        println!(
+            "\n{} {} moves were made before sessions were recorded; `noentropy undo` without --last or --session reverts them.",
+            "INFO:".cyan(),
+            legacy
+        );
+    }
+}
+
+fn format_time(secs: u64) -> String {
+    format!(
+        "{} {:02}:{:02}",
+        format_date(secs),
+        secs % 86_400 / 3_600,
+        secs % 3_600 / 60
+    )
+}
@@ -1,7 +1,17 @@
+mod cache;
+mod discover;
+mod history;
 mod offline;
 mod online;
+mod redo;
 mod undo;
+mod usage;
 
+pub use cache::{handle_cache, run_cache_action};
+pub use discover::{EditOutcome, apply_category_edit, handle_discover, request_proposals};
+pub use history::{handle_history, print_history};
 pub use offline::handle_offline_organization;
 pub use online::handle_online_organization;
+pub use redo::handle_redo;
 pub use undo::handle_undo;
+pub use usage::handle_usage;
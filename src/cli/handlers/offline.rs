@@ -1,6 +1,8 @@
 use crate::error::Result;
-use crate::files::{FileBatch, categorize_files_offline, execute_move};
-use crate::models::OrganizationPlan;
+use crate::files::{
+    FileBatch, apply_content_sub_categories, categorize_files_offline, execute_move,
+};
+use crate::models::{OrganizationPlan, SessionMode};
 use crate::storage::UndoLog;
 use colored::*;
 use std::collections::HashMap;
@@ -10,11 +12,13 @@ pub fn handle_offline_organization(
     batch: FileBatch,
     target_path: &Path,
     dry_run: bool,
+    read_documents: bool,
     undo_log: &mut UndoLog,
 ) -> Result<Option<OrganizationPlan>> {
     println!("{}", "Categorizing files by extension...".cyan());
 
-    let result = categorize_files_offline(batch.filenames);
+    let mut result = categorize_files_offline(batch.filenames);
+    apply_content_sub_categories(&mut result.plan, target_path, read_documents);
 
     if result.plan.files.is_empty() {
         println!("{}", "No files could be categorized offline.".yellow());
@@ -29,7 +33,9 @@ pub fn handle_offline_organization(
     if dry_run {
         println!("{} Dry run mode - skipping file moves.", "INFO:".cyan());
     } else {
+        undo_log.begin_session(target_path, SessionMode::Offline);
         execute_move(target_path, result.plan, Some(undo_log));
+        undo_log.finish_session();
     }
 
     println!("{}", "Done!".green().bold());
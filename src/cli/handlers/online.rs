@@ -1,15 +1,16 @@
 use crate::cli::Command;
 use crate::cli::errors::handle_gemini_error;
 use crate::error::Result;
-use crate::files::{FileBatch, execute_move, is_text_file, read_file_sample};
-use crate::gemini::GeminiClient;
-use crate::models::OrganizationPlan;
+use crate::files::{
+    FileBatch, SubFolderIndex, execute_move, read_inspection_targets, sanitize_plan,
+};
+use crate::gemini::{GeminiClient, SubCategoryFailure};
+use crate::models::{MultimodalConfig, OrganizationPlan, SessionMode};
 use crate::settings::Config;
-use crate::storage::{Cache, UndoLog};
+use crate::storage::{Cache, UndoLog, UsageLog};
 use colored::*;
-use futures::future::join_all;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 
 fn get_deep_inspect_flags(command: &Command) -> (bool, bool) {
     match command {
@@ -29,6 +30,7 @@ pub async fn handle_online_organization(
     target_path: &Path,
     cache: &mut Cache,
     undo_log: &mut UndoLog,
+    usage_log: &mut UsageLog,
 ) -> Result<Option<OrganizationPlan>> {
     let (max_concurrent, dry_run) = match command {
         Command::Organize {
@@ -42,16 +44,28 @@ pub async fn handle_online_organization(
     let (skip_flag, no_skip_flag) = get_deep_inspect_flags(command);
     let should_deep_inspect = config.should_deep_inspect(skip_flag, no_skip_flag);
 
-    let paths = batch.paths.clone();
+    let paths: HashMap<String, PathBuf> = batch
+        .filenames
+        .iter()
+        .cloned()
+        .zip(batch.paths.iter().cloned())
+        .collect();
 
-    let client = GeminiClient::new(&config.api_key, &config.categories);
+    let mut client = GeminiClient::from_config(config)?;
+    client.set_max_concurrent(max_concurrent);
+    client.usage().set_folder(target_path);
+    client
+        .usage()
+        .set_limits(config.usage_limits.clone(), usage_log.today());
 
     println!("Asking Gemini to organize...");
 
-    let mut plan: OrganizationPlan = match client
+    let result = client
         .organize_files_in_batches(batch.filenames, Some(cache), Some(target_path))
-        .await
-    {
+        .await;
+    usage_log.extend(client.usage().take_records());
+
+    let mut plan: OrganizationPlan = match result {
         Ok(plan) => plan,
         Err(e) => {
             handle_gemini_error(e);
@@ -59,70 +73,116 @@ pub async fn handle_online_organization(
         }
     };
 
+    print_uncategorized_files(&plan.uncategorized);
+
     if should_deep_inspect {
-        perform_deep_inspection(&mut plan, &paths, &client, max_concurrent).await;
+        perform_deep_inspection(
+            &mut plan,
+            &paths,
+            &client,
+            &config.multimodal,
+            target_path,
+            undo_log,
+            cache,
+        )
+        .await;
+        usage_log.extend(client.usage().take_records());
     }
 
+    for fix in sanitize_plan(&mut plan) {
+        println!("{} {}", "WARN:".yellow(), fix);
+    }
+    plan.hold_low_confidence(config.min_confidence);
+
     println!("{}", "Moving Files.....".green());
 
     if dry_run {
         println!("{} Dry run mode - skipping file moves.", "INFO:".cyan());
     } else {
+        undo_log.begin_session(target_path, SessionMode::Online);
         execute_move(target_path, plan, Some(undo_log));
+        undo_log.finish_session();
     }
     println!("{}", "Done!".green().bold());
 
     Ok(None)
 }
 
+fn print_uncategorized_files(uncategorized: &[String]) {
+    if uncategorized.is_empty() {
+        return;
+    }
+
+    println!(
+        "{} {} file(s) were not categorized by Gemini and will be left in place:",
+        "WARN:".yellow(),
+        uncategorized.len()
+    );
+    for filename in uncategorized.iter().take(10) {
+        println!("  - {}", filename);
+    }
+    if uncategorized.len() > 10 {
+        println!("  ... and {} more", uncategorized.len() - 10);
+    }
+    println!();
+}
+
 async fn perform_deep_inspection(
     plan: &mut OrganizationPlan,
-    paths: &[PathBuf],
+    paths: &HashMap<String, PathBuf>,
     client: &GeminiClient,
-    max_concurrent: usize,
+    multimodal: &MultimodalConfig,
+    target_path: &Path,
+    undo_log: &UndoLog,
+    cache: &mut Cache,
 ) {
     println!(
         "{}",
         "Gemini Plan received! Performing deep inspection...".green()
     );
 
-    let client_arc = Arc::new(client.clone());
-    let semaphore = Arc::new(tokio::sync::Semaphore::new(max_concurrent));
-
-    let tasks: Vec<_> = plan
-        .files
-        .iter_mut()
-        .zip(paths.iter())
-        .map(|(file_category, path)| {
-            let client = Arc::clone(&client_arc);
-            let filename = file_category.filename.clone();
-            let category = file_category.category.clone();
-            let path = path.clone();
-            let semaphore = Arc::clone(&semaphore);
-
-            async move {
-                if is_text_file(&path) {
-                    let _permit = semaphore.acquire().await.unwrap();
-                    if let Some(content) = read_file_sample(&path, 5000) {
-                        println!("Reading content of {}...", filename.green());
-                        client
-                            .get_ai_sub_category(&filename, &category, &content)
-                            .await
-                    } else {
-                        String::new()
-                    }
-                } else {
-                    String::new()
-                }
-            }
-        })
-        .collect();
+    let files = plan.files.clone();
+    let paths = paths.clone();
+    let multimodal = multimodal.clone();
+    let budget = client.media_budget().clone();
+    // Extracting text and downscaling images is CPU-bound
+    let targets = tokio::task::spawn_blocking(move || {
+        read_inspection_targets(&files, &paths, 5000, Some(&multimodal), &budget)
+    })
+    .await
+    .unwrap_or_default();
+
+    println!("Reading content of {} file(s)...", targets.len());
+    let existing = SubFolderIndex::scan(
+        target_path,
+        &plan.categories(),
+        &undo_log.get_directory_usage(target_path),
+    );
+    let results = client
+        .inspect_sub_categories_with_cache(&targets, &existing, Some(cache))
+        .await;
+    plan.apply_sub_categories(&results.sub_categories);
+    existing.unify_sub_categories(plan);
 
-    let sub_categories: Vec<String> = join_all(tasks).await;
+    println!("{}", "Deep inspection complete!".green());
+    print_sub_category_failures(&results.failures);
+}
 
-    for (file_category, sub_category) in plan.files.iter_mut().zip(sub_categories) {
-        file_category.sub_category = sub_category;
+fn print_sub_category_failures(failures: &[SubCategoryFailure]) {
+    if failures.is_empty() {
+        return;
     }
 
-    println!("{}", "Deep inspection complete!".green());
+    println!(
+        "{} {} file(s) could not be sub-categorized and will stay in their category folder:",
+        "WARN:".yellow(),
+        failures.len()
+    );
+    for failure in failures.iter().take(10) {
+        println!("  - {}: {}", failure.filename, failure.error);
+    }
+    if failures.len() > 10 {
+        println!("  ... and {} more", failures.len() - 10);
+    }
+    println!();
 }
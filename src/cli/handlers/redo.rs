@@ -0,0 +1,97 @@
+use crate::cli::Command;
+use crate::cli::path_utils::validate_and_normalize_path;
+use crate::error::Result;
+use crate::files::UndoFilter;
+use crate::settings::Config;
+use crate::storage::{SessionLock, UndoLog};
+use colored::*;
+use std::path::PathBuf;
+
+pub async fn handle_redo(command: &Command, download_path: PathBuf) -> Result<()> {
+    let undo_log_path = Config::get_undo_log_path()?;
+
+    if !undo_log_path.exists() {
+        println!("{}", "No undo log found. Nothing to redo.".yellow());
+        return Ok(());
+    }
+
+    let _lock = SessionLock::acquire(&undo_log_path, false)?;
+    let mut undo_log = UndoLog::load_or_create(&undo_log_path, false);
+
+    if undo_log.get_undone_moves().is_empty() {
+        println!("{}", "No undone moves to redo.".yellow());
+        return Ok(());
+    }
+
+    let (dry_run, path, session) = match command {
+        Command::Redo {
+            dry_run,
+            path,
+            session,
+        } => (*dry_run, path, *session),
+        _ => unreachable!(),
+    };
+
+    let session = match session {
+        Some(id) => match undo_log.session(id) {
+            Some(session) => Some(session.clone()),
+            None => {
+                println!(
+                    "{}",
+                    format!(
+                        "No session with ID {}. Run `noentropy history` to list them.",
+                        id
+                    )
+                    .yellow()
+                );
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    // A session is redone relative to the folder it organized
+    let target_path = path
+        .as_ref()
+        .cloned()
+        .or_else(|| session.as_ref().map(|s| s.target_path.clone()))
+        .unwrap_or(download_path);
+
+    let target_path = match validate_and_normalize_path(&target_path).await {
+        Ok(normalized) => normalized,
+        Err(e) => {
+            println!("{}", format!("ERROR: {}", e).red());
+            return Ok(());
+        }
+    };
+
+    let filter = match &session {
+        Some(session) => {
+            println!(
+                "Redoing session {} ({} {}): {}",
+                session.id,
+                session.mode,
+                target_path.display(),
+                session.summary
+            );
+            UndoFilter::session(session.id)
+        }
+        None => UndoFilter::default(),
+    };
+
+    crate::files::redo_moves(&target_path, &mut undo_log, &filter, dry_run)?;
+
+    if let Err(e) = undo_log.save(&undo_log_path) {
+        eprintln!(
+            "{}",
+            format!(
+                "WARNING: Failed to save undo log to '{}': {}. Your undo history may be incomplete.",
+                undo_log_path.display(),
+                e
+            )
+            .yellow()
+        );
+    }
+
+    Ok(())
+}
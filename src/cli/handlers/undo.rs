@@ -1,8 +1,9 @@
 use crate::cli::Command;
 use crate::cli::path_utils::validate_and_normalize_path;
 use crate::error::Result;
+use crate::files::UndoFilter;
 use crate::settings::Config;
-use crate::storage::UndoLog;
+use crate::storage::{SessionLock, UndoLog};
 use colored::*;
 use std::path::PathBuf;
 
@@ -14,6 +15,7 @@ pub async fn handle_undo(command: &Command, download_path: PathBuf) -> Result<()
         return Ok(());
     }
 
+    let _lock = SessionLock::acquire(&undo_log_path, false)?;
     let mut undo_log = UndoLog::load_or_create(&undo_log_path, false);
 
     if !undo_log.has_completed_moves() {
@@ -21,12 +23,70 @@ pub async fn handle_undo(command: &Command, download_path: PathBuf) -> Result<()
         return Ok(());
     }
 
-    let (dry_run, path) = match command {
-        Command::Undo { dry_run, path } => (*dry_run, path),
+    let (dry_run, path, last, session, filter) = match command {
+        Command::Undo {
+            dry_run,
+            path,
+            last,
+            session,
+            source,
+            destination,
+            category,
+            since,
+            until,
+        } => (
+            *dry_run,
+            path,
+            *last,
+            *session,
+            UndoFilter {
+                source: source.clone(),
+                destination: destination.clone(),
+                category: category.clone(),
+                since: *since,
+                until: *until,
+                ..UndoFilter::default()
+            },
+        ),
         _ => unreachable!(),
     };
 
-    let target_path = path.as_ref().cloned().unwrap_or(download_path);
+    let session_id = if last {
+        match undo_log.last_session_id() {
+            Some(id) => Some(id),
+            None => {
+                println!("{}", "No organize sessions to undo.".yellow());
+                return Ok(());
+            }
+        }
+    } else {
+        session
+    };
+
+    let session = match session_id {
+        Some(id) => match undo_log.session(id) {
+            Some(session) => Some(session.clone()),
+            None => {
+                println!(
+                    "{}",
+                    format!(
+                        "No session with ID {}. Run `noentropy history` to list them.",
+                        id
+                    )
+                    .yellow()
+                );
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    // A session is undone relative to the folder it organized
+    let target_path = path
+        .as_ref()
+        .cloned()
+        .or_else(|| session.as_ref().map(|s| s.target_path.clone()))
+        .unwrap_or(download_path);
 
     let target_path = match validate_and_normalize_path(&target_path).await {
         Ok(normalized) => normalized,
@@ -36,7 +96,24 @@ pub async fn handle_undo(command: &Command, download_path: PathBuf) -> Result<()
         }
     };
 
-    crate::files::undo_moves(&target_path, &mut undo_log, dry_run)?;
+    let filter = match &session {
+        Some(session) => {
+            println!(
+                "Undoing session {} ({} {}): {}",
+                session.id,
+                session.mode,
+                target_path.display(),
+                session.summary
+            );
+            UndoFilter {
+                session: Some(session.id),
+                ..filter
+            }
+        }
+        None => filter,
+    };
+
+    crate::files::undo_moves(&target_path, &mut undo_log, &filter, dry_run)?;
 
     if let Err(e) = undo_log.save(&undo_log_path) {
         eprintln!(
@@ -0,0 +1,114 @@
+use crate::cli::Command;
+use crate::error::Result;
+use crate::models::{UsageLimits, UsageTotals};
+use crate::settings::Config;
+use crate::storage::UsageLog;
+use colored::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn handle_usage(command: &Command) -> Result<()> {
+    let days = match command {
+        Command::Usage { days } => (*days).max(1),
+        _ => unreachable!(),
+    };
+
+    let usage_log_path = Config::get_usage_log_path()?;
+    if !usage_log_path.exists() {
+        println!("{}", "No usage recorded yet.".yellow());
+        return Ok(());
+    }
+    let usage_log = UsageLog::load_or_create(&usage_log_path, false)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let since =
+        (now - now % SECONDS_PER_DAY).saturating_sub((days - 1).saturating_mul(SECONDS_PER_DAY));
+
+    let by_day = usage_log.by_day(since);
+    if by_day.is_empty() {
+        println!(
+            "{}",
+            format!("No usage recorded in the last {} day(s).", days).yellow()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "\n{}",
+        format!("--- USAGE BY DAY (last {} days, UTC) ---", days)
+            .bold()
+            .underline()
+    );
+    print_table("Day", &by_day);
+
+    println!("\n{}", "--- USAGE BY FOLDER ---".bold().underline());
+    print_table("Folder", &usage_log.by_folder(since));
+
+    let limits = Config::load_or_default()?.usage_limits;
+    print_limits(&limits, &usage_log.today());
+
+    Ok(())
+}
+
+fn print_table(label: &str, rows: &[(String, UsageTotals)]) {
+    println!(
+        "{:<40} {:>9} {:>10} {:>12} {:>12} {:>10}",
+        label, "Requests", "Cache hits", "Input tok", "Output tok", "Avg ms"
+    );
+
+    let mut total = UsageTotals::default();
+    for (key, totals) in rows {
+        print_row(key, totals);
+        total.merge(totals);
+    }
+    if rows.len() > 1 {
+        print_row("Total", &total);
+    }
+}
+
+fn print_row(key: &str, totals: &UsageTotals) {
+    println!(
+        "{:<40} {:>9} {:>10} {:>12} {:>12} {:>10}",
+        truncate_left(key, 40),
+        totals.requests,
+        totals.cache_hits,
+        totals.prompt_tokens,
+        totals.output_tokens,
+        totals.average_latency_ms()
+    );
+}
+
+fn print_limits(limits: &UsageLimits, today: &UsageTotals) {
+    if limits.is_unlimited() {
+        return;
+    }
+
+    println!("\n{}", "--- DAILY LIMITS ---".bold().underline());
+    if let Some(max) = limits.max_requests_per_day {
+        println!("Requests: {}/{}", today.requests, max);
+    }
+    if let Some(max) = limits.max_tokens_per_day {
+        println!("Tokens:   {}/{}", today.total_tokens, max);
+    }
+    if let Some(details) = limits.exceeded_by(today) {
+        println!(
+            "{} Limit reached ({}). Online runs are paused until tomorrow (UTC).",
+            "WARN:".yellow(),
+            details
+        );
+    }
+}
+
+/// Keeps the end of long folder paths, which is the part that tells them apart.
+pub(super) fn truncate_left(value: &str, width: usize) -> String {
+    let len = value.chars().count();
+    if len <= width {
+        return value.to_string();
+    }
+    let tail: String = value.chars().skip(len - (width - 3)).collect();
+    format!("...{}", tail)
+}
@@ -6,5 +6,8 @@ pub mod path_utils;
 
 pub use args::{Args, Command};
 pub use errors::handle_gemini_error;
-pub use handlers::{handle_offline_organization, handle_online_organization, handle_undo};
+pub use handlers::{
+    handle_cache, handle_discover, handle_history, handle_offline_organization,
+    handle_online_organization, handle_redo, handle_undo, handle_usage,
+};
 pub use orchestrator::handle_organization;
@@ -6,24 +6,39 @@ use crate::error::Result;
 use crate::files::FileBatch;
 use crate::gemini::GeminiClient;
 use crate::settings::{Config, Prompter};
-use crate::storage::{Cache, UndoLog};
+use crate::storage::{Cache, SessionLock, USAGE_LOG_RETENTION_SECONDS, UndoLog, UsageLog};
 use colored::*;
 
-fn initialize_cache() -> Result<(Cache, std::path::PathBuf)> {
+// Each initializer also returns the session lock on its file, which must be
+// held until the file is saved.
+
+fn initialize_cache() -> Result<(Cache, std::path::PathBuf, SessionLock)> {
     const CACHE_RETENTION_SECONDS: u64 = 7 * 24 * 60 * 60;
-    let data_dir = Config::get_data_dir()?;
-    let cache_path = data_dir.join(".noentropy_cache.json");
+    let cache_path = Config::get_cache_path()?;
+    let lock = SessionLock::acquire(&cache_path, false)?;
     let mut cache = Cache::load_or_create(&cache_path, false);
-    cache.cleanup_old_entries(CACHE_RETENTION_SECONDS);
-    Ok((cache, cache_path))
+    let removed = cache.cleanup_old_entries(CACHE_RETENTION_SECONDS);
+    if removed > 0 {
+        println!("Cleaned up {} old cache entries", removed);
+    }
+    Ok((cache, cache_path, lock))
 }
 
-fn initialize_undo_log() -> Result<(UndoLog, std::path::PathBuf)> {
+fn initialize_undo_log() -> Result<(UndoLog, std::path::PathBuf, SessionLock)> {
     const UNDO_LOG_RETENTION_SECONDS: u64 = 30 * 24 * 60 * 60;
     let undo_log_path = Config::get_undo_log_path()?;
+    let lock = SessionLock::acquire(&undo_log_path, false)?;
     let mut undo_log = UndoLog::load_or_create(&undo_log_path, false);
     undo_log.cleanup_old_entries(UNDO_LOG_RETENTION_SECONDS);
-    Ok((undo_log, undo_log_path))
+    Ok((undo_log, undo_log_path, lock))
+}
+
+fn initialize_usage_log() -> Result<(UsageLog, std::path::PathBuf, SessionLock)> {
+    let usage_log_path = Config::get_usage_log_path()?;
+    let lock = SessionLock::acquire(&usage_log_path, false)?;
+    let mut usage_log = UsageLog::load_or_create(&usage_log_path, false)?;
+    usage_log.cleanup_old_entries(USAGE_LOG_RETENTION_SECONDS);
+    Ok((usage_log, usage_log_path, lock))
 }
 
 async fn resolve_target_path(args: &Args, config: &Config) -> Option<std::path::PathBuf> {
@@ -79,15 +94,18 @@ async fn determine_offline_mode(args: &Args, config: &Config) -> Option<bool> {
 
     // Online mode requested - validate API connectivity
     println!("{}", "Checking online mode availability...".cyan());
-    let client = GeminiClient::new(&config.api_key, &config.categories);
-    match client.check_connectivity().await {
+    let connectivity = match GeminiClient::from_config(config) {
+        Ok(client) => client.check_connectivity().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match connectivity {
         Ok(()) => {
             println!("{}", "Online mode enabled.".green());
             Some(false)
         }
         Err(e) => {
             println!("{}", format!("Online mode unavailable: {}", e).yellow());
-            if Prompter::prompt_offline_mode(&e.to_string()) {
+            if Prompter::prompt_offline_mode(&e) {
                 Some(true)
             } else {
                 println!("{}", "Exiting.".yellow());
@@ -98,17 +116,26 @@ async fn determine_offline_mode(args: &Args, config: &Config) -> Option<bool> {
 }
 
 pub async fn handle_organization(args: Args, config: Config) -> Result<()> {
-    let (mut cache, cache_path) = initialize_cache()?;
-    let (mut undo_log, undo_log_path) = initialize_undo_log()?;
+    let (mut cache, cache_path, _cache_lock) = initialize_cache()?;
+    let (mut undo_log, undo_log_path, _undo_log_lock) = initialize_undo_log()?;
 
     let Some(target_path) = resolve_target_path(&args, &config).await else {
         return Ok(());
     };
+    let config = config.for_folder(&target_path);
 
-    let (batch, dry_run) = match &args.command {
+    let (batch, dry_run, deep_inspect) = match &args.command {
         Some(Command::Organize {
-            recursive, dry_run, ..
-        }) => (FileBatch::from_path(&target_path, *recursive), *dry_run),
+            recursive,
+            dry_run,
+            skip_deep_inspect,
+            no_skip_deep_inspect,
+            ..
+        }) => (
+            FileBatch::from_path(&target_path, *recursive),
+            *dry_run,
+            config.should_deep_inspect(*skip_deep_inspect, *no_skip_deep_inspect),
+        ),
         _ => unreachable!(),
     };
 
@@ -124,17 +151,23 @@ pub async fn handle_organization(args: Args, config: Config) -> Result<()> {
     };
 
     let plan = if use_offline {
-        handle_offline_organization(batch, &target_path, dry_run, &mut undo_log)?
+        handle_offline_organization(batch, &target_path, dry_run, deep_inspect, &mut undo_log)?
     } else {
-        handle_online_organization(
+        let (mut usage_log, usage_log_path, _usage_log_lock) = initialize_usage_log()?;
+        let plan = handle_online_organization(
             args.command.as_ref().unwrap(),
             &config,
             batch,
             &target_path,
             &mut cache,
             &mut undo_log,
+            &mut usage_log,
         )
-        .await?
+        .await?;
+        if let Err(e) = usage_log.save(&usage_log_path) {
+            eprintln!("Warning: Failed to save usage log: {}", e);
+        }
+        plan
     };
 
     if let Err(e) = cache.save(&cache_path)
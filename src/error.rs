@@ -29,6 +29,9 @@ pub enum AppError {
     #[error("Undo log error: {0}")]
     UndoLogError(String),
 
+    #[error("Usage log error: {0}")]
+    UsageLogError(String),
+
     #[error("File operation error: {0}")]
     FileOperationError(String),
 
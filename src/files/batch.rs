@@ -41,6 +41,20 @@ impl FileBatch {
         FileBatch { filenames, paths }
     }
 
+    /// Up to `max` filenames spread evenly across the batch in name order,
+    /// so a large folder is represented by more than its first few files.
+    pub fn sample(&self, max: usize) -> Vec<String> {
+        let mut sorted: Vec<&String> = self.filenames.iter().collect();
+        sorted.sort_unstable();
+        if sorted.len() <= max {
+            return sorted.into_iter().cloned().collect();
+        }
+
+        (0..max)
+            .map(|i| sorted[i * sorted.len() / max].clone())
+            .collect()
+    }
+
     pub fn count(&self) -> usize {
         self.filenames.len()
     }
@@ -1,3 +1,4 @@
+use rayon::prelude::*;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::LazyLock;
@@ -5,6 +6,8 @@ use string_interner::{DefaultSymbol, StringInterner};
 
 type Sym = DefaultSymbol;
 
+use super::extract::{has_readable_content, read_content_sample};
+use super::image_meta::{classify_image, read_image_metadata};
 use crate::models::{FileCategory, OrganizationPlan};
 
 const IMAGE_EXTENSIONS: &[&str] = &[
@@ -93,6 +96,112 @@ pub fn categorize_by_extension(filename: &str) -> Option<String> {
         .map(|&sym| EXTENSION_MAP.1.resolve(sym).unwrap().to_string())
 }
 
+/// Characters of document text inspected by the offline content rules.
+const CONTENT_SAMPLE_CHARS: usize = 3000;
+
+/// Keyword rules for sub-categorizing documents offline, checked in order
+/// against the lowercased text. The first rule with a matching keyword wins.
+const CONTENT_RULES: &[(&str, &[&str])] = &[
+    (
+        "Invoices",
+        &[
+            "invoice number",
+            "invoice #",
+            "invoice no",
+            "amount due",
+            "bill to",
+        ],
+    ),
+    (
+        "Receipts",
+        &[
+            "receipt",
+            "thank you for your purchase",
+            "order confirmation",
+        ],
+    ),
+    (
+        "Statements",
+        &[
+            "account statement",
+            "statement period",
+            "opening balance",
+            "closing balance",
+        ],
+    ),
+    (
+        "Taxes",
+        &["tax return", "form w-2", "form 1099", "taxable income"],
+    ),
+    (
+        "Contracts",
+        &[
+            "this agreement",
+            "terms and conditions",
+            "hereinafter",
+            "the parties agree",
+        ],
+    ),
+    (
+        "Resumes",
+        &[
+            "curriculum vitae",
+            "work experience",
+            "professional experience",
+        ],
+    ),
+    (
+        "Tickets",
+        &[
+            "boarding pass",
+            "booking reference",
+            "e-ticket",
+            "itinerary",
+        ],
+    ),
+];
+
+/// Suggests a document sub-category from its text using keyword rules.
+pub fn categorize_by_content(text: &str) -> Option<&'static str> {
+    let text = text.to_lowercase();
+    CONTENT_RULES
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|keyword| text.contains(keyword)))
+        .map(|(sub_category, _)| *sub_category)
+}
+
+/// Fills in sub-categories from file contents: images that look like photos,
+/// screenshots, scans or memes, and, when `read_documents` is set, documents
+/// whose text matches a content rule. Parsing documents can be slow, so it
+/// follows the deep inspection setting. Files that can't be read or match
+/// no rule are left unchanged.
+pub fn apply_content_sub_categories(
+    plan: &mut OrganizationPlan,
+    base_path: &Path,
+    read_documents: bool,
+) {
+    plan.files.par_iter_mut().for_each(|file| {
+        if !file.sub_category.is_empty() {
+            return;
+        }
+        let path = base_path.join(&file.filename);
+        let sub_category = match file.category.as_str() {
+            "Documents" if read_documents && has_readable_content(&path) => {
+                read_content_sample(&path, CONTENT_SAMPLE_CHARS)
+                    .as_deref()
+                    .and_then(categorize_by_content)
+            }
+            "Images" => read_image_metadata(&path)
+                .and_then(|metadata| classify_image(&file.filename, &metadata))
+                .map(|kind| kind.sub_category()),
+            _ => None,
+        };
+        if let Some(sub_category) = sub_category {
+            file.sub_category = sub_category.to_string();
+        }
+    });
+}
+
 /// Result of offline categorization
 pub struct OfflineCategorizationResult {
     pub plan: OrganizationPlan,
@@ -112,6 +221,7 @@ pub fn categorize_files_offline(filenames: Vec<String>) -> OfflineCategorization
                     filename,
                     category,
                     sub_category: String::new(),
+                    ..Default::default()
                 });
             }
             None => {
@@ -121,7 +231,10 @@ pub fn categorize_files_offline(filenames: Vec<String>) -> OfflineCategorization
     }
 
     OfflineCategorizationResult {
-        plan: OrganizationPlan { files },
+        plan: OrganizationPlan {
+            files,
+            ..Default::default()
+        },
         skipped,
     }
 }
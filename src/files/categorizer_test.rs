@@ -69,3 +69,69 @@ fn test_categorize_files_offline() {
     assert!(result.skipped.contains(&"unknown".to_string()));
     assert!(result.skipped.contains(&"file.xyz".to_string()));
 }
+
+#[test]
+fn test_categorize_by_content_keywords() {
+    assert_eq!(
+        categorize_by_content("INVOICE NUMBER: 1234\nAmount due: $50"),
+        Some("Invoices")
+    );
+    assert_eq!(
+        categorize_by_content("Boarding Pass - Gate 12"),
+        Some("Tickets")
+    );
+    assert_eq!(categorize_by_content("Just some notes"), None);
+}
+
+#[test]
+fn test_apply_content_sub_categories_reads_documents_when_enabled() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(dir.path().join("scan.txt"), "Statement period: May").unwrap();
+    std::fs::write(dir.path().join("other.txt"), "shopping list").unwrap();
+
+    let mut plan = OrganizationPlan {
+        files: vec![
+            FileCategory {
+                filename: "scan.txt".to_string(),
+                category: "Documents".to_string(),
+                ..Default::default()
+            },
+            FileCategory {
+                filename: "other.txt".to_string(),
+                category: "Documents".to_string(),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    apply_content_sub_categories(&mut plan, dir.path(), false);
+    assert!(plan.files[0].sub_category.is_empty());
+
+    apply_content_sub_categories(&mut plan, dir.path(), true);
+    assert_eq!(plan.files[0].sub_category, "Statements");
+    assert!(plan.files[1].sub_category.is_empty());
+}
+
+#[test]
+fn test_apply_content_sub_categories_sorts_screenshots() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0DIHDR".to_vec();
+    png.extend_from_slice(&2560u32.to_be_bytes());
+    png.extend_from_slice(&1440u32.to_be_bytes());
+    png.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    std::fs::write(dir.path().join("capture.png"), png).unwrap();
+
+    let mut plan = OrganizationPlan {
+        files: vec![FileCategory {
+            filename: "capture.png".to_string(),
+            category: "Images".to_string(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    apply_content_sub_categories(&mut plan, dir.path(), false);
+
+    assert_eq!(plan.files[0].sub_category, "Screenshots");
+}
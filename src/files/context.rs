@@ -0,0 +1,89 @@
+use super::detector::{detect_mime_type, is_text_file, read_file_sample};
+use crate::models::{FileContext, FileContextFields};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+const SNIPPET_CHARS: usize = 200;
+
+/// Gathers the details enabled in `fields` for a file relative to `base_path`.
+/// Details that can't be read are left out rather than failing.
+pub fn collect_file_context(
+    base_path: &Path,
+    filename: &str,
+    fields: &FileContextFields,
+) -> FileContext {
+    let path = base_path.join(filename);
+    let metadata = fs::metadata(&path).ok();
+
+    let size_bytes = metadata.as_ref().filter(|_| fields.size).map(|m| m.len());
+
+    let modified = metadata
+        .as_ref()
+        .filter(|_| fields.modified)
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| format_date(d.as_secs()));
+
+    let mime_type = fields
+        .mime_type
+        .then(|| detect_mime_type(&path))
+        .flatten()
+        .map(str::to_string);
+
+    let parent_dir = fields
+        .parent_dir
+        .then(|| path.parent()?.file_name())
+        .flatten()
+        .map(|name| name.to_string_lossy().into_owned());
+
+    let snippet = (fields.snippet && is_text_file(&path))
+        .then(|| read_file_sample(&path, SNIPPET_CHARS * 4))
+        .flatten()
+        .map(|content| summarize_snippet(&content))
+        .filter(|snippet| !snippet.is_empty());
+
+    FileContext {
+        filename: filename.to_string(),
+        size_bytes,
+        modified,
+        mime_type,
+        parent_dir,
+        snippet,
+    }
+}
+
+/// Collapses whitespace and truncates content to a short single-line snippet.
+fn summarize_snippet(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(SNIPPET_CHARS)
+        .collect()
+}
+
+/// Formats seconds since the Unix epoch as a `YYYY-MM-DD` UTC date.
+pub fn format_date(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Howard Hinnant's days-to-civil conversion
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+#[path = "context_test.rs"]
+mod tests;
@@ -0,0 +1,83 @@
+use super::*;
+use std::fs::File;
+use std::io::Write;
+
+#[test]
+fn test_format_date() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(951_782_400), "2000-02-29");
+    assert_eq!(format_date(1_735_689_599), "2024-12-31");
+}
+
+fn metadata_fields() -> FileContextFields {
+    FileContextFields {
+        size: true,
+        modified: true,
+        mime_type: true,
+        parent_dir: true,
+        ..FileContextFields::none()
+    }
+}
+
+#[test]
+fn test_collect_file_context_metadata_fields() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut file = File::create(temp_dir.path().join("report.pdf")).unwrap();
+    file.write_all(b"%PDF-1.4 fake").unwrap();
+
+    let context = collect_file_context(temp_dir.path(), "report.pdf", &metadata_fields());
+
+    assert_eq!(context.filename, "report.pdf");
+    assert_eq!(context.size_bytes, Some(13));
+    assert!(context.modified.is_some());
+    assert_eq!(context.mime_type.as_deref(), Some("application/pdf"));
+    assert!(context.parent_dir.is_some());
+    assert!(context.snippet.is_none());
+}
+
+#[test]
+fn test_collect_file_context_no_fields() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    File::create(temp_dir.path().join("a.txt")).unwrap();
+
+    let context = collect_file_context(temp_dir.path(), "a.txt", &FileContextFields::none());
+
+    assert_eq!(
+        context,
+        FileContext {
+            filename: "a.txt".to_string(),
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_collect_file_context_snippet_is_single_line() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(temp_dir.path().join("notes")).unwrap();
+    let mut file = File::create(temp_dir.path().join("notes/todo.md")).unwrap();
+    file.write_all(b"# Todo\n\n- buy milk\n- call bank")
+        .unwrap();
+
+    let fields = FileContextFields {
+        snippet: true,
+        ..FileContextFields::none()
+    };
+    let context = collect_file_context(temp_dir.path(), "notes/todo.md", &fields);
+
+    assert_eq!(
+        context.snippet.as_deref(),
+        Some("# Todo - buy milk - call bank")
+    );
+}
+
+#[test]
+fn test_collect_file_context_missing_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let context = collect_file_context(temp_dir.path(), "missing.bin", &metadata_fields());
+
+    assert!(context.size_bytes.is_none());
+    assert!(context.modified.is_none());
+    assert!(context.mime_type.is_none());
+}
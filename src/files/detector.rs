@@ -31,6 +31,62 @@ pub fn read_file_sample(path: &Path, max_chars: usize) -> Option<String> {
     String::from_utf8(buffer).ok()
 }
 
+/// Leading-byte signatures checked by `detect_mime_type`, as (offset, magic, mime).
+const MAGIC_SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xFF\xD8\xFF", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (8, b"WEBP", "image/webp"),
+    (0, b"BM", "image/bmp"),
+    (0, b"II*\x00", "image/tiff"),
+    (0, b"MM\x00*", "image/tiff"),
+    (8, b"WAVE", "audio/wav"),
+    (8, b"AVI ", "video/x-msvideo"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"OggS", "audio/ogg"),
+    (4, b"ftypM4A", "audio/mp4"),
+    (4, b"ftypqt", "video/quicktime"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"\x1A\x45\xDF\xA3", "video/x-matroska"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1F\x8B", "application/gzip"),
+    (0, b"BZh", "application/x-bzip2"),
+    (0, b"\xFD7zXZ\x00", "application/x-xz"),
+    (0, b"7z\xBC\xAF\x27\x1C", "application/x-7z-compressed"),
+    (0, b"Rar!\x1A\x07", "application/vnd.rar"),
+    (0, b"\x28\xB5\x2F\xFD", "application/zstd"),
+    (0, b"MZ", "application/x-msdownload"),
+    (0, b"\x7FELF", "application/x-elf"),
+    (
+        0,
+        b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1",
+        "application/x-ole-storage",
+    ),
+    (0, b"SQLite format 3\x00", "application/vnd.sqlite3"),
+    (0, b"\x00\x00\x01\x00", "image/x-icon"),
+];
+
+/// Detects a file's MIME type from its leading bytes, falling back to
+/// `text/plain` for readable text files.
+pub fn detect_mime_type(path: &Path) -> Option<&'static str> {
+    use std::io::Read;
+    let mut header = [0u8; 16];
+    let len = fs::File::open(path)
+        .and_then(|mut f| f.read(&mut header))
+        .ok()?;
+    let header = &header[..len];
+
+    let sniffed = MAGIC_SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| header.get(*offset..offset + magic.len()) == Some(*magic))
+        .map(|(_, _, mime)| *mime);
+
+    sniffed.or_else(|| is_text_file(path).then_some("text/plain"))
+}
+
 #[cfg(test)]
 #[path = "detector_test.rs"]
 mod tests;
@@ -69,3 +69,45 @@ fn test_read_file_sample_nonexistent() {
     let content = read_file_sample(Path::new("/nonexistent/file.txt"), 1000);
     assert_eq!(content, None);
 }
+
+#[test]
+fn test_detect_mime_type_by_magic_bytes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cases: &[(&str, &[u8], &str)] = &[
+        ("doc.bin", b"%PDF-1.7\n", "application/pdf"),
+        ("image", b"\x89PNG\r\n\x1a\n\0\0\0\0", "image/png"),
+        ("photo.dat", b"\xFF\xD8\xFF\xE0\0\x10JFIF", "image/jpeg"),
+        ("archive", b"PK\x03\x04\x14\0", "application/zip"),
+        ("setup.exe", b"MZ\x90\0\x03\0", "application/x-msdownload"),
+        ("clip.mp4", b"\0\0\0\x18ftypmp42", "video/mp4"),
+        ("pic.webp", b"RIFF\0\0\0\0WEBPVP8 ", "image/webp"),
+    ];
+
+    for (name, bytes, expected) in cases {
+        let path = temp_dir.path().join(name);
+        File::create(&path).unwrap().write_all(bytes).unwrap();
+        assert_eq!(detect_mime_type(&path), Some(*expected), "{}", name);
+    }
+}
+
+#[test]
+fn test_detect_mime_type_falls_back_to_text() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("notes.md");
+    File::create(&path).unwrap().write_all(b"# Notes").unwrap();
+
+    assert_eq!(detect_mime_type(&path), Some("text/plain"));
+}
+
+#[test]
+fn test_detect_mime_type_unknown_or_missing() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("blob.xyz");
+    File::create(&path)
+        .unwrap()
+        .write_all(b"\x01\x02\x03")
+        .unwrap();
+
+    assert_eq!(detect_mime_type(&path), None);
+    assert_eq!(detect_mime_type(Path::new("/nonexistent/file")), None);
+}
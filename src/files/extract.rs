@@ -0,0 +1,331 @@
+use super::detector::{detect_mime_type, is_text_file, read_file_sample};
+use super::image_meta::read_image_metadata;
+use pdf_extract::PlainTextOutput;
+use std::fs;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// Documents larger than this are not parsed.
+const MAX_DOCUMENT_BYTES: u64 = 50 * 1024 * 1024;
+/// PDFs larger than this are not parsed; the parser loads them whole.
+const MAX_PDF_BYTES: u64 = 16 * 1024 * 1024;
+/// Pages read from the start of a PDF for a text sample.
+const MAX_PDF_PAGES: usize = 5;
+/// Decompressed bytes read from a single archive entry.
+const MAX_ENTRY_BYTES: u64 = 8 * 1024 * 1024;
+/// Decompressed bytes read from all entries of one archive together.
+const MAX_ARCHIVE_BYTES: u64 = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Odt,
+    Epub,
+}
+
+impl DocumentKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "pdf" => Some(Self::Pdf),
+            "docx" => Some(Self::Docx),
+            "odt" => Some(Self::Odt),
+            "epub" => Some(Self::Epub),
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if text can be pulled from the file, either directly or by
+/// extracting it from a supported document format.
+pub fn has_readable_content(path: &Path) -> bool {
+    is_text_file(path) || DocumentKind::from_path(path).is_some()
+}
+
+/// Reads up to `max_chars` of text from a text file or a supported document.
+pub fn read_content_sample(path: &Path, max_chars: usize) -> Option<String> {
+    if is_text_file(path) {
+        return read_file_sample(path, max_chars);
+    }
+    extract_document_text(path, max_chars)
+}
+
+/// Returns true if deep inspection has something to send for the file:
+/// readable text, or metadata from an image header.
+pub fn has_inspectable_content(path: &Path) -> bool {
+    has_readable_content(path) || is_image(path)
+}
+
+/// Reads what deep inspection sends to the AI: a text sample for text files
+/// and documents, or a one-line metadata summary for images.
+pub fn read_inspection_sample(path: &Path, max_chars: usize) -> Option<String> {
+    if has_readable_content(path) {
+        return read_content_sample(path, max_chars);
+    }
+    if is_image(path) {
+        return read_image_metadata(path).map(|m| format!("Image metadata: {}", m.describe()));
+    }
+    None
+}
+
+fn is_image(path: &Path) -> bool {
+    detect_mime_type(path).is_some_and(|mime| mime.starts_with("image/"))
+}
+
+/// Extracts plain text from a PDF, DOCX, ODT or EPUB file, truncated to
+/// `max_chars` characters. Returns `None` for unsupported, oversized,
+/// encrypted or malformed files, and for documents without any text.
+pub fn extract_document_text(path: &Path, max_chars: usize) -> Option<String> {
+    let kind = DocumentKind::from_path(path)?;
+    if fs::metadata(path).ok()?.len() > MAX_DOCUMENT_BYTES {
+        return None;
+    }
+
+    let text = match kind {
+        DocumentKind::Pdf => extract_pdf(path, max_chars)?,
+        DocumentKind::Docx => extract_zip_entries(
+            path,
+            |name| name == "word/document.xml",
+            max_chars,
+            MAX_ARCHIVE_BYTES,
+        )?,
+        DocumentKind::Odt => extract_zip_entries(
+            path,
+            |name| name == "content.xml",
+            max_chars,
+            MAX_ARCHIVE_BYTES,
+        )?,
+        DocumentKind::Epub => {
+            extract_zip_entries(path, is_epub_chapter, max_chars, MAX_ARCHIVE_BYTES)?
+        }
+    };
+
+    let text = collapse_blank_lines(&text);
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.chars().take(max_chars).collect())
+}
+
+fn extract_pdf(path: &Path, max_chars: usize) -> Option<String> {
+    if fs::metadata(path).ok()?.len() > MAX_PDF_BYTES {
+        return None;
+    }
+    let bytes = fs::read(path).ok()?;
+    // The PDF parser panics on some malformed files; treat that as no text
+    panic::catch_unwind(AssertUnwindSafe(|| read_pdf_pages(&bytes, max_chars)))
+        .ok()
+        .flatten()
+}
+
+/// Extracts the text of the first `MAX_PDF_PAGES` pages, stopping early
+/// once `max_chars` characters are collected.
+fn read_pdf_pages(bytes: &[u8], max_chars: usize) -> Option<String> {
+    let mut document = lopdf::Document::load_mem(bytes).ok()?;
+    if document.is_encrypted() && document.decrypt("").is_err() {
+        return None;
+    }
+
+    let mut text = String::new();
+    for page in document.get_pages().into_keys().take(MAX_PDF_PAGES) {
+        let mut output = PlainTextOutput::new(&mut text);
+        if pdf_extract::output_doc_page(&document, &mut output, page).is_err() {
+            break;
+        }
+        if text.chars().count() >= max_chars {
+            break;
+        }
+    }
+    Some(text)
+}
+
+/// Concatenates the text of the archive entries matching `wanted`, in
+/// archive order. Stops once `max_chars` characters of text are collected
+/// or `max_bytes` bytes have been decompressed, so a book with hundreds of
+/// chapters is not read in full for a short sample.
+fn extract_zip_entries<F>(
+    path: &Path,
+    wanted: F,
+    max_chars: usize,
+    max_bytes: u64,
+) -> Option<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let file = fs::File::open(path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let mut text = String::new();
+    let mut chars = 0;
+    let mut remaining = max_bytes;
+    let mut found = false;
+
+    for index in 0..archive.len() {
+        if chars >= max_chars || remaining == 0 {
+            break;
+        }
+        let Ok(entry) = archive.by_index(index) else {
+            continue;
+        };
+        if !wanted(entry.name()) {
+            continue;
+        }
+        found = true;
+
+        let mut xml = Vec::new();
+        let limit = MAX_ENTRY_BYTES.min(remaining);
+        let Ok(read) = entry.take(limit).read_to_end(&mut xml) else {
+            continue;
+        };
+        remaining -= read as u64;
+        // An entry cut off by the limit may end mid-character
+        let entry_text = collapse_blank_lines(&xml_to_text(&String::from_utf8_lossy(&xml)));
+        if !entry_text.is_empty() {
+            chars += entry_text.chars().count();
+            text.push_str(&entry_text);
+            text.push('\n');
+        }
+    }
+
+    found.then_some(text)
+}
+
+fn is_epub_chapter(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let is_markup =
+        lower.ends_with(".xhtml") || lower.ends_with(".html") || lower.ends_with(".htm");
+    let file_name = lower.rsplit('/').next().unwrap_or(&lower);
+    is_markup && !file_name.starts_with("nav") && !file_name.starts_with("toc")
+}
+
+/// Tags after which a line break keeps words from running together.
+const BLOCK_TAGS: &[&str] = &[
+    "w:p",
+    "w:br",
+    "w:tab",
+    "text:p",
+    "text:h",
+    "text:line-break",
+    "text:tab",
+    "p",
+    "br",
+    "div",
+    "li",
+    "tr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "title",
+];
+
+/// Strips markup from an XML or XHTML document, keeping the character data
+/// and breaking lines at paragraph-like elements.
+pub(crate) fn xml_to_text(xml: &str) -> String {
+    let mut text = String::with_capacity(xml.len() / 4);
+    let mut rest = xml;
+    let mut skip_depth = 0usize;
+
+    while let Some(start) = rest.find('<') {
+        if skip_depth == 0 {
+            text.push_str(&decode_entities(&rest[..start]));
+        }
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        if matches!(name.as_str(), "script" | "style" | "head") && !tag.ends_with('/') {
+            if closing {
+                skip_depth = skip_depth.saturating_sub(1);
+            } else {
+                skip_depth += 1;
+            }
+            continue;
+        }
+
+        if BLOCK_TAGS.contains(&name.as_str()) && (closing || tag.ends_with('/')) {
+            text.push('\n');
+        }
+    }
+    if skip_depth == 0 {
+        text.push_str(&decode_entities(rest));
+    }
+
+    text
+}
+
+fn decode_entities(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_string();
+    }
+
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find(';').filter(|&end| end <= 10) else {
+            decoded.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let entity = &rest[1..end];
+        let replacement = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                .and_then(char::from_u32),
+        };
+
+        match replacement {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Trims every line and drops empty ones.
+fn collapse_blank_lines(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+#[path = "extract_test.rs"]
+mod tests;
@@ -0,0 +1,313 @@
+use super::*;
+use std::io::Write;
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+
+fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+    let file = fs::File::create(path).unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    for (name, content) in entries {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+/// Builds a one-page PDF showing `text`, with a correct cross-reference table.
+fn minimal_pdf(text: &str) -> Vec<u8> {
+    let stream = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R \
+         /Resources << /Font << /F1 5 0 R >> >> >>"
+            .to_string(),
+        format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            stream.len(),
+            stream
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+/// Builds a PDF with one page per entry of `texts`, each showing its text.
+fn pages_pdf(texts: &[String]) -> Vec<u8> {
+    use lopdf::{Object, Stream, dictionary};
+
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+
+    let kids: Vec<Object> = texts
+        .iter()
+        .map(|text| {
+            let content = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+            let content = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                "Contents" => content,
+                "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            })
+            .into()
+        })
+        .collect();
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => texts.len() as i64,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut pdf = Vec::new();
+    doc.save_to(&mut pdf).unwrap();
+    pdf
+}
+
+#[test]
+fn test_document_kind_from_path() {
+    assert_eq!(
+        DocumentKind::from_path(Path::new("a.PDF")),
+        Some(DocumentKind::Pdf)
+    );
+    assert_eq!(
+        DocumentKind::from_path(Path::new("a.docx")),
+        Some(DocumentKind::Docx)
+    );
+    assert_eq!(DocumentKind::from_path(Path::new("a.doc")), None);
+    assert_eq!(DocumentKind::from_path(Path::new("noext")), None);
+}
+
+#[test]
+fn test_has_readable_content() {
+    assert!(has_readable_content(Path::new("notes.txt")));
+    assert!(has_readable_content(Path::new("book.epub")));
+    assert!(!has_readable_content(Path::new("photo.jpg")));
+}
+
+#[test]
+fn test_extract_docx_text() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("letter.docx");
+    write_zip(
+        &path,
+        &[
+            ("[Content_Types].xml", "<Types/>"),
+            (
+                "word/document.xml",
+                r#"<?xml version="1.0"?><w:document><w:body><w:p><w:r><w:t>Invoice</w:t></w:r><w:r><w:t xml:space="preserve"> #42</w:t></w:r></w:p><w:p><w:r><w:t>Total &amp; tax</w:t></w:r></w:p></w:body></w:document>"#,
+            ),
+        ],
+    );
+
+    let text = extract_document_text(&path, 1000).unwrap();
+    assert_eq!(text, "Invoice #42\nTotal & tax");
+}
+
+#[test]
+fn test_extract_odt_text() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("notes.odt");
+    write_zip(
+        &path,
+        &[
+            ("mimetype", "application/vnd.oasis.opendocument.text"),
+            (
+                "content.xml",
+                "<office:document-content><office:body><office:text><text:h>Meeting</text:h><text:p>Agenda items</text:p></office:text></office:body></office:document-content>",
+            ),
+        ],
+    );
+
+    let text = extract_document_text(&path, 1000).unwrap();
+    assert_eq!(text, "Meeting\nAgenda items");
+}
+
+#[test]
+fn test_extract_epub_skips_navigation_and_markup() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("book.epub");
+    write_zip(
+        &path,
+        &[
+            ("mimetype", "application/epub+zip"),
+            (
+                "OEBPS/nav.xhtml",
+                "<html><body><p>Contents</p></body></html>",
+            ),
+            (
+                "OEBPS/chapter1.xhtml",
+                "<html><head><title>Ch 1</title><style>p { color: red }</style></head><body><h1>Chapter One</h1><p>It was a dark night.</p></body></html>",
+            ),
+        ],
+    );
+
+    let text = extract_document_text(&path, 1000).unwrap();
+    assert_eq!(text, "Chapter One\nIt was a dark night.");
+}
+
+fn chapters(count: usize) -> Vec<(String, String)> {
+    (1..=count)
+        .map(|i| {
+            (
+                format!("OEBPS/chapter{:03}.xhtml", i),
+                format!("<html><body><p>Chapter {} text.</p></body></html>", i),
+            )
+        })
+        .collect()
+}
+
+fn write_book(path: &Path, chapters: &[(String, String)]) {
+    let entries: Vec<(&str, &str)> = chapters
+        .iter()
+        .map(|(name, body)| (name.as_str(), body.as_str()))
+        .collect();
+    write_zip(path, &entries);
+}
+
+#[test]
+fn test_extract_zip_stops_once_enough_text_is_collected() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("book.epub");
+    write_book(&path, &chapters(300));
+
+    let text = extract_zip_entries(&path, is_epub_chapter, 30, MAX_ARCHIVE_BYTES).unwrap();
+    assert_eq!(text, "Chapter 1 text.\nChapter 2 text.\n");
+}
+
+#[test]
+fn test_extract_zip_caps_total_decompressed_bytes() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("book.epub");
+    let book = chapters(10);
+    write_book(&path, &book);
+
+    // Room for the first two chapters only
+    let budget = (book[0].1.len() + book[1].1.len()) as u64;
+    let text = extract_zip_entries(&path, is_epub_chapter, usize::MAX, budget).unwrap();
+    assert_eq!(text, "Chapter 1 text.\nChapter 2 text.\n");
+}
+
+#[test]
+fn test_extract_pdf_text() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("report.pdf");
+    fs::write(&path, minimal_pdf("Quarterly Report")).unwrap();
+
+    let text = extract_document_text(&path, 1000).unwrap();
+    assert!(text.contains("Quarterly Report"), "got: {:?}", text);
+}
+
+#[test]
+fn test_extract_pdf_reads_only_the_first_pages() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("manual.pdf");
+    let texts: Vec<String> = (1..=MAX_PDF_PAGES + 3)
+        .map(|page| format!("Marker{:02}", page))
+        .collect();
+    fs::write(&path, pages_pdf(&texts)).unwrap();
+
+    let text = extract_document_text(&path, 10_000).unwrap();
+
+    assert!(text.contains(&texts[MAX_PDF_PAGES - 1]), "got: {:?}", text);
+    assert!(!text.contains(&texts[MAX_PDF_PAGES]), "got: {:?}", text);
+}
+
+#[test]
+fn test_extract_respects_max_chars() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("long.docx");
+    let body = format!("<w:p><w:t>{}</w:t></w:p>", "word ".repeat(500));
+    write_zip(&path, &[("word/document.xml", &body)]);
+
+    let text = extract_document_text(&path, 20).unwrap();
+    assert_eq!(text.chars().count(), 20);
+}
+
+#[test]
+fn test_extract_malformed_documents_return_none() {
+    let dir = TempDir::new().unwrap();
+    let pdf = dir.path().join("broken.pdf");
+    let docx = dir.path().join("broken.docx");
+    fs::write(&pdf, b"%PDF-1.4 not really a pdf").unwrap();
+    fs::write(&docx, b"not a zip").unwrap();
+
+    assert!(extract_document_text(&pdf, 100).is_none());
+    assert!(extract_document_text(&docx, 100).is_none());
+}
+
+#[test]
+fn test_read_content_sample_dispatches_by_type() {
+    let dir = TempDir::new().unwrap();
+    let txt = dir.path().join("plain.txt");
+    fs::write(&txt, "plain text").unwrap();
+    let docx = dir.path().join("doc.docx");
+    write_zip(
+        &docx,
+        &[("word/document.xml", "<w:p><w:t>from docx</w:t></w:p>")],
+    );
+
+    assert_eq!(read_content_sample(&txt, 100).unwrap(), "plain text");
+    assert_eq!(read_content_sample(&docx, 100).unwrap(), "from docx");
+    assert!(read_content_sample(&dir.path().join("photo.jpg"), 100).is_none());
+}
+
+#[test]
+fn test_xml_to_text_decodes_entities() {
+    assert_eq!(
+        xml_to_text("<p>a &lt;b&gt; &#65;&#x42; &unknown; &</p>"),
+        "a <b> AB &unknown; &\n"
+    );
+}
+
+#[test]
+fn test_read_inspection_sample_describes_images() {
+    let dir = TempDir::new().unwrap();
+    let png = dir.path().join("shot.png");
+    let mut bytes = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0DIHDR".to_vec();
+    bytes.extend_from_slice(&1920u32.to_be_bytes());
+    bytes.extend_from_slice(&1080u32.to_be_bytes());
+    bytes.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    fs::write(&png, bytes).unwrap();
+
+    assert!(has_inspectable_content(&png));
+    let sample = read_inspection_sample(&png, 1000).unwrap();
+    assert!(sample.starts_with("Image metadata: dimensions: 1920x1080"));
+}
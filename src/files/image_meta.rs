@@ -0,0 +1,252 @@
+use exif::{In, Tag, Value};
+use std::fs;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Common screen resolutions, as (width, height) in landscape orientation.
+/// Screenshots from HiDPI displays are matched at 2x and 3x as well.
+const SCREEN_SIZES: &[(u32, u32)] = &[
+    (1280, 720),
+    (1280, 800),
+    (1366, 768),
+    (1440, 900),
+    (1536, 864),
+    (1600, 900),
+    (1680, 1050),
+    (1920, 1080),
+    (1920, 1200),
+    (2560, 1080),
+    (2560, 1440),
+    (2560, 1600),
+    (3440, 1440),
+    (3840, 2160),
+    (568, 320),
+    (667, 375),
+    (736, 414),
+    (812, 375),
+    (844, 390),
+    (852, 393),
+    (896, 414),
+    (926, 428),
+    (932, 430),
+    (1024, 768),
+    (1180, 820),
+    (1194, 834),
+    (1366, 1024),
+    (800, 360),
+    (915, 412),
+];
+
+/// Scanner makers and software that appear in EXIF Make/Model/Software.
+const SCANNER_HINTS: &[&str] = &[
+    "scan",
+    "canoscan",
+    "perfection",
+    "fujitsu",
+    "fi-",
+    "docucentre",
+];
+
+/// Resolution at or above which an image without camera details is treated
+/// as a scan.
+const SCAN_MIN_DPI: f64 = 200.0;
+
+/// Longest side range, in pixels, for images treated as memes.
+const MEME_SIDE_RANGE: (u32, u32) = (200, 1280);
+
+/// Formats memes are usually shared in; screenshots tend to be PNG.
+const MEME_EXTENSIONS: &[&str] = &["jpg", "jpeg", "gif", "webp"];
+
+/// Details read from an image's header and EXIF block.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageMetadata {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub camera: Option<String>,
+    pub software: Option<String>,
+    /// Capture date as `YYYY-MM-DD`
+    pub captured: Option<String>,
+    pub has_gps: bool,
+    pub has_exif: bool,
+    pub dpi: Option<f64>,
+}
+
+/// What kind of picture an image appears to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    Photo,
+    Screenshot,
+    Scan,
+    Meme,
+}
+
+impl ImageKind {
+    pub fn sub_category(&self) -> &'static str {
+        match self {
+            ImageKind::Photo => "Photos",
+            ImageKind::Screenshot => "Screenshots",
+            ImageKind::Scan => "Scans",
+            ImageKind::Meme => "Memes",
+        }
+    }
+}
+
+impl ImageMetadata {
+    /// Summarizes the metadata on one line for the AI prompt.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let (Some(w), Some(h)) = (self.width, self.height) {
+            parts.push(format!("dimensions: {}x{}", w, h));
+        }
+        if let Some(camera) = &self.camera {
+            parts.push(format!("camera: {}", camera));
+        }
+        if let Some(software) = &self.software {
+            parts.push(format!("software: {}", software));
+        }
+        if let Some(captured) = &self.captured {
+            parts.push(format!("captured: {}", captured));
+        }
+        if let Some(dpi) = self.dpi {
+            parts.push(format!("resolution: {:.0} dpi", dpi));
+        }
+        parts.push(format!("gps: {}", if self.has_gps { "yes" } else { "no" }));
+        parts.push(format!(
+            "exif: {}",
+            if self.has_exif { "yes" } else { "no" }
+        ));
+        parts.join(", ")
+    }
+
+    fn longest_side(&self) -> Option<u32> {
+        Some(self.width?.max(self.height?))
+    }
+
+    fn is_screen_sized(&self) -> bool {
+        let (Some(w), Some(h)) = (self.width, self.height) else {
+            return false;
+        };
+        let (long, short) = (w.max(h), w.min(h));
+        SCREEN_SIZES
+            .iter()
+            .any(|&(sw, sh)| (1..=3).any(|scale| long == sw * scale && short == sh * scale))
+    }
+
+    fn mentions_scanner(&self) -> bool {
+        [&self.camera, &self.software]
+            .into_iter()
+            .flatten()
+            .map(|value| value.to_lowercase())
+            .any(|value| SCANNER_HINTS.iter().any(|hint| value.contains(hint)))
+    }
+}
+
+/// Reads dimensions and EXIF details from a JPEG, PNG, WebP, TIFF or HEIF
+/// image. Returns `None` if the file isn't a readable image.
+pub fn read_image_metadata(path: &Path) -> Option<ImageMetadata> {
+    let size = imagesize::size(path).ok();
+    let exif = fs::File::open(path).ok().and_then(|file| {
+        exif::Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .ok()
+    });
+
+    if size.is_none() && exif.is_none() {
+        return None;
+    }
+
+    let mut metadata = ImageMetadata {
+        width: size.map(|s| s.width as u32),
+        height: size.map(|s| s.height as u32),
+        ..Default::default()
+    };
+
+    if let Some(exif) = exif {
+        let ascii = |tag| {
+            exif.get_field(tag, In::PRIMARY)
+                .and_then(|f| ascii_value(&f.value))
+        };
+
+        metadata.has_exif = true;
+        metadata.camera = match (ascii(Tag::Make), ascii(Tag::Model)) {
+            (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+            (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+            (make, model) => make.or(model),
+        };
+        metadata.software = ascii(Tag::Software);
+        metadata.captured = ascii(Tag::DateTimeOriginal)
+            .or_else(|| ascii(Tag::DateTime))
+            .and_then(|value| exif_date(&value));
+        metadata.has_gps = exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_some();
+        metadata.dpi = exif
+            .get_field(Tag::XResolution, In::PRIMARY)
+            .and_then(|f| match &f.value {
+                Value::Rational(values) => values.first().map(|r| r.to_f64()),
+                _ => None,
+            });
+    }
+
+    Some(metadata)
+}
+
+/// Classifies an image using its metadata and filename. Returns `None` when
+/// there's too little to go on.
+pub fn classify_image(filename: &str, metadata: &ImageMetadata) -> Option<ImageKind> {
+    let name = filename.to_lowercase();
+
+    if name.contains("screenshot") || name.contains("screen shot") {
+        return Some(ImageKind::Screenshot);
+    }
+    if metadata.mentions_scanner() || name.starts_with("scan") {
+        return Some(ImageKind::Scan);
+    }
+    if metadata.camera.is_some() || metadata.has_gps {
+        return Some(ImageKind::Photo);
+    }
+    if metadata.is_screen_sized() {
+        return Some(ImageKind::Screenshot);
+    }
+    if metadata.dpi.is_some_and(|dpi| dpi >= SCAN_MIN_DPI) {
+        return Some(ImageKind::Scan);
+    }
+    let extension = Path::new(&name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    if !metadata.has_exif
+        && MEME_EXTENSIONS.contains(&extension)
+        && metadata
+            .longest_side()
+            .is_some_and(|side| (MEME_SIDE_RANGE.0..=MEME_SIDE_RANGE.1).contains(&side))
+    {
+        return Some(ImageKind::Meme);
+    }
+    None
+}
+
+fn ascii_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Ascii(values) => values
+            .first()
+            .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+            .filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+/// Converts an EXIF `YYYY:MM:DD HH:MM:SS` timestamp to `YYYY-MM-DD`.
+fn exif_date(value: &str) -> Option<String> {
+    let date = value.get(..10)?;
+    let parts: Vec<&str> = date.split(':').collect();
+    if parts.len() != 3 || !parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    if parts.iter().all(|p| p.chars().all(|c| c == '0')) {
+        return None;
+    }
+    Some(parts.join("-"))
+}
+
+#[cfg(test)]
+#[path = "image_meta_test.rs"]
+mod tests;
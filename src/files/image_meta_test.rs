@@ -0,0 +1,201 @@
+use super::*;
+use tempfile::TempDir;
+
+struct IfdEntry {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    data: Vec<u8>,
+}
+
+fn ascii(tag: u16, value: &str) -> IfdEntry {
+    let mut data = value.as_bytes().to_vec();
+    data.push(0);
+    IfdEntry {
+        tag,
+        kind: 2,
+        count: data.len() as u32,
+        data,
+    }
+}
+
+/// Builds a little-endian TIFF block with one IFD, plus a GPS IFD when
+/// `with_gps` is set.
+fn tiff(entries: &[IfdEntry], with_gps: bool) -> Vec<u8> {
+    let entry_count = entries.len() + usize::from(with_gps);
+    let ifd_size = 2 + entry_count * 12 + 4;
+    let mut data_offset = 8 + ifd_size;
+    let mut out = b"II\x2A\x00\x08\x00\x00\x00".to_vec();
+    let mut data_area = Vec::new();
+
+    out.extend_from_slice(&(entry_count as u16).to_le_bytes());
+    for entry in entries {
+        out.extend_from_slice(&entry.tag.to_le_bytes());
+        out.extend_from_slice(&entry.kind.to_le_bytes());
+        out.extend_from_slice(&entry.count.to_le_bytes());
+        if entry.data.len() <= 4 {
+            let mut inline = entry.data.clone();
+            inline.resize(4, 0);
+            out.extend_from_slice(&inline);
+        } else {
+            out.extend_from_slice(&(data_offset as u32).to_le_bytes());
+            data_area.extend_from_slice(&entry.data);
+            data_offset += entry.data.len();
+        }
+    }
+    if with_gps {
+        out.extend_from_slice(&0x8825u16.to_le_bytes());
+        out.extend_from_slice(&4u16.to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&(data_offset as u32).to_le_bytes());
+    }
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&data_area);
+
+    if with_gps {
+        // GPS IFD with a single GPSLatitude entry of three rationals
+        let rationals_offset = data_offset + 2 + 12 + 4;
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&5u16.to_le_bytes());
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&(rationals_offset as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        for value in [48u32, 51, 30] {
+            out.extend_from_slice(&value.to_le_bytes());
+            out.extend_from_slice(&1u32.to_le_bytes());
+        }
+    }
+    out
+}
+
+fn jpeg(width: u16, height: u16, exif: Option<Vec<u8>>) -> Vec<u8> {
+    let mut out = vec![0xFF, 0xD8];
+    if let Some(tiff) = exif {
+        let mut payload = b"Exif\x00\x00".to_vec();
+        payload.extend_from_slice(&tiff);
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        out.extend_from_slice(&payload);
+    }
+    out.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x11, 0x08]);
+    out.extend_from_slice(&height.to_be_bytes());
+    out.extend_from_slice(&width.to_be_bytes());
+    out.extend_from_slice(&[0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01]);
+    out.extend_from_slice(&[0xFF, 0xD9]);
+    out
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut out = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0DIHDR".to_vec();
+    out.extend_from_slice(&width.to_be_bytes());
+    out.extend_from_slice(&height.to_be_bytes());
+    out.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    out
+}
+
+fn write(dir: &TempDir, name: &str, bytes: &[u8]) -> std::path::PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn test_reads_camera_date_and_gps_from_jpeg() {
+    let dir = TempDir::new().unwrap();
+    let exif = tiff(
+        &[
+            ascii(0x10F, "Apple"),
+            ascii(0x110, "iPhone 12"),
+            ascii(0x132, "2023:05:01 10:20:30"),
+        ],
+        true,
+    );
+    let path = write(&dir, "IMG_0001.jpg", &jpeg(4032, 3024, Some(exif)));
+
+    let metadata = read_image_metadata(&path).unwrap();
+
+    assert_eq!(metadata.width, Some(4032));
+    assert_eq!(metadata.height, Some(3024));
+    assert_eq!(metadata.camera.as_deref(), Some("Apple iPhone 12"));
+    assert_eq!(metadata.captured.as_deref(), Some("2023-05-01"));
+    assert!(metadata.has_gps);
+    assert_eq!(
+        classify_image("IMG_0001.jpg", &metadata),
+        Some(ImageKind::Photo)
+    );
+}
+
+#[test]
+fn test_screen_sized_png_is_screenshot() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "image.png", &png(2880, 1800));
+
+    let metadata = read_image_metadata(&path).unwrap();
+
+    assert!(!metadata.has_exif);
+    assert_eq!(
+        classify_image("image.png", &metadata),
+        Some(ImageKind::Screenshot)
+    );
+}
+
+#[test]
+fn test_scanner_software_is_scan() {
+    let dir = TempDir::new().unwrap();
+    let exif = tiff(&[ascii(0x131, "EPSON Scan")], false);
+    let path = write(&dir, "page.jpg", &jpeg(2480, 3508, Some(exif)));
+
+    let metadata = read_image_metadata(&path).unwrap();
+
+    assert_eq!(classify_image("page.jpg", &metadata), Some(ImageKind::Scan));
+}
+
+#[test]
+fn test_small_jpeg_without_exif_is_meme() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "funny.jpg", &jpeg(640, 640, None));
+
+    let metadata = read_image_metadata(&path).unwrap();
+
+    assert_eq!(
+        classify_image("funny.jpg", &metadata),
+        Some(ImageKind::Meme)
+    );
+    assert_eq!(classify_image("funny.png", &metadata), None);
+}
+
+#[test]
+fn test_filename_hints_take_precedence() {
+    let metadata = ImageMetadata {
+        camera: Some("Canon EOS".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        classify_image("Screenshot 2024-01-01.png", &metadata),
+        Some(ImageKind::Screenshot)
+    );
+}
+
+#[test]
+fn test_non_image_returns_none() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "notes.jpg", b"not an image");
+
+    assert!(read_image_metadata(&path).is_none());
+}
+
+#[test]
+fn test_describe_lists_known_fields() {
+    let metadata = ImageMetadata {
+        width: Some(100),
+        height: Some(50),
+        camera: Some("Pixel 7".to_string()),
+        has_exif: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        metadata.describe(),
+        "dimensions: 100x50, camera: Pixel 7, gps: no, exif: yes"
+    );
+}
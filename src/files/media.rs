@@ -0,0 +1,218 @@
+use super::detector::detect_mime_type;
+use super::duplicate::duplicate_detector::compute_file_hash;
+use super::extract::{
+    DocumentKind, extract_document_text, has_inspectable_content, read_inspection_sample,
+};
+use super::image_meta::read_image_metadata;
+use crate::models::{FileCategory, MultimodalConfig};
+use image::ImageReader;
+use image::codecs::jpeg::JpegEncoder;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// JPEG quality used when re-encoding downscaled images.
+const JPEG_QUALITY: u8 = 80;
+
+/// An image or document page ready to be attached to a request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPart {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl MediaPart {
+    /// Bytes the attachment takes up in a request once base64-encoded.
+    pub fn encoded_len(&self) -> u64 {
+        (self.data.len() as u64).div_ceil(3) * 4
+    }
+}
+
+/// What deep inspection sends to the AI for a single file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InspectionInput {
+    /// A text sample, or a metadata summary for images.
+    Text(String),
+    /// An attachment, with any text details to send alongside it.
+    Media {
+        part: MediaPart,
+        details: Option<String>,
+    },
+}
+
+/// A planned file and what deep inspection read from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectionTarget {
+    pub filename: String,
+    pub category: String,
+    pub input: InspectionInput,
+    /// blake3 hash of the file's contents, used to cache its sub-category.
+    pub content_hash: Option<String>,
+}
+
+/// Attachment bytes left to send in the current run, shared between clones.
+#[derive(Debug, Clone)]
+pub struct MediaBudget {
+    remaining: Arc<AtomicU64>,
+}
+
+impl MediaBudget {
+    pub fn new(bytes: u64) -> Self {
+        Self {
+            remaining: Arc::new(AtomicU64::new(bytes)),
+        }
+    }
+
+    /// Takes `bytes` from the budget. Returns false, leaving the budget
+    /// untouched, if there isn't enough left.
+    pub fn try_reserve(&self, bytes: u64) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                left.checked_sub(bytes)
+            })
+            .is_ok()
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.remaining.load(Ordering::SeqCst)
+    }
+}
+
+/// Reads what deep inspection sends for a file. With multimodal inspection
+/// enabled, images are attached downscaled, and PDFs without extractable
+/// text (usually scans) are attached as their first page. Everything else,
+/// and every attachment once `budget` is used up, falls back to the text
+/// sample.
+pub fn read_inspection_input(
+    path: &Path,
+    max_chars: usize,
+    multimodal: Option<&MultimodalConfig>,
+    budget: &MediaBudget,
+) -> Option<InspectionInput> {
+    if let Some(config) = multimodal.filter(|c| c.enabled && budget.remaining() > 0) {
+        if is_image(path)
+            && let Some(part) = prepare_image(path, config)
+        {
+            let details =
+                read_image_metadata(path).map(|m| format!("Image metadata: {}", m.describe()));
+            return Some(InspectionInput::Media { part, details });
+        }
+        if DocumentKind::from_path(path) == Some(DocumentKind::Pdf) {
+            // Parsing a PDF is slow, so the text found here is the sample
+            if let Some(text) = extract_document_text(path, max_chars) {
+                return Some(InspectionInput::Text(text));
+            }
+            return prepare_pdf_first_page(path, config).map(|part| InspectionInput::Media {
+                part,
+                details: None,
+            });
+        }
+    }
+    read_inspection_sample(path, max_chars).map(InspectionInput::Text)
+}
+
+/// Reads inspection inputs for the planned files in parallel. Files without
+/// a known path or without anything to inspect are left out.
+pub fn read_inspection_targets(
+    files: &[FileCategory],
+    paths: &HashMap<String, PathBuf>,
+    max_chars: usize,
+    multimodal: Option<&MultimodalConfig>,
+    budget: &MediaBudget,
+) -> Vec<InspectionTarget> {
+    files
+        .par_iter()
+        .filter_map(|file| {
+            let path = paths.get(&file.filename)?;
+            if !has_inspectable_content(path) {
+                return None;
+            }
+            let input = read_inspection_input(path, max_chars, multimodal, budget)?;
+            Some(InspectionTarget {
+                filename: file.filename.clone(),
+                category: file.category.clone(),
+                input,
+                content_hash: compute_file_hash(path)
+                    .ok()
+                    .map(|hash| hash.to_hex().to_string()),
+            })
+        })
+        .collect()
+}
+
+fn is_image(path: &Path) -> bool {
+    detect_mime_type(path).is_some_and(|mime| mime.starts_with("image/"))
+}
+
+fn within_file_cap(path: &Path, config: &MultimodalConfig) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.len() <= config.max_file_bytes())
+}
+
+fn within_attachment_cap(data: &[u8], config: &MultimodalConfig) -> bool {
+    data.len() as u64 <= config.max_attachment_bytes()
+}
+
+/// Decodes an image, shrinks it to fit `max_image_dimension` and re-encodes
+/// it as JPEG.
+fn prepare_image(path: &Path, config: &MultimodalConfig) -> Option<MediaPart> {
+    if !within_file_cap(path, config) {
+        return None;
+    }
+
+    let image = ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()?;
+    let max = config.max_image_dimension.max(1);
+    let image = if image.width() > max || image.height() > max {
+        image.thumbnail(max, max)
+    } else {
+        image
+    };
+
+    let mut data = Vec::new();
+    JpegEncoder::new_with_quality(Cursor::new(&mut data), JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .ok()?;
+
+    within_attachment_cap(&data, config).then(|| MediaPart {
+        mime_type: "image/jpeg".to_string(),
+        data,
+    })
+}
+
+/// Rewrites a PDF so it contains only its first page.
+fn prepare_pdf_first_page(path: &Path, config: &MultimodalConfig) -> Option<MediaPart> {
+    if !within_file_cap(path, config) {
+        return None;
+    }
+
+    let mut document = lopdf::Document::load(path).ok()?;
+    if document.is_encrypted() {
+        return None;
+    }
+
+    let later_pages: Vec<u32> = document.get_pages().keys().copied().skip(1).collect();
+    if !later_pages.is_empty() {
+        document.delete_pages(&later_pages);
+        document.prune_objects();
+    }
+
+    let mut data = Vec::new();
+    document.save_to(&mut data).ok()?;
+
+    within_attachment_cap(&data, config).then(|| MediaPart {
+        mime_type: "application/pdf".to_string(),
+        data,
+    })
+}
+
+#[cfg(test)]
+#[path = "media_test.rs"]
+mod tests;
@@ -0,0 +1,265 @@
+use super::*;
+use image::{ImageFormat, Rgb, RgbImage};
+use lopdf::{Object, Stream, dictionary};
+use tempfile::TempDir;
+
+fn enabled() -> MultimodalConfig {
+    MultimodalConfig {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+fn unlimited() -> MediaBudget {
+    MediaBudget::new(u64::MAX)
+}
+
+fn write_png(path: &Path, width: u32, height: u32) {
+    let image = RgbImage::from_fn(width, height, |x, y| {
+        Rgb([(x % 256) as u8, (y % 256) as u8, 90])
+    });
+    image.save_with_format(path, ImageFormat::Png).unwrap();
+}
+
+/// Builds a PDF whose pages draw a rectangle each and contain no text, like
+/// a scan.
+fn write_pdf_without_text(path: &Path, pages: usize) {
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+
+    let kids: Vec<Object> = (0..pages)
+        .map(|_| {
+            let content = doc.add_object(Stream::new(
+                dictionary! {},
+                b"0 0 1 rg 10 10 100 100 re f".to_vec(),
+            ));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                "Contents" => content,
+            })
+            .into()
+        })
+        .collect();
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => pages as i64,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).unwrap();
+}
+
+/// Builds a one-page PDF with `text` on it.
+fn write_pdf_with_text(path: &Path, text: &str) {
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let content = doc.add_object(Stream::new(
+        dictionary! {},
+        format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text).into_bytes(),
+    ));
+    let page = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        "Contents" => content,
+        "Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page.into()],
+            "Count" => 1,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).unwrap();
+}
+
+#[test]
+fn test_disabled_multimodal_sends_image_metadata() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("photo.png");
+    write_png(&path, 64, 32);
+
+    let input = read_inspection_input(
+        &path,
+        1000,
+        Some(&MultimodalConfig::default()),
+        &unlimited(),
+    )
+    .unwrap();
+    match input {
+        InspectionInput::Text(text) => {
+            assert!(text.starts_with("Image metadata: dimensions: 64x32"))
+        }
+        other => panic!("expected text, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_image_is_downscaled_and_reencoded_as_jpeg() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("large.png");
+    write_png(&path, 3000, 1500);
+
+    let Some(InspectionInput::Media { part, details }) =
+        read_inspection_input(&path, 1000, Some(&enabled()), &unlimited())
+    else {
+        panic!("expected an attachment");
+    };
+
+    assert_eq!(part.mime_type, "image/jpeg");
+    let decoded = image::load_from_memory(&part.data).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (1024, 512));
+    assert!(details.unwrap().contains("3000x1500"));
+}
+
+#[test]
+fn test_oversized_image_falls_back_to_text() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("noisy.png");
+    write_png(&path, 800, 800);
+
+    let tiny_attachment = MultimodalConfig {
+        max_attachment_kb: 1,
+        ..enabled()
+    };
+    assert!(matches!(
+        read_inspection_input(&path, 1000, Some(&tiny_attachment), &unlimited()),
+        Some(InspectionInput::Text(_))
+    ));
+
+    let tiny_source = MultimodalConfig {
+        max_file_mb: 0,
+        ..enabled()
+    };
+    assert!(matches!(
+        read_inspection_input(&path, 1000, Some(&tiny_source), &unlimited()),
+        Some(InspectionInput::Text(_))
+    ));
+}
+
+#[test]
+fn test_used_up_budget_skips_preparing_attachments() {
+    let dir = TempDir::new().unwrap();
+    let image = dir.path().join("photo.png");
+    let scan = dir.path().join("scan.pdf");
+    write_png(&image, 64, 32);
+    write_pdf_without_text(&scan, 1);
+
+    let used_up = MediaBudget::new(0);
+    assert!(matches!(
+        read_inspection_input(&image, 1000, Some(&enabled()), &used_up),
+        Some(InspectionInput::Text(_))
+    ));
+    assert!(read_inspection_input(&scan, 1000, Some(&enabled()), &used_up).is_none());
+}
+
+#[test]
+fn test_scanned_pdf_sends_first_page_only() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("scan.pdf");
+    write_pdf_without_text(&path, 3);
+
+    let Some(InspectionInput::Media { part, details }) =
+        read_inspection_input(&path, 1000, Some(&enabled()), &unlimited())
+    else {
+        panic!("expected an attachment");
+    };
+
+    assert_eq!(part.mime_type, "application/pdf");
+    assert!(details.is_none());
+    let page = lopdf::Document::load_mem(&part.data).unwrap();
+    assert_eq!(page.get_pages().len(), 1);
+}
+
+#[test]
+fn test_pdf_with_text_sends_its_text() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("report.pdf");
+    write_pdf_with_text(&path, "Quarterly Report");
+
+    let Some(InspectionInput::Text(text)) =
+        read_inspection_input(&path, 1000, Some(&enabled()), &unlimited())
+    else {
+        panic!("expected a text sample");
+    };
+
+    assert!(text.contains("Quarterly Report"), "got: {:?}", text);
+}
+
+#[test]
+fn test_media_budget_reserves_until_exhausted() {
+    let budget = MediaBudget::new(100);
+    let shared = budget.clone();
+
+    assert!(budget.try_reserve(60));
+    assert!(!shared.try_reserve(50));
+    assert_eq!(budget.remaining(), 40);
+    assert!(shared.try_reserve(40));
+    assert_eq!(budget.remaining(), 0);
+}
+
+#[test]
+fn test_encoded_len_counts_base64_bytes() {
+    let part = |len: usize| MediaPart {
+        mime_type: "image/jpeg".to_string(),
+        data: vec![0; len],
+    };
+
+    assert_eq!(part(0).encoded_len(), 0);
+    assert_eq!(part(3).encoded_len(), 4);
+    assert_eq!(part(4).encoded_len(), 8);
+    assert_eq!(part(300).encoded_len(), 400);
+}
+
+#[test]
+fn test_read_inspection_targets_skips_uninspectable_files() {
+    let dir = TempDir::new().unwrap();
+    let notes = dir.path().join("notes.txt");
+    let binary = dir.path().join("tool.bin");
+    fs::write(&notes, "meeting notes").unwrap();
+    fs::write(&binary, [0u8, 1, 2, 3]).unwrap();
+
+    let files: Vec<FileCategory> = ["notes.txt", "tool.bin", "missing.txt"]
+        .iter()
+        .map(|name| FileCategory {
+            filename: name.to_string(),
+            category: "Documents".to_string(),
+            ..Default::default()
+        })
+        .collect();
+    let paths = HashMap::from([
+        ("notes.txt".to_string(), notes),
+        ("tool.bin".to_string(), binary),
+    ]);
+
+    let targets = read_inspection_targets(&files, &paths, 100, None, &unlimited());
+
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].filename, "notes.txt");
+    assert_eq!(
+        targets[0].input,
+        InspectionInput::Text("meeting notes".to_string())
+    );
+}
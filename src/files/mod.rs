@@ -1,17 +1,40 @@
 pub mod batch;
 pub mod categorizer;
+pub mod context;
 pub mod detector;
 pub mod duplicate;
+pub mod extract;
 mod file_ops;
+pub mod image_meta;
+pub mod media;
 pub mod mover;
+pub mod sanitize;
+pub mod subfolders;
 pub mod undo;
 
 pub use batch::FileBatch;
-pub use categorizer::{OfflineCategorizationResult, categorize_files_offline};
-pub use detector::{is_text_file, read_file_sample};
+pub use categorizer::{
+    OfflineCategorizationResult, apply_content_sub_categories, categorize_files_offline,
+};
+pub use context::collect_file_context;
+pub use detector::{detect_mime_type, is_text_file, read_file_sample};
+pub use extract::{
+    extract_document_text, has_inspectable_content, has_readable_content, read_content_sample,
+    read_inspection_sample,
+};
 pub use file_ops::move_file_cross_platform;
+pub use image_meta::{ImageKind, ImageMetadata, classify_image, read_image_metadata};
+pub use media::{
+    InspectionInput, InspectionTarget, MediaBudget, MediaPart, read_inspection_input,
+    read_inspection_targets,
+};
 pub use mover::{MoveError, MoveSummary, execute_move, execute_move_auto, execute_move_silent};
-pub use undo::{UndoError, UndoSummary, undo_moves, undo_moves_auto};
+pub use sanitize::{PlanFix, SegmentError, sanitize_plan, sanitize_segment};
+pub use subfolders::{SubFolderIndex, is_same_folder};
+pub use undo::{
+    UndoError, UndoFilter, UndoSummary, parse_glob, parse_time, parse_until, redo_moves,
+    redo_moves_auto, undo_moves, undo_moves_auto,
+};
 
 #[cfg(test)]
 mod tests {
@@ -25,7 +48,9 @@ mod tests {
                 filename: "test.txt".to_string(),
                 category: "Documents".to_string(),
                 sub_category: "Text".to_string(),
+                ..Default::default()
             }],
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&plan).unwrap();
@@ -42,6 +67,7 @@ mod tests {
             filename: "file.rs".to_string(),
             category: "Code".to_string(),
             sub_category: "Rust".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&fc).unwrap();
@@ -51,4 +77,71 @@ mod tests {
         assert_eq!(fc.category, deserialized.category);
         assert_eq!(fc.sub_category, deserialized.sub_category);
     }
+
+    fn scored(filename: &str, confidence: Option<f32>) -> FileCategory {
+        FileCategory {
+            filename: filename.to_string(),
+            category: "Documents".to_string(),
+            confidence,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_hold_low_confidence_moves_files_to_review() {
+        let mut plan = OrganizationPlan {
+            files: vec![
+                scored("sure.pdf", Some(0.9)),
+                scored("unsure.pdf", Some(0.3)),
+                scored("unscored.pdf", None),
+            ],
+            ..Default::default()
+        };
+
+        plan.hold_low_confidence(0.5);
+
+        let kept: Vec<_> = plan.files.iter().map(|f| f.filename.as_str()).collect();
+        assert_eq!(kept, ["sure.pdf", "unscored.pdf"]);
+        assert_eq!(plan.needs_review.len(), 1);
+        assert_eq!(plan.needs_review[0].filename, "unsure.pdf");
+    }
+
+    #[test]
+    fn test_approve_review_returns_files_to_plan() {
+        let mut plan = OrganizationPlan {
+            files: vec![scored("sure.pdf", Some(0.9))],
+            ..Default::default()
+        };
+        plan.hold_low_confidence(0.95);
+        assert!(plan.files.is_empty());
+
+        plan.approve_review();
+
+        assert_eq!(plan.files.len(), 1);
+        assert!(plan.needs_review.is_empty());
+    }
+
+    #[test]
+    fn test_apply_sub_categories_sets_matching_files() {
+        let mut plan = OrganizationPlan {
+            files: vec![scored("a.txt", None), scored("b.txt", None)],
+            ..Default::default()
+        };
+        let sub_categories =
+            std::collections::HashMap::from([("b.txt".to_string(), "Notes".to_string())]);
+
+        plan.apply_sub_categories(&sub_categories);
+
+        assert!(plan.files[0].sub_category.is_empty());
+        assert_eq!(plan.files[1].sub_category, "Notes");
+    }
+
+    #[test]
+    fn test_file_category_without_confidence_deserializes() {
+        let json = r#"{"filename":"a.txt","category":"Documents","sub_category":""}"#;
+        let fc: FileCategory = serde_json::from_str(json).unwrap();
+
+        assert_eq!(fc.confidence, None);
+        assert!(fc.reasoning.is_empty());
+    }
 }
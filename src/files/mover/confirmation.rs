@@ -3,6 +3,9 @@ use std::io;
 
 pub trait ConfirmationStrategy {
     fn confirm(&self) -> Result<bool, MoveError>;
+
+    /// Asks whether files held for review should be moved as well.
+    fn confirm_review(&self, count: usize) -> bool;
 }
 
 pub struct StdinConfirmation;
@@ -25,6 +28,18 @@ impl ConfirmationStrategy for StdinConfirmation {
 
         Ok(true)
     }
+
+    fn confirm_review(&self, count: usize) -> bool {
+        eprint!("Also move the {} low-confidence file(s)? [y/N]: ", count);
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).is_err() {
+            return false;
+        }
+
+        let input = input.trim().to_lowercase();
+        input == "y" || input == "yes"
+    }
 }
 
 pub struct AutoConfirm;
@@ -33,4 +48,8 @@ impl ConfirmationStrategy for AutoConfirm {
     fn confirm(&self) -> Result<bool, MoveError> {
         Ok(true)
     }
+
+    fn confirm_review(&self, _count: usize) -> bool {
+        false
+    }
 }
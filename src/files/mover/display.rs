@@ -19,6 +19,31 @@ pub(super) fn display_plan(files: &[FileCategory]) {
     }
 }
 
+pub(super) fn display_review(files: &[FileCategory]) {
+    if files.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "--- NEEDS REVIEW ---".bold().underline());
+    for item in files {
+        let target_display = format_target_path(&item.category, &item.sub_category);
+        let confidence = item
+            .confidence
+            .map(|c| format!("{:.0}%", c * 100.0))
+            .unwrap_or_else(|| "?".to_string());
+        println!(
+            "Review: {} -> {}{} ({})",
+            item.filename,
+            target_display,
+            MAIN_SEPARATOR,
+            confidence.yellow()
+        );
+        if !item.reasoning.is_empty() {
+            println!("        {}", item.reasoning.dimmed());
+        }
+    }
+}
+
 pub(super) fn print_summary(summary: &super::types::MoveSummary) {
     println!("\n{}", "Organization Complete!".bold().green());
     println!(
@@ -1,5 +1,5 @@
 use super::confirmation::ConfirmationStrategy;
-use super::display::{display_plan, format_target_path};
+use super::display::{display_plan, display_review, format_target_path};
 use super::paths::{build_target_path, ensure_directory_exists};
 use super::types::{MoveError, MoveSummary};
 use crate::files::move_file_cross_platform;
@@ -9,36 +9,60 @@ use colored::*;
 use std::fs;
 use std::path::{MAIN_SEPARATOR, Path};
 
+/// Shows the plan, asks for confirmation and moves the files. The plan is
+/// expected to be sanitized already; files whose folder names are not safe
+/// path segments are reported and left in place.
 pub fn execute_move_with_strategy<C: ConfirmationStrategy>(
     base_path: &Path,
-    plan: OrganizationPlan,
+    mut plan: OrganizationPlan,
     mut undo_log: Option<&mut UndoLog>,
     confirmation: &C,
 ) -> Result<MoveSummary, MoveError> {
-    if plan.files.is_empty() {
+    if plan.files.is_empty() && plan.needs_review.is_empty() {
         println!("{}", "No files to organize.".yellow());
         return Ok(MoveSummary::new());
     }
 
     display_plan(&plan.files);
+    display_review(&plan.needs_review);
 
     confirmation.confirm()?;
 
+    if !plan.needs_review.is_empty() {
+        if confirmation.confirm_review(plan.needs_review.len()) {
+            plan.approve_review();
+        } else {
+            println!(
+                "{} Leaving {} low-confidence file(s) in place.",
+                "INFO:".cyan(),
+                plan.needs_review.len()
+            );
+        }
+    }
+
     println!("\n{}", "--- MOVING FILES ---".bold().underline());
 
     let mut summary = MoveSummary::new();
 
     for item in plan.files {
         let source = base_path.join(&item.filename);
-        let final_path = base_path.join(&item.category);
-        let target = build_target_path(
+        let target = match build_target_path(
             base_path,
             &item.category,
             &item.sub_category,
             &item.filename,
-        );
+        ) {
+            Ok(target) => target,
+            Err(e) => {
+                eprintln!("{} {}: {}", "ERROR:".red(), item.filename, e);
+                summary.errored();
+                continue;
+            }
+        };
 
-        if let Err(e) = ensure_directory_exists(&final_path) {
+        if let Some(final_path) = target.parent()
+            && let Err(e) = ensure_directory_exists(final_path)
+        {
             eprintln!("{} {}", "ERROR:".red(), e);
             summary.errored();
             continue;
@@ -104,15 +128,19 @@ pub fn execute_move_silent(
 
     for item in plan.files {
         let source = base_path.join(&item.filename);
-        let final_path = base_path.join(&item.category);
-        let target = build_target_path(
+        let Ok(target) = build_target_path(
             base_path,
             &item.category,
             &item.sub_category,
             &item.filename,
-        );
+        ) else {
+            summary.errored();
+            continue;
+        };
 
-        if ensure_directory_exists(&final_path).is_err() {
+        if let Some(final_path) = target.parent()
+            && ensure_directory_exists(final_path).is_err()
+        {
             summary.errored();
             continue;
         }
@@ -1,19 +1,23 @@
 use super::types::MoveError;
+use crate::files::sanitize::sanitize_segment;
 use std::{
     ffi::OsStr,
     fs,
     path::{Path, PathBuf},
 };
 
+/// Builds `base/category[/sub_category]/file_name`. Folder names that
+/// aren't already a single safe path segment are refused, so a plan can
+/// never place a file outside `base_path`.
 pub fn build_target_path(
     base_path: &Path,
     category: &str,
     sub_category: &str,
     filename: &str,
-) -> PathBuf {
-    let mut final_path = base_path.join(category);
+) -> Result<PathBuf, MoveError> {
+    let mut final_path = base_path.join(checked_segment(category)?);
     if !sub_category.is_empty() {
-        final_path = final_path.join(sub_category);
+        final_path = final_path.join(checked_segment(sub_category)?);
     }
 
     let file_name = Path::new(filename)
@@ -22,7 +26,14 @@ pub fn build_target_path(
         .to_string_lossy()
         .into_owned();
 
-    final_path.join(&file_name)
+    Ok(final_path.join(&file_name))
+}
+
+fn checked_segment(name: &str) -> Result<&str, MoveError> {
+    match sanitize_segment(name) {
+        Ok(segment) if segment == name => Ok(name),
+        _ => Err(MoveError::InvalidFolderName(name.to_string())),
+    }
 }
 
 pub fn ensure_directory_exists(path: &Path) -> Result<(), MoveError> {
@@ -42,6 +42,7 @@ pub enum MoveError {
     UserCancelled,
     DirectoryCreationFailed(PathBuf, std::io::Error),
     FileMoveFailed(PathBuf, PathBuf, std::io::Error),
+    InvalidFolderName(String),
 }
 
 impl std::fmt::Display for MoveError {
@@ -55,6 +56,9 @@ impl std::fmt::Display for MoveError {
             MoveError::FileMoveFailed(source, target, err) => {
                 write!(f, "Failed to move {:?} to {:?}: {}", source, target, err)
             }
+            MoveError::InvalidFolderName(name) => {
+                write!(f, "Refusing to use {:?} as a folder name", name)
+            }
         }
     }
 }
@@ -0,0 +1,192 @@
+use crate::models::OrganizationPlan;
+use std::fmt;
+
+/// Longest category or sub-category name kept, in UTF-8 bytes. Filesystems
+/// limit a name to 255 bytes, and a character can take up to four.
+pub const MAX_SEGMENT_BYTES: usize = 200;
+
+/// Characters Windows does not allow in file names.
+const RESERVED_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension.
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Why a folder name could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentError {
+    /// Nothing usable is left once unsafe characters are removed.
+    Empty,
+    /// A device name such as `CON` or `NUL`.
+    ReservedName(String),
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SegmentError::Empty => write!(f, "no usable characters"),
+            SegmentError::ReservedName(name) => write!(f, "'{}' is a reserved name", name),
+        }
+    }
+}
+
+/// Turns a category or sub-category name into a single safe path segment.
+///
+/// Path separators split the name into parts; empty, `.` and `..` parts are
+/// dropped and the rest are joined with `-`, so `../etc` becomes `etc` and
+/// `/a/b` becomes `a-b`. Control characters are removed, characters Windows
+/// rejects are replaced with `-`, leading dots and trailing dots and spaces
+/// are trimmed, and the result is cut to `MAX_SEGMENT_BYTES` at a character
+/// boundary.
+pub fn sanitize_segment(name: &str) -> Result<String, SegmentError> {
+    let joined = name
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .collect::<Vec<_>>()
+        .join("-");
+
+    let cleaned: String = joined
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| if RESERVED_CHARS.contains(&c) { '-' } else { c })
+        .collect();
+
+    let trimmed = cleaned.trim().trim_start_matches('.');
+    let segment = truncate_to_bytes(trimmed, MAX_SEGMENT_BYTES)
+        .trim_end_matches(['.', ' '])
+        .trim_start();
+
+    if segment.is_empty() {
+        return Err(SegmentError::Empty);
+    }
+    let stem = segment.split('.').next().unwrap_or(segment).to_lowercase();
+    if RESERVED_NAMES.contains(&stem.as_str()) {
+        return Err(SegmentError::ReservedName(segment.to_string()));
+    }
+    Ok(segment.to_string())
+}
+
+fn truncate_to_bytes(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let end = (0..=max_bytes)
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0);
+    &text[..end]
+}
+
+/// A change `sanitize_plan` made to a planned file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanFix {
+    Repaired {
+        filename: String,
+        from: String,
+        to: String,
+    },
+    /// The category was unusable; the file was left uncategorized.
+    RejectedCategory {
+        filename: String,
+        category: String,
+        error: SegmentError,
+    },
+    /// The sub-category was unusable; the file goes directly into its category.
+    RejectedSubCategory {
+        filename: String,
+        sub_category: String,
+        error: SegmentError,
+    },
+}
+
+impl fmt::Display for PlanFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanFix::Repaired { filename, from, to } => {
+                write!(f, "{}: renamed folder {:?} to {:?}", filename, from, to)
+            }
+            PlanFix::RejectedCategory {
+                filename,
+                category,
+                error,
+            } => write!(
+                f,
+                "{}: rejected category {:?} ({}), leaving the file in place",
+                filename, category, error
+            ),
+            PlanFix::RejectedSubCategory {
+                filename,
+                sub_category,
+                error,
+            } => write!(
+                f,
+                "{}: rejected sub-category {:?} ({}), using the category folder",
+                filename, sub_category, error
+            ),
+        }
+    }
+}
+
+/// Sanitizes every category and sub-category in the plan, whatever produced
+/// it. Files with an unusable category move to `uncategorized`; unusable
+/// sub-categories are cleared. Returns what was changed.
+pub fn sanitize_plan(plan: &mut OrganizationPlan) -> Vec<PlanFix> {
+    let mut fixes = Vec::new();
+    let mut rejected = Vec::new();
+
+    for list in [&mut plan.files, &mut plan.needs_review] {
+        list.retain_mut(|file| {
+            match sanitize_segment(&file.category) {
+                Ok(category) => {
+                    if category != file.category {
+                        fixes.push(PlanFix::Repaired {
+                            filename: file.filename.clone(),
+                            from: std::mem::replace(&mut file.category, category.clone()),
+                            to: category,
+                        });
+                    }
+                }
+                Err(error) => {
+                    fixes.push(PlanFix::RejectedCategory {
+                        filename: file.filename.clone(),
+                        category: file.category.clone(),
+                        error,
+                    });
+                    rejected.push(file.filename.clone());
+                    return false;
+                }
+            }
+
+            if !file.sub_category.is_empty() {
+                match sanitize_segment(&file.sub_category) {
+                    Ok(sub_category) if sub_category != file.sub_category => {
+                        fixes.push(PlanFix::Repaired {
+                            filename: file.filename.clone(),
+                            from: std::mem::replace(&mut file.sub_category, sub_category.clone()),
+                            to: sub_category,
+                        });
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        fixes.push(PlanFix::RejectedSubCategory {
+                            filename: file.filename.clone(),
+                            sub_category: std::mem::take(&mut file.sub_category),
+                            error,
+                        });
+                    }
+                }
+            }
+            true
+        });
+    }
+
+    plan.uncategorized.extend(rejected);
+    fixes
+}
+
+#[cfg(test)]
+#[path = "sanitize_test.rs"]
+mod tests;
@@ -0,0 +1,112 @@
+use super::*;
+use crate::models::FileCategory;
+
+fn file(filename: &str, category: &str, sub_category: &str) -> FileCategory {
+    FileCategory {
+        filename: filename.to_string(),
+        category: category.to_string(),
+        sub_category: sub_category.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_sanitize_segment_keeps_safe_names() {
+    assert_eq!(sanitize_segment("Documents").unwrap(), "Documents");
+    assert_eq!(
+        sanitize_segment("Tax Returns 2023").unwrap(),
+        "Tax Returns 2023"
+    );
+    assert_eq!(sanitize_segment("Über-Fotos").unwrap(), "Über-Fotos");
+}
+
+#[test]
+fn test_sanitize_segment_removes_traversal_and_separators() {
+    assert_eq!(sanitize_segment("../etc").unwrap(), "etc");
+    assert_eq!(sanitize_segment("/etc/passwd").unwrap(), "etc-passwd");
+    assert_eq!(sanitize_segment("..\\..\\Windows").unwrap(), "Windows");
+    assert_eq!(sanitize_segment("Work/Invoices").unwrap(), "Work-Invoices");
+    assert_eq!(sanitize_segment(".hidden").unwrap(), "hidden");
+}
+
+#[test]
+fn test_sanitize_segment_repairs_characters_and_length() {
+    assert_eq!(sanitize_segment("Notes\u{0}\n\t").unwrap(), "Notes");
+    assert_eq!(sanitize_segment("What? Now*").unwrap(), "What- Now-");
+    assert_eq!(sanitize_segment("Reports. . ").unwrap(), "Reports");
+    assert_eq!(
+        sanitize_segment(&"a".repeat(300)).unwrap().len(),
+        MAX_SEGMENT_BYTES
+    );
+}
+
+#[test]
+fn test_sanitize_segment_cuts_multi_byte_names_by_bytes() {
+    // 3 bytes per character; 200 is not a multiple of 3
+    let cjk = sanitize_segment(&"文".repeat(100)).unwrap();
+    assert_eq!(cjk, "文".repeat(66));
+
+    // 4 bytes per character
+    let emoji = sanitize_segment(&"📁".repeat(100)).unwrap();
+    assert_eq!(emoji, "📁".repeat(50));
+    assert!(emoji.len() <= MAX_SEGMENT_BYTES);
+}
+
+#[test]
+fn test_sanitize_segment_rejects_unusable_names() {
+    assert_eq!(sanitize_segment(""), Err(SegmentError::Empty));
+    assert_eq!(sanitize_segment("../.."), Err(SegmentError::Empty));
+    assert_eq!(sanitize_segment(" . "), Err(SegmentError::Empty));
+    assert!(matches!(
+        sanitize_segment("NUL"),
+        Err(SegmentError::ReservedName(_))
+    ));
+    assert!(matches!(
+        sanitize_segment("com1.txt"),
+        Err(SegmentError::ReservedName(_))
+    ));
+}
+
+#[test]
+fn test_sanitize_plan_repairs_and_rejects() {
+    let mut plan = OrganizationPlan {
+        files: vec![
+            file("a.pdf", "Documents", "Invoices"),
+            file("b.pdf", "../Documents", ""),
+            file("c.pdf", "..", "Notes"),
+            file("d.pdf", "Documents", "/"),
+        ],
+        needs_review: vec![file("e.pdf", "Documents", "Reports.")],
+        ..Default::default()
+    };
+
+    let fixes = sanitize_plan(&mut plan);
+
+    let categories: Vec<_> = plan
+        .files
+        .iter()
+        .map(|f| {
+            (
+                f.filename.as_str(),
+                f.category.as_str(),
+                f.sub_category.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        categories,
+        [
+            ("a.pdf", "Documents", "Invoices"),
+            ("b.pdf", "Documents", ""),
+            ("d.pdf", "Documents", ""),
+        ]
+    );
+    assert_eq!(plan.needs_review[0].sub_category, "Reports");
+    assert_eq!(plan.uncategorized, ["c.pdf"]);
+    assert_eq!(fixes.len(), 4);
+    assert!(fixes.contains(&PlanFix::Repaired {
+        filename: "b.pdf".to_string(),
+        from: "../Documents".to_string(),
+        to: "Documents".to_string(),
+    }));
+}
@@ -0,0 +1,235 @@
+use crate::models::OrganizationPlan;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Groups of sub-folder names treated as the same folder. Names are
+/// compared by their normalized key, so list them singular and lowercase.
+const SYNONYMS: &[&[&str]] = &[
+    &["invoice", "bill"],
+    &["receipt", "purchase"],
+    &["photo", "picture", "pic", "image"],
+    &["screenshot", "screencapture", "screengrab"],
+    &["document", "doc"],
+    &["wallpaper", "background"],
+    &["resume", "cv"],
+    &["presentation", "slide", "deck"],
+    &["spreadsheet", "sheet"],
+    &["note", "memo"],
+    &["manual", "guide"],
+];
+
+/// Sub-folders that already exist under each category folder, from disk and
+/// from past moves.
+#[derive(Debug, Clone, Default)]
+pub struct SubFolderIndex {
+    by_category: HashMap<String, Vec<String>>,
+}
+
+impl SubFolderIndex {
+    /// Collects the sub-folders of each of `categories` under `base_path`,
+    /// plus any recorded in `directory_usage` (relative destination
+    /// directories, as returned by `UndoLog::get_directory_usage`). Folders
+    /// used more often come first.
+    pub fn scan(
+        base_path: &Path,
+        categories: &[String],
+        directory_usage: &HashMap<String, usize>,
+    ) -> Self {
+        let mut counts: HashMap<&str, HashMap<String, usize>> = HashMap::new();
+
+        for category in categories {
+            let folders = counts.entry(category.as_str()).or_default();
+            let Ok(entries) = fs::read_dir(base_path.join(category)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !name.starts_with('.') && entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    folders.entry(name).or_insert(0);
+                }
+            }
+        }
+
+        for (dir, uses) in directory_usage {
+            let mut parts = Path::new(dir).components();
+            let (Some(category), Some(sub_folder)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let category = category.as_os_str().to_string_lossy();
+            if let Some(folders) = counts.get_mut(category.as_ref()) {
+                let sub_folder = sub_folder.as_os_str().to_string_lossy().into_owned();
+                *folders.entry(sub_folder).or_insert(0) += uses;
+            }
+        }
+
+        let by_category = counts
+            .into_iter()
+            .filter(|(_, folders)| !folders.is_empty())
+            .map(|(category, folders)| {
+                let mut folders: Vec<_> = folders.into_iter().collect();
+                folders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                let names = folders.into_iter().map(|(name, _)| name).collect();
+                (category.to_string(), names)
+            })
+            .collect();
+
+        Self { by_category }
+    }
+
+    /// Existing sub-folders of `category`, most used first.
+    pub fn for_category(&self, category: &str) -> &[String] {
+        self.by_category
+            .get(category)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Renames near-duplicate sub-categories in the plan to a single
+    /// spelling: an existing folder when one matches, otherwise the first
+    /// spelling suggested in this plan.
+    pub fn unify_sub_categories(&self, plan: &mut OrganizationPlan) {
+        let mut known: HashMap<String, Vec<String>> = self.by_category.clone();
+
+        for file in plan.files.iter_mut().chain(plan.needs_review.iter_mut()) {
+            if file.sub_category.is_empty() {
+                continue;
+            }
+            let names = known.entry(file.category.clone()).or_default();
+            match names
+                .iter()
+                .find(|name| is_same_folder(name, &file.sub_category))
+            {
+                Some(name) => file.sub_category = name.clone(),
+                None => names.push(file.sub_category.clone()),
+            }
+        }
+    }
+}
+
+/// Returns true if two folder names differ only in case, separators,
+/// plural form, a small typo, or by being listed as synonyms.
+pub fn is_same_folder(a: &str, b: &str) -> bool {
+    let (a, b) = (folder_keys(a), folder_keys(b));
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+
+    a.iter().any(|x| {
+        b.iter().any(|y| {
+            x == y
+                || synonym_group(x).is_some_and(|group| synonym_group(y) == Some(group))
+                || within_typo_distance(x, y)
+        })
+    })
+}
+
+/// Lowercases a folder name, drops everything but letters and digits, and
+/// returns it along with its possible singular forms.
+fn folder_keys(name: &str) -> Vec<String> {
+    let key: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if key.is_empty() {
+        return Vec::new();
+    }
+
+    let mut keys = vec![key.clone()];
+    if key.len() > 4
+        && let Some(stem) = key.strip_suffix("ies")
+    {
+        // "categories" -> "category", "movies" -> "movie"
+        keys.push(format!("{}y", stem));
+        keys.push(format!("{}ie", stem));
+    }
+    if key.len() > 4
+        && let Some(stem) = key.strip_suffix("es")
+        && ["s", "x", "z", "ch", "sh"]
+            .iter()
+            .any(|end| stem.ends_with(end))
+    {
+        // "taxes" -> "tax", "batches" -> "batch"; "clothes" is not "cloth"
+        keys.push(stem.to_string());
+    }
+    if key.len() > 3
+        && !key.ends_with("ss")
+        && let Some(stem) = key.strip_suffix('s')
+    {
+        keys.push(stem.to_string());
+    }
+    keys
+}
+
+fn within_typo_distance(a: &str, b: &str) -> bool {
+    // "2023" and "2024", or "Q1" and "Q2", are different folders
+    if a.chars().chain(b.chars()).any(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    // Typos rarely hit the first letter, while words one letter apart at the
+    // start are common ("Medications"/"Dedications")
+    if a.chars().next() != b.chars().next() {
+        return false;
+    }
+    let shorter = a.chars().count().min(b.chars().count());
+    match shorter {
+        0..=5 => false,
+        // One inserted or replaced letter turns real words into each other
+        // ("Contacts"/"Contracts", "Reports"/"Resorts"), so only a swap of
+        // adjacent letters, as in "Reciepts", counts at this length
+        6..=8 => is_adjacent_swap(a, b),
+        // Two edits link unrelated longer words ("Passports"/"Passwords",
+        // "Insurance"/"Endurance"), so allow one, counting a swap as one
+        _ => edit_distance(a, b) <= 1,
+    }
+}
+
+/// True if `b` is `a` with exactly one pair of adjacent characters swapped.
+fn is_adjacent_swap(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len() != b.len() {
+        return false;
+    }
+    let diffs: Vec<usize> = (0..a.len()).filter(|&i| a[i] != b[i]).collect();
+    matches!(diffs[..], [i, j] if j == i + 1 && a[i] == b[j] && a[j] == b[i])
+}
+
+fn synonym_group(key: &str) -> Option<usize> {
+    SYNONYMS.iter().position(|group| group.contains(&key))
+}
+
+/// Edit distance between two strings, by characters, counting a swap of
+/// adjacent characters as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows: Vec<Vec<usize>> = (0..=a.len())
+        .map(|i| {
+            let mut row = vec![0; b.len() + 1];
+            row[0] = i;
+            row
+        })
+        .collect();
+    rows[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+#[path = "subfolders_test.rs"]
+mod tests;
@@ -0,0 +1,120 @@
+use super::*;
+use crate::models::FileCategory;
+use tempfile::TempDir;
+
+fn file(filename: &str, category: &str, sub_category: &str) -> FileCategory {
+    FileCategory {
+        filename: filename.to_string(),
+        category: category.to_string(),
+        sub_category: sub_category.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_is_same_folder_matches_near_duplicates() {
+    assert!(is_same_folder("Invoices", "invoice"));
+    assert!(is_same_folder("Tax Documents", "tax-documents"));
+    assert!(is_same_folder("Categories", "Category"));
+    assert!(is_same_folder("Movies", "Movie"));
+    assert!(is_same_folder("Taxes", "Tax"));
+    assert!(is_same_folder("Receipts", "Reciepts"));
+    assert!(is_same_folder("Bills", "Invoices"));
+    assert!(is_same_folder("Pictures", "Photos"));
+}
+
+#[test]
+fn test_is_same_folder_keeps_distinct_names_apart() {
+    assert!(!is_same_folder("Invoices", "Receipts"));
+    assert!(!is_same_folder("Notes", "Nodes"));
+    assert!(!is_same_folder("2023", "2024"));
+    assert!(!is_same_folder("Q1-Reports", "Q2-Reports"));
+    assert!(!is_same_folder("", ""));
+}
+
+#[test]
+fn test_is_same_folder_keeps_one_letter_apart_words_apart() {
+    assert!(!is_same_folder("Contacts", "Contracts"));
+    assert!(!is_same_folder("Contact", "Contract"));
+    assert!(!is_same_folder("Drafts", "Crafts"));
+    assert!(!is_same_folder("Reports", "Resorts"));
+    assert!(is_same_folder("Contracts", "Contarcts"));
+}
+
+#[test]
+fn test_is_same_folder_keeps_two_letter_apart_long_words_apart() {
+    assert!(!is_same_folder("Passports", "Passwords"));
+    assert!(!is_same_folder("Vacations", "Locations"));
+    assert!(!is_same_folder("Insurance", "Endurance"));
+    assert!(!is_same_folder("Presentations", "Representations"));
+    assert!(is_same_folder("Presentations", "Presentatons"));
+    assert!(is_same_folder("Insurance", "Insuarnce"));
+}
+
+#[test]
+fn test_is_same_folder_strips_es_only_after_sibilants() {
+    assert!(is_same_folder("Addresses", "Address"));
+    assert!(is_same_folder("Batches", "Batch"));
+    assert!(is_same_folder("Wishes", "Wish"));
+    assert!(is_same_folder("Notes", "Note"));
+    assert!(!is_same_folder("Clothes", "Cloth"));
+    assert!(!is_same_folder("Notes", "Not"));
+}
+
+#[test]
+fn test_is_same_folder_needs_the_same_first_letter_for_typos() {
+    assert!(!is_same_folder("Medications", "Dedications"));
+    assert!(!is_same_folder("Reflections", "Deflections"));
+    assert!(!is_same_folder("Invoices1", "Invoices2"));
+    assert!(!is_same_folder("Contracts", "Contacts"));
+    assert!(is_same_folder("Medications", "Medicatoins"));
+}
+
+#[test]
+fn test_scan_reads_disk_and_directory_usage() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("Documents/Invoices")).unwrap();
+    fs::create_dir_all(dir.path().join("Documents/.hidden")).unwrap();
+    fs::write(dir.path().join("Documents/loose.txt"), "x").unwrap();
+
+    let usage = HashMap::from([
+        ("Documents/Contracts".to_string(), 5),
+        ("Documents".to_string(), 3),
+        ("Music/Albums".to_string(), 2),
+    ]);
+    let categories = vec!["Documents".to_string(), "Images".to_string()];
+    let index = SubFolderIndex::scan(dir.path(), &categories, &usage);
+
+    assert_eq!(index.for_category("Documents"), ["Contracts", "Invoices"]);
+    assert!(index.for_category("Images").is_empty());
+    assert!(index.for_category("Music").is_empty());
+}
+
+#[test]
+fn test_unify_prefers_existing_folders_then_first_suggestion() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("Documents/Invoices")).unwrap();
+    let index = SubFolderIndex::scan(dir.path(), &["Documents".to_string()], &HashMap::new());
+
+    let mut plan = OrganizationPlan {
+        files: vec![
+            file("a.pdf", "Documents", "Invoice"),
+            file("b.pdf", "Documents", "Bills"),
+            file("c.pdf", "Documents", "Receipts"),
+            file("d.pdf", "Documents", "receipt"),
+            file("e.png", "Images", "Receipts"),
+            file("f.pdf", "Documents", ""),
+        ],
+        ..Default::default()
+    };
+
+    index.unify_sub_categories(&mut plan);
+
+    let subs: Vec<_> = plan.files.iter().map(|f| f.sub_category.as_str()).collect();
+    assert_eq!(
+        subs,
+        [
+            "Invoices", "Invoices", "Receipts", "Receipts", "Receipts", ""
+        ]
+    );
+}
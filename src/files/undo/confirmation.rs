@@ -2,20 +2,22 @@ use super::types::UndoError;
 use std::io;
 
 pub trait ConfirmationStrategy {
-    fn confirm(&self) -> Result<bool, UndoError>;
+    /// Asks whether to `action` ("undo" or "redo") the previewed moves.
+    fn confirm(&self, action: &str) -> Result<bool, UndoError>;
 }
 
 pub struct StdinConfirmation;
 
 impl ConfirmationStrategy for StdinConfirmation {
-    fn confirm(&self) -> Result<bool, UndoError> {
-        eprint!("\nDo you want to undo these changes? [y/N]: ");
+    fn confirm(&self, action: &str) -> Result<bool, UndoError> {
+        eprint!("\nDo you want to {} these changes? [y/N]: ", action);
 
         let mut input = String::new();
         if io::stdin().read_line(&mut input).is_err() {
-            return Err(UndoError::InputReadFailed(
-                "Failed to read input. Undo cancelled.".to_string(),
-            ));
+            return Err(UndoError::InputReadFailed(format!(
+                "Failed to read input; {} cancelled.",
+                action
+            )));
         }
 
         let input = input.trim().to_lowercase();
@@ -30,7 +32,7 @@ impl ConfirmationStrategy for StdinConfirmation {
 pub struct AutoConfirm;
 
 impl ConfirmationStrategy for AutoConfirm {
-    fn confirm(&self) -> Result<bool, UndoError> {
+    fn confirm(&self, _action: &str) -> Result<bool, UndoError> {
         Ok(true)
     }
 }
@@ -25,6 +25,44 @@ pub(super) fn display_undo_preview(records: &[FileMoveRecord], base_path: &Path)
     }
 }
 
+pub(super) fn display_redo_preview(records: &[FileMoveRecord], base_path: &Path) {
+    println!("\n{}", "--- REDO PREVIEW ---".bold().underline());
+    println!(
+        "{} will move {} files again:",
+        "INFO:".cyan(),
+        records.len()
+    );
+
+    for record in records {
+        match (
+            record.source_path.strip_prefix(base_path),
+            record.destination_path.strip_prefix(base_path),
+        ) {
+            (Ok(rel_source), Ok(rel_dest)) => println!(
+                "  {} -> {}",
+                rel_source.display().to_string().red(),
+                rel_dest.display().to_string().green()
+            ),
+            _ => println!(
+                "  {} -> {}",
+                record.source_path.display(),
+                record.destination_path.display()
+            ),
+        }
+    }
+}
+
+pub(super) fn print_redo_summary(summary: &super::types::UndoSummary) {
+    println!("\n{}", "REDO COMPLETE!".bold().green());
+    println!(
+        "Files moved again: {}, Skipped: {}, Failed: {}",
+        summary.restored_count().to_string().green(),
+        summary.skipped_count().to_string().yellow(),
+        summary.failed_count().to_string().red()
+    );
+    print_skipped_files(summary);
+}
+
 pub(super) fn print_undo_summary(summary: &super::types::UndoSummary) {
     println!("\n{}", "UNDO COMPLETE!".bold().green());
     println!(
@@ -33,4 +71,15 @@ pub(super) fn print_undo_summary(summary: &super::types::UndoSummary) {
         summary.skipped_count().to_string().yellow(),
         summary.failed_count().to_string().red()
     );
+    print_skipped_files(summary);
+}
+
+fn print_skipped_files(summary: &super::types::UndoSummary) {
+    if summary.skipped_count() == 0 {
+        return;
+    }
+    println!("\n{}", "Skipped files:".yellow());
+    for (path, reason) in summary.skipped_files() {
+        println!("  {} - {}", path.display(), reason);
+    }
 }
@@ -1,8 +1,10 @@
 use super::cleanup::cleanup_empty_directories;
 use super::confirmation::ConfirmationStrategy;
 use super::display::display_undo_preview;
+use super::filter::UndoFilter;
 use super::types::{UndoError, UndoSummary};
 use crate::files::move_file_cross_platform;
+use crate::models::FileStamp;
 use crate::storage::UndoLog;
 use colored::*;
 use std::path::Path;
@@ -10,17 +12,23 @@ use std::path::Path;
 pub fn undo_with_strategy<C: ConfirmationStrategy>(
     base_path: &Path,
     undo_log: &mut UndoLog,
+    filter: &UndoFilter,
     confirmation: &C,
     dry_run: bool,
 ) -> Result<UndoSummary, UndoError> {
     let completed_moves: Vec<_> = undo_log
         .get_completed_moves()
         .into_iter()
+        .filter(|record| filter.matches(record, base_path))
         .cloned()
         .collect();
 
     if completed_moves.is_empty() {
-        println!("{}", "No completed moves to undo.".yellow());
+        if *filter == UndoFilter::default() {
+            println!("{}", "No completed moves to undo.".yellow());
+        } else {
+            println!("{}", "No completed moves match the filters.".yellow());
+        }
         return Ok(UndoSummary::new());
     }
 
@@ -31,7 +39,7 @@ pub fn undo_with_strategy<C: ConfirmationStrategy>(
         return Ok(UndoSummary::new());
     }
 
-    confirmation.confirm()?;
+    confirmation.confirm("undo")?;
 
     println!("\n{}", "--- UNDOING MOVES ---".bold().underline());
 
@@ -52,12 +60,7 @@ pub fn undo_with_strategy<C: ConfirmationStrategy>(
         }
 
         if source.exists() {
-            eprintln!(
-                "{} Skipping {} - source already exists",
-                "WARN:".yellow(),
-                source.display()
-            );
-            summary.skipped();
+            summary.skipped(source, "a file already exists at the original location");
             continue;
         }
 
@@ -69,7 +72,7 @@ pub fn undo_with_strategy<C: ConfirmationStrategy>(
                     source.display().to_string().green()
                 );
                 summary.restored();
-                undo_log.mark_as_undone(destination);
+                undo_log.mark_record_as_undone(&record, FileStamp::read(source));
             }
             Err(e) => {
                 eprintln!(
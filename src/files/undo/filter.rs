@@ -0,0 +1,176 @@
+use crate::models::FileMoveRecord;
+use glob::{MatchOptions, Pattern};
+use std::path::{Path, is_separator};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Selects which completed moves an undo reverts. Every criterion that is
+/// set must match; the default matches all moves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UndoFilter {
+    /// Only moves made in this `UndoSession`
+    pub session: Option<u64>,
+    /// Glob the original path must match
+    pub source: Option<Pattern>,
+    /// Glob the organized path must match
+    pub destination: Option<Pattern>,
+    /// Category folder the file was moved into, e.g. "Code"
+    pub category: Option<String>,
+    /// Only moves made at or after this Unix time
+    pub since: Option<u64>,
+    /// Only moves made at or before this Unix time
+    pub until: Option<u64>,
+}
+
+impl UndoFilter {
+    pub fn session(id: u64) -> Self {
+        Self {
+            session: Some(id),
+            ..Self::default()
+        }
+    }
+
+    /// Whether `record` is selected. Globs and the category are resolved
+    /// against `base_path`, the folder that was organized.
+    pub fn matches(&self, record: &FileMoveRecord, base_path: &Path) -> bool {
+        self.session.is_none_or(|id| record.session == Some(id))
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp <= until)
+            && self
+                .source
+                .as_ref()
+                .is_none_or(|p| glob_matches(p, &record.source_path, base_path))
+            && self
+                .destination
+                .as_ref()
+                .is_none_or(|p| glob_matches(p, &record.destination_path, base_path))
+            && self
+                .category
+                .as_deref()
+                .is_none_or(|c| category_of(&record.destination_path, base_path) == Some(c))
+    }
+}
+
+/// A pattern without a separator, such as `*.csv`, matches the file name.
+/// Other patterns match the path relative to `base_path` or the full path.
+fn glob_matches(pattern: &Pattern, path: &Path, base_path: &Path) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+
+    if !pattern.as_str().chars().any(is_separator) {
+        return path
+            .file_name()
+            .is_some_and(|name| pattern.matches_with(&name.to_string_lossy(), options));
+    }
+
+    path.strip_prefix(base_path)
+        .is_ok_and(|relative| pattern.matches_path_with(relative, options))
+        || pattern.matches_path_with(path, options)
+}
+
+/// The top-level folder under `base_path` that `destination` was moved into.
+fn category_of<'a>(destination: &'a Path, base_path: &Path) -> Option<&'a str> {
+    let relative = destination.strip_prefix(base_path).ok()?;
+    if relative.parent()?.as_os_str().is_empty() {
+        return None;
+    }
+    relative.components().next()?.as_os_str().to_str()
+}
+
+/// Parses a glob for `--source` and `--dest`.
+pub fn parse_glob(value: &str) -> Result<Pattern, String> {
+    Pattern::new(value).map_err(|e| format!("invalid glob '{}': {}", value, e))
+}
+
+/// Parses a time for `--since`: an age such as `30m`, `2h`, `3d` or `1w`,
+/// or a UTC date as `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`. A bare date is the
+/// start of that day.
+pub fn parse_time(value: &str) -> Result<u64, String> {
+    parse_with(value, parse_time_at)
+}
+
+/// Parses a time for `--until`, in the same formats as `parse_time`. A bare
+/// date is the last second of that day, so the whole day is included.
+pub fn parse_until(value: &str) -> Result<u64, String> {
+    parse_with(value, parse_until_at)
+}
+
+fn parse_with(value: &str, parse: fn(&str, u64) -> Option<u64>) -> Result<u64, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    parse(value.trim(), now).ok_or_else(|| {
+        format!(
+            "invalid time '{}': use YYYY-MM-DD[ HH:MM] or an age like 2h or 3d",
+            value
+        )
+    })
+}
+
+fn parse_time_at(value: &str, now: u64) -> Option<u64> {
+    parse_bound_at(value, now, false)
+}
+
+fn parse_until_at(value: &str, now: u64) -> Option<u64> {
+    parse_bound_at(value, now, true)
+}
+
+fn parse_bound_at(value: &str, now: u64, end_of_day: bool) -> Option<u64> {
+    if let Some(unit) = value.chars().last().filter(char::is_ascii_alphabetic) {
+        let seconds = match unit {
+            'm' => 60,
+            'h' => 3_600,
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => return None,
+        };
+        let amount: u64 = value[..value.len() - 1].parse().ok()?;
+        return Some(now.saturating_sub(amount.checked_mul(seconds)?));
+    }
+
+    let (date, time) = match value.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let seconds_of_day = match time {
+        Some(time) => {
+            let (hour, minute) = time.split_once(':')?;
+            let (hour, minute): (u64, u64) = (hour.parse().ok()?, minute.parse().ok()?);
+            if hour > 23 || minute > 59 {
+                return None;
+            }
+            hour * 3_600 + minute * 60
+        }
+        None if end_of_day => 86_399,
+        None => 0,
+    };
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * 86_400 + seconds_of_day)
+}
+
+// Howard Hinnant's civil-to-days conversion, the inverse of `format_date`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+#[path = "filter_test.rs"]
+mod tests;
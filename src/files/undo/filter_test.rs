@@ -0,0 +1,146 @@
+use super::*;
+use crate::models::MoveStatus;
+use std::path::PathBuf;
+
+fn record(source: &str, destination: &str, timestamp: u64) -> FileMoveRecord {
+    let mut record = FileMoveRecord::new(
+        PathBuf::from(source),
+        PathBuf::from(destination),
+        MoveStatus::Completed,
+    );
+    record.timestamp = timestamp;
+    record
+}
+
+fn base() -> &'static Path {
+    Path::new("/dl")
+}
+
+#[test]
+fn test_default_filter_matches_everything() {
+    let filter = UndoFilter::default();
+    assert!(filter.matches(&record("/dl/a.csv", "/dl/Code/a.csv", 1), base()));
+    assert!(filter.matches(&record("/x/a.csv", "/y/a.csv", 1), base()));
+}
+
+#[test]
+fn test_glob_without_separator_matches_file_name() {
+    let filter = UndoFilter {
+        source: Some(parse_glob("*.csv").unwrap()),
+        ..Default::default()
+    };
+    assert!(filter.matches(&record("/dl/sub/a.csv", "/dl/Code/a.csv", 1), base()));
+    assert!(!filter.matches(&record("/dl/a.rs", "/dl/Code/a.rs", 1), base()));
+}
+
+#[test]
+fn test_glob_with_separator_matches_relative_or_full_path() {
+    let relative = UndoFilter {
+        destination: Some(parse_glob("Code/*.csv").unwrap()),
+        ..Default::default()
+    };
+    assert!(relative.matches(&record("/dl/a.csv", "/dl/Code/a.csv", 1), base()));
+    assert!(!relative.matches(&record("/dl/a.csv", "/dl/Code/Data/a.csv", 1), base()));
+
+    let recursive = UndoFilter {
+        destination: Some(parse_glob("Code/**/*.csv").unwrap()),
+        ..Default::default()
+    };
+    assert!(recursive.matches(&record("/dl/a.csv", "/dl/Code/Data/a.csv", 1), base()));
+
+    let absolute = UndoFilter {
+        destination: Some(parse_glob("/dl/Code/*").unwrap()),
+        ..Default::default()
+    };
+    assert!(absolute.matches(&record("/dl/a.csv", "/dl/Code/a.csv", 1), base()));
+}
+
+#[test]
+fn test_category_matches_top_level_folder() {
+    let filter = UndoFilter {
+        category: Some("Code".to_string()),
+        ..Default::default()
+    };
+    assert!(filter.matches(&record("/dl/a.csv", "/dl/Code/a.csv", 1), base()));
+    assert!(filter.matches(&record("/dl/b.csv", "/dl/Code/Data/b.csv", 1), base()));
+    assert!(!filter.matches(&record("/dl/c.csv", "/dl/Documents/c.csv", 1), base()));
+    assert!(!filter.matches(&record("/dl/Code", "/dl/Code", 1), base()));
+    assert!(!filter.matches(&record("/x/a.csv", "/x/Code/a.csv", 1), base()));
+}
+
+#[test]
+fn test_time_range_is_inclusive() {
+    let filter = UndoFilter {
+        since: Some(100),
+        until: Some(200),
+        ..Default::default()
+    };
+    assert!(!filter.matches(&record("/dl/a", "/dl/A/a", 99), base()));
+    assert!(filter.matches(&record("/dl/a", "/dl/A/a", 100), base()));
+    assert!(filter.matches(&record("/dl/a", "/dl/A/a", 200), base()));
+    assert!(!filter.matches(&record("/dl/a", "/dl/A/a", 201), base()));
+}
+
+#[test]
+fn test_all_criteria_must_match() {
+    let mut matching = record("/dl/a.csv", "/dl/Code/a.csv", 150);
+    matching.session = Some(2);
+    let filter = UndoFilter {
+        session: Some(2),
+        source: Some(parse_glob("*.csv").unwrap()),
+        category: Some("Code".to_string()),
+        since: Some(100),
+        ..Default::default()
+    };
+    assert!(filter.matches(&matching, base()));
+
+    let mut other_session = matching.clone();
+    other_session.session = Some(1);
+    assert!(!filter.matches(&other_session, base()));
+}
+
+#[test]
+fn test_parse_time_dates_and_ages() {
+    assert_eq!(parse_time_at("1970-01-01", 0), Some(0));
+    assert_eq!(parse_time_at("2024-03-01", 0), Some(1_709_251_200));
+    assert_eq!(parse_time_at("2024-03-01 12:30", 0), Some(1_709_296_200));
+    assert_eq!(parse_time_at("2024-03-01T12:30", 0), Some(1_709_296_200));
+    assert_eq!(parse_time_at("2h", 10_000), Some(2_800));
+    assert_eq!(parse_time_at("3d", 10_000), Some(0));
+    assert_eq!(parse_time_at("1w", 1_000_000), Some(395_200));
+}
+
+#[test]
+fn test_parse_until_includes_the_whole_day() {
+    let end_of_day = 1_709_251_200 + 86_399;
+    assert_eq!(parse_until_at("2024-03-01", 0), Some(end_of_day));
+    assert_eq!(parse_until_at("2024-03-01 12:30", 0), Some(1_709_296_200));
+    assert_eq!(parse_until_at("2h", 10_000), Some(2_800));
+
+    let filter = UndoFilter {
+        until: Some(parse_until_at("2024-03-01", 0).unwrap()),
+        ..Default::default()
+    };
+    assert!(filter.matches(&record("/dl/a", "/dl/A/a", 1_709_296_200), base()));
+    assert!(filter.matches(&record("/dl/a", "/dl/A/a", end_of_day), base()));
+    assert!(!filter.matches(&record("/dl/a", "/dl/A/a", end_of_day + 1), base()));
+}
+
+#[test]
+fn test_parse_time_rejects_invalid_values() {
+    for value in [
+        "",
+        "yesterday",
+        "2024-13-01",
+        "2024-01-01 25:00",
+        "5y",
+        "-1d",
+        "1969-12-31",
+    ] {
+        assert_eq!(parse_time_at(value, 0), None, "{}", value);
+        assert_eq!(parse_until_at(value, 0), None, "{}", value);
+    }
+    assert!(parse_time("soon").is_err());
+    assert!(parse_until("soon").is_err());
+    assert!(parse_glob("[").is_err());
+}
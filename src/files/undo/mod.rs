@@ -7,20 +7,24 @@ mod cleanup;
 mod confirmation;
 mod display;
 mod execution;
+mod filter;
+mod redo;
 mod types;
 
 use confirmation::{AutoConfirm, StdinConfirmation};
-use display::print_undo_summary;
+use display::{print_redo_summary, print_undo_summary};
 
+pub use filter::{UndoFilter, parse_glob, parse_time, parse_until};
 pub use types::{UndoError, UndoSummary};
 
 pub fn undo_moves(
     base_path: &Path,
     undo_log: &mut UndoLog,
+    filter: &UndoFilter,
     dry_run: bool,
 ) -> Result<(usize, usize, usize)> {
     let confirmation = StdinConfirmation;
-    match execution::undo_with_strategy(base_path, undo_log, &confirmation, dry_run) {
+    match execution::undo_with_strategy(base_path, undo_log, filter, &confirmation, dry_run) {
         Ok(summary) => {
             if !dry_run {
                 print_undo_summary(&summary);
@@ -45,8 +49,49 @@ pub fn undo_moves(
 pub fn undo_moves_auto(
     base_path: &Path,
     undo_log: &mut UndoLog,
+    filter: &UndoFilter,
     dry_run: bool,
 ) -> std::result::Result<UndoSummary, UndoError> {
     let confirmation = AutoConfirm;
-    execution::undo_with_strategy(base_path, undo_log, &confirmation, dry_run)
+    execution::undo_with_strategy(base_path, undo_log, filter, &confirmation, dry_run)
+}
+
+/// Re-applies undone moves selected by `filter`, asking for confirmation.
+pub fn redo_moves(
+    base_path: &Path,
+    undo_log: &mut UndoLog,
+    filter: &UndoFilter,
+    dry_run: bool,
+) -> Result<(usize, usize, usize)> {
+    let confirmation = StdinConfirmation;
+    match redo::redo_with_strategy(base_path, undo_log, filter, &confirmation, dry_run) {
+        Ok(summary) => {
+            if !dry_run {
+                print_redo_summary(&summary);
+            }
+            Ok((
+                summary.restored_count(),
+                summary.skipped_count(),
+                summary.failed_count(),
+            ))
+        }
+        Err(e) => {
+            if matches!(e, UndoError::UserCancelled) {
+                println!("\n{}", "Redo cancelled.".red());
+            } else {
+                eprintln!("\n{}", format!("{}", e).red());
+            }
+            Ok((0, 0, 0))
+        }
+    }
+}
+
+pub fn redo_moves_auto(
+    base_path: &Path,
+    undo_log: &mut UndoLog,
+    filter: &UndoFilter,
+    dry_run: bool,
+) -> std::result::Result<UndoSummary, UndoError> {
+    let confirmation = AutoConfirm;
+    redo::redo_with_strategy(base_path, undo_log, filter, &confirmation, dry_run)
 }
@@ -0,0 +1,120 @@
+use super::confirmation::ConfirmationStrategy;
+use super::display::display_redo_preview;
+use super::filter::UndoFilter;
+use super::types::{UndoError, UndoSummary};
+use crate::files::move_file_cross_platform;
+use crate::models::FileStamp;
+use crate::storage::UndoLog;
+use colored::*;
+use std::fs;
+use std::path::Path;
+
+/// Moves files back to where an undone organize put them. A file is only
+/// moved if it is unchanged since the undo; like undo, an existing file at
+/// the target is never overwritten. Files left in place are listed in the
+/// summary with the reason.
+pub fn redo_with_strategy<C: ConfirmationStrategy>(
+    base_path: &Path,
+    undo_log: &mut UndoLog,
+    filter: &UndoFilter,
+    confirmation: &C,
+    dry_run: bool,
+) -> Result<UndoSummary, UndoError> {
+    let undone_moves: Vec<_> = undo_log
+        .get_undone_moves()
+        .into_iter()
+        .filter(|record| filter.matches(record, base_path))
+        .cloned()
+        .collect();
+
+    if undone_moves.is_empty() {
+        println!("{}", "No undone moves to redo.".yellow());
+        return Ok(UndoSummary::new());
+    }
+
+    display_redo_preview(&undone_moves, base_path);
+
+    if dry_run {
+        println!("\n{}", "Dry run mode - skipping redo operation.".cyan());
+        return Ok(UndoSummary::new());
+    }
+
+    confirmation.confirm("redo")?;
+
+    println!("\n{}", "--- REDOING MOVES ---".bold().underline());
+
+    let mut summary = UndoSummary::new();
+
+    for record in undone_moves {
+        let source = &record.source_path;
+        let destination = &record.destination_path;
+
+        let Some(current) = FileStamp::read(source) else {
+            eprintln!(
+                "{} File not found at original location: {}",
+                "WARN:".yellow(),
+                source.display()
+            );
+            summary.failed();
+            continue;
+        };
+
+        let Some(undone_file) = record.undone_file else {
+            summary.skipped(
+                source,
+                "undone before redo was supported, so it cannot be checked for changes",
+            );
+            continue;
+        };
+
+        if undone_file != current {
+            summary.skipped(source, "changed since it was restored");
+            continue;
+        }
+
+        if destination.exists() {
+            let reason = format!(
+                "another file now exists at {}; move or rename it and redo again",
+                destination.display()
+            );
+            summary.skipped(source, &reason);
+            continue;
+        }
+
+        if let Some(parent) = destination.parent()
+            && let Err(e) = fs::create_dir_all(parent)
+        {
+            eprintln!(
+                "{} Failed to create {}: {}",
+                "ERROR:".red(),
+                parent.display(),
+                e
+            );
+            summary.failed();
+            continue;
+        }
+
+        match move_file_cross_platform(source, destination) {
+            Ok(_) => {
+                println!(
+                    "Moved again: {} -> {}",
+                    source.display().to_string().red(),
+                    destination.display().to_string().green()
+                );
+                summary.restored();
+                undo_log.mark_record_as_redone(&record);
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to move {}: {}",
+                    "ERROR:".red(),
+                    source.display(),
+                    e
+                );
+                summary.failed();
+            }
+        }
+    }
+
+    Ok(summary)
+}
@@ -1,10 +1,12 @@
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Default)]
 pub struct UndoSummary {
     restored_count: usize,
-    skipped_count: usize,
     failed_count: usize,
+    /// Files left where they are, with the reason, in the order seen.
+    skipped: Vec<(PathBuf, String)>,
 }
 
 impl UndoSummary {
@@ -16,8 +18,9 @@ impl UndoSummary {
         self.restored_count += 1;
     }
 
-    pub fn skipped(&mut self) {
-        self.skipped_count += 1;
+    /// Records that `path` was left where it is because of `reason`.
+    pub fn skipped(&mut self, path: &Path, reason: &str) {
+        self.skipped.push((path.to_path_buf(), reason.to_string()));
     }
 
     pub fn failed(&mut self) {
@@ -29,7 +32,12 @@ impl UndoSummary {
     }
 
     pub fn skipped_count(&self) -> usize {
-        self.skipped_count
+        self.skipped.len()
+    }
+
+    /// Skipped files and why each was skipped.
+    pub fn skipped_files(&self) -> &[(PathBuf, String)] {
+        &self.skipped
     }
 
     pub fn failed_count(&self) -> usize {
@@ -37,7 +45,7 @@ impl UndoSummary {
     }
 
     pub fn total_processed(&self) -> usize {
-        self.restored_count + self.skipped_count + self.failed_count
+        self.restored_count + self.skipped.len() + self.failed_count
     }
 
     pub fn has_failures(&self) -> bool {
@@ -0,0 +1,131 @@
+use crate::gemini::types::SubCategoryItem;
+
+/// Rough characters-per-token ratio used for estimates; errs on the high side
+/// for the short, punctuation-heavy filenames we send.
+const CHARS_PER_TOKEN: usize = 3;
+/// Prompt text sent once per request besides the file list.
+const PROMPT_OVERHEAD_TOKENS: usize = 150;
+/// JSON wrapping for one `{ "filename": ..., "category": ... }` entry.
+const OUTPUT_ENTRY_OVERHEAD_TOKENS: usize = 12;
+/// Allowance for the category name the model writes for each file.
+const OUTPUT_CATEGORY_TOKENS: usize = 6;
+/// Allowance for the confidence score and one-line reasoning per file.
+const OUTPUT_REASONING_TOKENS: usize = 30;
+
+/// JSON wrapping for one `"filename": "sub-folder"` entry.
+const OUTPUT_MAP_ENTRY_OVERHEAD_TOKENS: usize = 4;
+/// Per-line JSON keys around each file in a sub-category prompt.
+const SUB_CATEGORY_ITEM_OVERHEAD_TOKENS: usize = 12;
+
+/// Limits used to size categorization batches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBudget {
+    pub max_input_tokens: usize,
+    pub max_output_tokens: usize,
+    pub max_files: usize,
+    /// Extra prompt tokens each file costs for the metadata sent with it.
+    pub context_tokens_per_file: usize,
+}
+
+impl Default for TokenBudget {
+    fn default() -> Self {
+        Self {
+            max_input_tokens: 16_000,
+            max_output_tokens: 8_000,
+            max_files: 200,
+            context_tokens_per_file: 0,
+        }
+    }
+}
+
+impl TokenBudget {
+    /// Output tokens handed to the API, leaving headroom over the batch estimate.
+    pub fn output_limit(&self) -> usize {
+        self.max_output_tokens + self.max_output_tokens / 4
+    }
+}
+
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+fn input_cost(filename: &str, budget: &TokenBudget) -> usize {
+    // Each name is joined with ", " in the prompt
+    estimate_tokens(filename) + 1 + budget.context_tokens_per_file
+}
+
+fn output_cost(filename: &str) -> usize {
+    // The model echoes the filename back alongside its category
+    estimate_tokens(filename)
+        + OUTPUT_CATEGORY_TOKENS
+        + OUTPUT_REASONING_TOKENS
+        + OUTPUT_ENTRY_OVERHEAD_TOKENS
+}
+
+/// Splits `filenames` into consecutive batches whose estimated prompt and
+/// response sizes stay within `budget`. Every batch holds at least one file.
+pub fn plan_batches(filenames: &[String], budget: &TokenBudget) -> Vec<Vec<String>> {
+    plan_by_cost(filenames, budget, |filename| {
+        (input_cost(filename, budget), output_cost(filename))
+    })
+}
+
+/// Splits sub-category items into consecutive batches the same way
+/// `plan_batches` does, counting each item's snippet toward the prompt.
+pub fn plan_sub_category_batches(
+    items: &[SubCategoryItem],
+    budget: &TokenBudget,
+) -> Vec<Vec<SubCategoryItem>> {
+    plan_by_cost(items, budget, |item| {
+        let input = estimate_tokens(&item.filename)
+            + estimate_tokens(&item.category)
+            + estimate_tokens(&item.snippet)
+            + SUB_CATEGORY_ITEM_OVERHEAD_TOKENS;
+        let output = estimate_tokens(&item.filename)
+            + OUTPUT_CATEGORY_TOKENS
+            + OUTPUT_MAP_ENTRY_OVERHEAD_TOKENS;
+        (input, output)
+    })
+}
+
+/// Packs `items` into consecutive batches, where `cost` gives the estimated
+/// prompt and response tokens of one item. A batch is closed before it
+/// would exceed `budget`, but always holds at least one item.
+fn plan_by_cost<T: Clone>(
+    items: &[T],
+    budget: &TokenBudget,
+    cost: impl Fn(&T) -> (usize, usize),
+) -> Vec<Vec<T>> {
+    let mut batches = Vec::new();
+    let mut current = Vec::new();
+    let mut input_tokens = PROMPT_OVERHEAD_TOKENS;
+    let mut output_tokens = 0;
+
+    for item in items {
+        let (input, output) = cost(item);
+
+        let over_budget = input_tokens + input > budget.max_input_tokens
+            || output_tokens + output > budget.max_output_tokens
+            || current.len() >= budget.max_files;
+
+        if over_budget && !current.is_empty() {
+            batches.push(std::mem::take(&mut current));
+            input_tokens = PROMPT_OVERHEAD_TOKENS;
+            output_tokens = 0;
+        }
+
+        input_tokens += input;
+        output_tokens += output;
+        current.push(item.clone());
+    }
+
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    batches
+}
+
+#[cfg(test)]
+#[path = "batching_test.rs"]
+mod tests;
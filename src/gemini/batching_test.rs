@@ -0,0 +1,131 @@
+use super::*;
+
+fn names(count: usize, len: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| format!("{:0>width$}", i, width = len))
+        .collect()
+}
+
+#[test]
+fn test_estimate_tokens() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("abc"), 1);
+    assert_eq!(estimate_tokens("abcd"), 2);
+}
+
+#[test]
+fn test_plan_batches_empty() {
+    assert!(plan_batches(&[], &TokenBudget::default()).is_empty());
+}
+
+#[test]
+fn test_plan_batches_small_list_single_batch() {
+    let files = names(10, 12);
+    let batches = plan_batches(&files, &TokenBudget::default());
+
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0], files);
+}
+
+#[test]
+fn test_plan_batches_respects_max_files() {
+    let budget = TokenBudget {
+        max_files: 4,
+        ..Default::default()
+    };
+    let files = names(10, 8);
+    let batches = plan_batches(&files, &budget);
+
+    assert_eq!(batches.len(), 3);
+    assert!(batches.iter().all(|b| b.len() <= 4));
+    assert_eq!(batches.concat(), files);
+}
+
+#[test]
+fn test_plan_batches_long_paths_use_smaller_batches() {
+    let budget = TokenBudget::default();
+    let short = plan_batches(&names(400, 10), &budget);
+    let long = plan_batches(&names(400, 200), &budget);
+
+    assert!(long.len() > short.len());
+    assert_eq!(long.concat(), names(400, 200));
+}
+
+#[test]
+fn test_plan_batches_respects_output_budget() {
+    let budget = TokenBudget {
+        max_output_tokens: 200,
+        ..Default::default()
+    };
+    let files = names(50, 30);
+    let batches = plan_batches(&files, &budget);
+
+    for batch in &batches {
+        let output: usize = batch.iter().map(|f| output_cost(f)).sum();
+        assert!(output <= budget.max_output_tokens);
+    }
+}
+
+#[test]
+fn test_plan_batches_oversized_file_gets_own_batch() {
+    let budget = TokenBudget {
+        max_output_tokens: 20,
+        ..Default::default()
+    };
+    let files = vec!["x".repeat(300), "a.txt".to_string()];
+    let batches = plan_batches(&files, &budget);
+
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0], vec!["x".repeat(300)]);
+}
+
+#[test]
+fn test_plan_batches_accounts_for_context_tokens() {
+    let files = names(400, 10);
+    let budget = TokenBudget {
+        max_output_tokens: 100_000,
+        max_files: 1000,
+        ..Default::default()
+    };
+    let plain = plan_batches(&files, &budget);
+    let with_context = plan_batches(
+        &files,
+        &TokenBudget {
+            context_tokens_per_file: 100,
+            ..budget
+        },
+    );
+
+    assert!(with_context.len() > plain.len());
+}
+
+fn item(filename: &str, snippet_len: usize) -> SubCategoryItem {
+    SubCategoryItem {
+        filename: filename.to_string(),
+        category: "Documents".to_string(),
+        snippet: "x".repeat(snippet_len),
+    }
+}
+
+#[test]
+fn test_plan_sub_category_batches_groups_small_snippets() {
+    let items: Vec<_> = (0..10).map(|i| item(&format!("{}.txt", i), 100)).collect();
+    let batches = plan_sub_category_batches(&items, &TokenBudget::default());
+
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0], items);
+}
+
+#[test]
+fn test_plan_sub_category_batches_splits_on_snippet_size() {
+    let budget = TokenBudget {
+        max_input_tokens: 1_000,
+        ..TokenBudget::default()
+    };
+    // Each snippet costs ~500 tokens, so only one fits next to the prompt
+    let items: Vec<_> = (0..3).map(|i| item(&format!("{}.txt", i), 1500)).collect();
+    let batches = plan_sub_category_batches(&items, &budget);
+
+    assert_eq!(batches.len(), 3);
+    assert!(batches.iter().all(|b| b.len() == 1));
+}
@@ -1,30 +1,76 @@
+use crate::error::AppError;
+use crate::files::{
+    InspectionInput, InspectionTarget, MediaBudget, MediaPart, SubFolderIndex, collect_file_context,
+};
+use crate::gemini::batching::{
+    TokenBudget, estimate_tokens, plan_batches, plan_sub_category_batches,
+};
+use crate::gemini::discovery::normalize_proposals;
 use crate::gemini::errors::GeminiError;
 use crate::gemini::prompt::PromptBuilder;
-use crate::gemini::types::{GeminiResponse, OrganizationPlanResponse};
-use crate::models::OrganizationPlan;
-use crate::storage::Cache;
-use log::{debug, error, info};
+use crate::gemini::rate_limit::RateLimitGate;
+use crate::gemini::reconcile::{Reconciliation, reconcile_plan, reconcile_sub_categories};
+use crate::gemini::templates::PromptTemplates;
+use crate::gemini::types::{
+    CategoryProposal, CategoryProposalResponse, GeminiResponse, GenerateContentRequest, InlineData,
+    OrganizationPlanResponse, RequestContent, RequestPart, SubCategoryItem, SubCategoryResults,
+};
+use crate::gemini::usage::{UsageReservation, UsageTracker};
+use crate::models::{
+    CacheFingerprint, FileCategory, FileContextFields, MultimodalConfig, OrganizationPlan,
+    RequestKind,
+};
+use crate::settings::Config;
+use crate::storage::{Cache, CacheLookup};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use futures::future::join_all;
+use log::{debug, error, info, warn};
 use reqwest::Client;
+use serde::Serialize;
 use serde_json::json;
+use std::collections::HashMap;
 use std::path::Path;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
+const API_KEY_HEADER: &str = "x-goog-api-key";
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
 const DEFAULT_MODEL: &str = "gemini-3-flash-preview";
 const DEFAULT_TIMEOUT_SECS: u64 = 120;
 const MAX_RETRIES: u32 = 3;
-const BATCH_SIZE: usize = 50;
+const MAX_RECONCILE_RETRIES: u32 = 2;
+const DEFAULT_MAX_CONCURRENT: usize = 5;
+/// Characters of each file's content sample sent in a batched sub-category
+/// request.
+const BATCH_SNIPPET_CHARS: usize = 1500;
+
+/// Results of `GeminiClient::request_halving`.
+struct Halved<T, R> {
+    /// The result of every successful request, in item order.
+    results: Vec<R>,
+    /// Items whose response was incomplete even when sent alone.
+    failed: Vec<(T, GeminiError)>,
+}
 
 #[derive(Clone)]
 pub struct GeminiClient {
     api_key: String,
     client: Client,
     base_url: String,
-    #[allow(dead_code)]
     model: String,
-    #[allow(dead_code)]
     timeout: Duration,
+    max_retry_delay: Option<Duration>,
     categories: Vec<String>,
     silent: bool,
+    max_concurrent: usize,
+    token_budget: TokenBudget,
+    file_context: FileContextFields,
+    rate_limit: RateLimitGate,
+    usage: UsageTracker,
+    multimodal: MultimodalConfig,
+    media_budget: MediaBudget,
+    prompts: PromptTemplates,
 }
 
 impl GeminiClient {
@@ -40,7 +86,7 @@ impl GeminiClient {
         info!("Checking Gemini API connectivity");
         debug!("Connectivity check URL: {}", url);
 
-        match self.client.post(&url).json(&request_body).send().await {
+        match self.post(&url).json(&request_body).send().await {
             Ok(response) => {
                 if response.status().is_success() {
                     info!("Gemini API connectivity check successful");
@@ -78,7 +124,7 @@ impl GeminiClient {
     ) -> Self {
         let timeout = Duration::from_secs(DEFAULT_TIMEOUT_SECS);
         let client = Self::build_client(timeout);
-        let base_url = Self::build_base_url(&model);
+        let base_url = Self::build_base_url(DEFAULT_BASE_URL, &model);
 
         Self {
             api_key,
@@ -86,8 +132,17 @@ impl GeminiClient {
             base_url,
             model,
             timeout,
+            max_retry_delay: None,
             categories,
             silent,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+            token_budget: TokenBudget::default(),
+            file_context: FileContextFields::default(),
+            rate_limit: RateLimitGate::new(),
+            usage: UsageTracker::new(),
+            multimodal: MultimodalConfig::default(),
+            media_budget: MediaBudget::new(0),
+            prompts: PromptTemplates::default(),
         }
     }
 
@@ -100,10 +155,91 @@ impl GeminiClient {
         )
     }
 
+    /// Builds a client from the settings in `config`: its API key,
+    /// categories, API root, file context, attachments and prompt templates.
+    /// The key may be empty when none is configured; see `has_api_key`.
+    pub fn from_config(config: &Config) -> Result<Self, AppError> {
+        let api_key = config.resolve_api_key()?;
+        let mut client = Self::new(&api_key, &config.categories);
+        if let Some(base_url) = &config.api_base_url {
+            client.set_base_url(base_url);
+        }
+        client.set_file_context(config.file_context.clone());
+        client.set_multimodal(config.multimodal.clone());
+        client.set_prompt_templates(config.prompt_templates()?);
+        Ok(client)
+    }
+
+    /// Whether the client was given a non-empty API key.
+    pub fn has_api_key(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
     pub fn set_silent(&mut self, silent: bool) {
         self.silent = silent;
     }
 
+    /// Points the client at another Gemini-compatible API root, such as a
+    /// proxy or a local mock server. Defaults to
+    /// `https://generativelanguage.googleapis.com/v1beta`.
+    pub fn set_base_url(&mut self, base_url: &str) {
+        self.base_url = Self::build_base_url(base_url, &self.model);
+    }
+
+    /// Sets how long a single request may take before it is abandoned.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+        self.client = Self::build_client(timeout);
+    }
+
+    /// Caps how long the client waits before retrying a failed request,
+    /// whatever delay the error or the API asks for.
+    pub fn set_max_retry_delay(&mut self, max_retry_delay: Duration) {
+        self.max_retry_delay = Some(max_retry_delay);
+    }
+
+    /// Sets how many batch requests may be in flight at once.
+    pub fn set_max_concurrent(&mut self, max_concurrent: usize) {
+        self.max_concurrent = max_concurrent.max(1);
+    }
+
+    /// Sets which per-file details are sent with each filename.
+    pub fn set_file_context(&mut self, file_context: FileContextFields) {
+        self.file_context = file_context;
+    }
+
+    /// Sets the token limits used to size categorization batches.
+    pub fn set_token_budget(&mut self, token_budget: TokenBudget) {
+        self.token_budget = token_budget;
+    }
+
+    /// Enables or disables attaching images and PDF pages during deep
+    /// inspection, and resets the run's attachment byte budget.
+    pub fn set_multimodal(&mut self, multimodal: MultimodalConfig) {
+        self.media_budget = MediaBudget::new(if multimodal.enabled {
+            multimodal.max_run_bytes()
+        } else {
+            0
+        });
+        self.multimodal = multimodal;
+    }
+
+    /// Sets the templates prompts are built from.
+    pub fn set_prompt_templates(&mut self, prompts: PromptTemplates) {
+        self.prompts = prompts;
+    }
+
+    /// Usage recorded by this client and its clones.
+    pub fn usage(&self) -> &UsageTracker {
+        &self.usage
+    }
+
+    /// Attachment bytes left to send in this run, shared with deep
+    /// inspection so it stops preparing attachments once they can't be sent.
+    pub fn media_budget(&self) -> &MediaBudget {
+        &self.media_budget
+    }
+
     fn build_client(timeout: Duration) -> Client {
         Client::builder()
             .timeout(timeout)
@@ -111,9 +247,10 @@ impl GeminiClient {
             .unwrap_or_default()
     }
 
-    fn build_base_url(model: &str) -> String {
+    fn build_base_url(base_url: &str, model: &str) -> String {
         format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent",
+            "{}/models/{}:generateContent",
+            base_url.trim_end_matches('/'),
             model
         )
     }
@@ -125,41 +262,330 @@ impl GeminiClient {
         self.organize_files_with_cache(filenames, None, None).await
     }
 
+    /// Categorizes `filenames`, requesting only the files with no cached
+    /// categorization and merging the cached ones back in.
     pub async fn organize_files_with_cache(
         &self,
         filenames: Vec<String>,
         mut cache: Option<&mut Cache>,
         base_path: Option<&Path>,
     ) -> Result<OrganizationPlan, GeminiError> {
-        let url = self.build_url();
+        let lookup = self.lookup_cached(&filenames, cache.as_deref_mut(), base_path);
+        self.organize_looked_up(filenames, lookup, cache, base_path)
+            .await
+    }
 
-        // Check cache first
-        if let Some(ref mut c) = cache
-            && let Some(bp) = base_path
-            && let Some(cached) = c.check_cache(&filenames, bp)
-        {
-            return Ok(cached);
+    /// Requests the files `lookup` found no cached categorization for in a
+    /// single batch, caches the answer and merges the cached files back in.
+    async fn organize_looked_up(
+        &self,
+        filenames: Vec<String>,
+        lookup: CacheLookup,
+        cache: Option<&mut Cache>,
+        base_path: Option<&Path>,
+    ) -> Result<OrganizationPlan, GeminiError> {
+        if lookup.missing.is_empty() {
+            return Ok(self.merge_cached(&filenames, lookup.cached, OrganizationPlan::default()));
+        }
+
+        let plan = self
+            .categorize_with_reconciliation(&lookup.missing, base_path)
+            .await?;
+
+        // Skipped files are left out of the cache so they are retried next run
+        if let (Some(cache), Some(base_path)) = (cache, base_path) {
+            let fingerprint = self.cache_fingerprint().digest();
+            let stored =
+                cache.cache_response(&lookup.missing, plan.clone(), base_path, &fingerprint);
+            if !self.silent {
+                println!("Cached response for {} files", stored);
+            }
+        }
+
+        Ok(self.merge_cached(&filenames, lookup.cached, plan))
+    }
+
+    /// The settings this client's categorizations depend on.
+    pub fn cache_fingerprint(&self) -> CacheFingerprint {
+        CacheFingerprint {
+            categories: self.categories.clone(),
+            model: self.model.clone(),
+            // The categorization prompt also carries the shared file details
+            prompt_version: format!(
+                "{}|{}",
+                self.prompts.categorization_version(),
+                self.file_context.enabled_names()
+            ),
+            backend: self.base_url.clone(),
+        }
+    }
+
+    fn lookup_cached(
+        &self,
+        filenames: &[String],
+        cache: Option<&mut Cache>,
+        base_path: Option<&Path>,
+    ) -> CacheLookup {
+        match (cache, base_path) {
+            (Some(cache), Some(base_path)) => {
+                cache.lookup(filenames, base_path, &self.cache_fingerprint().digest())
+            }
+            _ => CacheLookup {
+                cached: Vec::new(),
+                missing: filenames.to_vec(),
+            },
+        }
+    }
+
+    /// Combines cached categorizations with a plan for the remaining files,
+    /// ordered like `filenames`.
+    fn merge_cached(
+        &self,
+        filenames: &[String],
+        mut cached: Vec<FileCategory>,
+        plan: OrganizationPlan,
+    ) -> OrganizationPlan {
+        if !cached.is_empty() {
+            self.usage
+                .record_cache_hit(RequestKind::Categorization, &self.model, cached.len());
+        }
+
+        cached.extend(plan.files);
+        OrganizationPlan {
+            needs_review: plan.needs_review,
+            ..Self::assemble_plan(filenames, cached, plan.uncategorized)
+        }
+    }
+
+    /// Requests a categorization and re-asks for any files the response
+    /// skipped, returning a plan ordered like `filenames`.
+    async fn categorize_with_reconciliation(
+        &self,
+        filenames: &[String],
+        base_path: Option<&Path>,
+    ) -> Result<OrganizationPlan, GeminiError> {
+        let mut categorized = Vec::with_capacity(filenames.len());
+        let mut pending = filenames.to_vec();
+        let mut failed = Vec::new();
+
+        for attempt in 0..=MAX_RECONCILE_RETRIES {
+            if attempt > 0 {
+                self.print_reconcile_retry(pending.len(), attempt);
+            }
+
+            let plan = match self.request_categorization(&pending, base_path).await {
+                Ok(plan) => plan,
+                Err(e) if attempt > 0 => {
+                    warn!("Re-asking for uncategorized files failed: {}", e);
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let reconciliation = reconcile_plan(&pending, plan.files);
+            self.report_reconciliation(&reconciliation);
+
+            categorized.extend(reconciliation.matched);
+            // Files that failed on their own are not asked for again
+            failed.extend(plan.uncategorized);
+            pending = reconciliation
+                .missing
+                .into_iter()
+                .filter(|f| !failed.contains(f))
+                .collect();
+
+            if pending.is_empty() {
+                break;
+            }
         }
 
-        let prompt = PromptBuilder::new(&filenames).build_categorization_prompt(&self.categories);
+        pending.extend(failed);
+        Ok(Self::assemble_plan(filenames, categorized, pending))
+    }
+
+    /// Requests a categorization, halving the file list and retrying whenever
+    /// the response comes back truncated or unparseable. Files whose response
+    /// is still incomplete when sent alone are listed as uncategorized.
+    async fn request_categorization(
+        &self,
+        filenames: &[String],
+        base_path: Option<&Path>,
+    ) -> Result<OrganizationPlan, GeminiError> {
+        let halved = self
+            .request_halving(filenames, |chunk| async move {
+                self.request_categorization_once(&chunk, base_path).await
+            })
+            .await?;
+
+        Ok(OrganizationPlan {
+            files: halved
+                .results
+                .into_iter()
+                .flat_map(|plan| plan.files)
+                .collect(),
+            uncategorized: halved
+                .failed
+                .into_iter()
+                .map(|(name, e)| {
+                    warn!("Leaving {} uncategorized: {}", name, e);
+                    name
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Sends `items` through `request`, splitting the list in half and
+    /// retrying each half whenever the response comes back truncated or
+    /// unparseable. Returns the result of every request, in item order, and
+    /// the items whose response was still incomplete when sent alone. Other
+    /// errors fail the whole call.
+    async fn request_halving<T, R, F, Fut>(
+        &self,
+        items: &[T],
+        request: F,
+    ) -> Result<Halved<T, R>, GeminiError>
+    where
+        T: Clone,
+        F: Fn(Vec<T>) -> Fut,
+        Fut: Future<Output = Result<R, GeminiError>>,
+    {
+        let mut pending = vec![items.to_vec()];
+        let mut halved = Halved {
+            results: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        while let Some(mut chunk) = pending.pop() {
+            match request(chunk.clone()).await {
+                Ok(result) => halved.results.push(result),
+                Err(e) if e.is_incomplete_response() && chunk.len() > 1 => {
+                    warn!("Incomplete response for {} files: {}", chunk.len(), e);
+                    self.print_split_message(chunk.len());
+                    let (first, second) = chunk.split_at(chunk.len() / 2);
+                    pending.push(second.to_vec());
+                    pending.push(first.to_vec());
+                }
+                Err(e) if e.is_incomplete_response() => {
+                    halved.failed.push((chunk.remove(0), e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(halved)
+    }
+
+    async fn request_categorization_once(
+        &self,
+        filenames: &[String],
+        base_path: Option<&Path>,
+    ) -> Result<OrganizationPlan, GeminiError> {
+        let url = self.build_url();
+        let prompt = self
+            .prompt_builder(filenames, base_path)
+            .build_categorization_prompt(&self.categories, &self.prompts);
         let request_body = self.build_categorization_request(&prompt);
 
-        let res = self.send_request_with_retry(&url, &request_body).await?;
-        let plan = self.parse_categorization_response(res).await?;
+        let started = Instant::now();
+        let (res, _reservation) = self
+            .send_request_with_retry(
+                &url,
+                &request_body,
+                RequestKind::Categorization,
+                estimate_tokens(&prompt),
+            )
+            .await?;
+        self.parse_categorization_response(res, filenames.len(), started)
+            .await
+    }
 
-        // Cache the response
-        if let (Some(cache), Some(base_path)) = (cache, base_path) {
-            cache.cache_response(&filenames, plan.clone(), base_path);
+    /// Includes per-file details in the prompt when any are enabled and the
+    /// files can be located on disk.
+    fn prompt_builder(&self, filenames: &[String], base_path: Option<&Path>) -> PromptBuilder {
+        match base_path {
+            Some(base_path) if self.file_context.any() => {
+                let contexts: Vec<_> = filenames
+                    .iter()
+                    .map(|f| collect_file_context(base_path, f, &self.file_context))
+                    .collect();
+                PromptBuilder::with_context(&contexts)
+            }
+            _ => PromptBuilder::new(filenames),
         }
+    }
 
-        Ok(plan)
+    /// Orders categorized files by their position in the request and records
+    /// the files that are still uncategorized.
+    fn assemble_plan(
+        requested: &[String],
+        mut categorized: Vec<FileCategory>,
+        uncategorized: Vec<String>,
+    ) -> OrganizationPlan {
+        let positions: HashMap<&str, usize> = requested
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+        categorized.sort_by_key(|f| positions.get(f.filename.as_str()).copied());
+
+        OrganizationPlan {
+            files: categorized,
+            uncategorized,
+            ..Default::default()
+        }
+    }
+
+    fn report_reconciliation(&self, reconciliation: &Reconciliation) {
+        for name in &reconciliation.unknown {
+            warn!("Dropping unknown file returned by Gemini: {}", name);
+        }
+        for name in &reconciliation.duplicates {
+            warn!("Ignoring duplicate categorization for: {}", name);
+        }
+
+        if self.silent {
+            return;
+        }
+        if !reconciliation.unknown.is_empty() {
+            println!(
+                "Ignored {} file(s) returned by Gemini that were not requested",
+                reconciliation.unknown.len()
+            );
+        }
+        if !reconciliation.duplicates.is_empty() {
+            println!(
+                "Ignored {} duplicate categorization(s) returned by Gemini",
+                reconciliation.duplicates.len()
+            );
+        }
+    }
+
+    fn print_split_message(&self, batch_len: usize) {
+        if !self.silent {
+            println!(
+                "Gemini's response for {} files was incomplete. Splitting the batch and retrying...",
+                batch_len
+            );
+        }
+    }
+
+    fn print_reconcile_retry(&self, missing: usize, attempt: u32) {
+        if !self.silent {
+            println!(
+                "Gemini skipped {} file(s). Asking again (attempt {}/{})...",
+                missing, attempt, MAX_RECONCILE_RETRIES
+            );
+        }
     }
 
     /// Organizes files in batches to handle large file lists efficiently.
     ///
-    /// When the number of files exceeds BATCH_SIZE, splits them into smaller
-    /// chunks to avoid API timeout and payload size issues. Each batch is
-    /// processed sequentially with progress feedback.
+    /// Splits the files into batches sized by an estimated token budget so
+    /// long paths don't overflow the prompt or the response. Only files
+    /// missing from the cache are batched; the batches are requested
+    /// concurrently, at most `max_concurrent` at a time, and merged back with
+    /// the cached files in their original order.
     ///
     /// # Arguments
     /// * `filenames` - Vector of filenames to organize
@@ -174,75 +600,208 @@ impl GeminiClient {
         mut cache: Option<&mut Cache>,
         base_path: Option<&Path>,
     ) -> Result<OrganizationPlan, GeminiError> {
+        let budget = TokenBudget {
+            context_tokens_per_file: if base_path.is_some() {
+                self.file_context.estimated_tokens()
+            } else {
+                0
+            },
+            ..self.token_budget
+        };
+        let lookup = self.lookup_cached(&filenames, cache.as_deref_mut(), base_path);
+        let batches = plan_batches(&lookup.missing, &budget);
+
         // No batching needed for small file lists
-        if filenames.len() <= BATCH_SIZE {
+        if batches.len() <= 1 {
             return self
-                .organize_files_with_cache(filenames, cache, base_path)
+                .organize_looked_up(filenames, lookup, cache, base_path)
                 .await;
         }
 
-        let total_files = filenames.len();
-        let total_batches = total_files.div_ceil(BATCH_SIZE);
+        let total_batches = batches.len();
 
         if !self.silent {
             println!(
-                "Processing {} files in {} batches...",
-                total_files, total_batches
+                "Processing {} files in {} batches (up to {} at a time)...",
+                lookup.missing.len(),
+                total_batches,
+                self.max_concurrent
             );
         }
 
-        let mut all_files = Vec::with_capacity(total_files);
-
-        for batch_index in 0..total_batches {
-            let start = batch_index * BATCH_SIZE;
-            let end = std::cmp::min(start + BATCH_SIZE, total_files);
-            let batch = filenames[start..end].to_vec();
+        let semaphore = Semaphore::new(self.max_concurrent);
+        let tasks: Vec<_> = batches
+            .iter()
+            .enumerate()
+            .map(|(index, batch)| {
+                let semaphore = &semaphore;
+                async move {
+                    let _permit = semaphore
+                        .acquire()
+                        .await
+                        .expect("batch semaphore is never closed");
+                    let result = self.categorize_with_reconciliation(batch, base_path).await;
+                    if result.is_ok() && !self.silent {
+                        println!(
+                            "Finished batch {}/{} ({} files)",
+                            index + 1,
+                            total_batches,
+                            batch.len()
+                        );
+                    }
+                    (index, result)
+                }
+            })
+            .collect();
 
-            if !self.silent {
-                println!(
-                    "Processing batch {}/{} ({} files)...",
-                    batch_index + 1,
-                    total_batches,
-                    batch.len()
-                );
+        let fingerprint = self.cache_fingerprint().digest();
+        let mut combined = OrganizationPlan::default();
+        let mut first_error = None;
+        for (index, result) in join_all(tasks).await {
+            match result {
+                Ok(plan) => {
+                    // Finished batches are cached even if another one fails
+                    if let (Some(c), Some(bp)) = (cache.as_deref_mut(), base_path) {
+                        let stored =
+                            c.cache_response(&batches[index], plan.clone(), bp, &fingerprint);
+                        if !self.silent {
+                            println!("Cached response for {} files", stored);
+                        }
+                    }
+                    combined.files.extend(plan.files);
+                    combined.uncategorized.extend(plan.uncategorized);
+                    combined.needs_review.extend(plan.needs_review);
+                }
+                Err(e) => {
+                    error!("Batch {}/{} failed: {}", index + 1, total_batches, e);
+                    first_error.get_or_insert(e);
+                }
             }
+        }
 
-            let plan = self
-                .organize_files_with_cache(batch, cache.as_deref_mut(), base_path)
-                .await?;
-
-            all_files.extend(plan.files);
+        if let Some(e) = first_error {
+            return Err(e);
         }
 
-        Ok(OrganizationPlan { files: all_files })
+        Ok(self.merge_cached(&filenames, lookup.cached, combined))
+    }
+
+    /// Asks the model to propose top-level categories for a folder of
+    /// `total_files` files, from a sample of their names. The proposals are
+    /// cleaned up with `normalize_proposals`.
+    pub async fn propose_categories(
+        &self,
+        sample: &[String],
+        total_files: usize,
+    ) -> Result<Vec<CategoryProposal>, GeminiError> {
+        let url = self.build_url();
+        let prompt = PromptBuilder::build_discovery_prompt(sample, total_files);
+        let request_body = self.build_categorization_request(&prompt);
+
+        let started = Instant::now();
+        let (res, _reservation) = self
+            .send_request_with_retry(
+                &url,
+                &request_body,
+                RequestKind::Discovery,
+                estimate_tokens(&prompt),
+            )
+            .await?;
+        let raw_text = self
+            .read_json_response(res, RequestKind::Discovery, 0, started)
+            .await?;
+        let response: CategoryProposalResponse = serde_json::from_str(&raw_text)?;
+
+        Ok(normalize_proposals(response.categories))
     }
 
     fn build_url(&self) -> String {
-        format!("{}?key={}", self.base_url, self.api_key)
+        self.base_url.clone()
+    }
+
+    /// Starts a POST to `url` carrying the API key in the `x-goog-api-key`
+    /// header, so it never appears in URLs, logs or proxy records.
+    fn post(&self, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .post(url)
+            .header(API_KEY_HEADER, self.api_key.as_str())
     }
 
     fn build_categorization_request(&self, prompt: &str) -> serde_json::Value {
         json!({
             "contents": [{ "parts": [{ "text": prompt }] }],
-            "generationConfig": { "response_mime_type": "application/json" }
+            "generationConfig": {
+                "response_mime_type": "application/json",
+                "maxOutputTokens": self.token_budget.output_limit()
+            }
         })
     }
 
     async fn parse_categorization_response(
         &self,
         res: reqwest::Response,
+        files: usize,
+        started: Instant,
     ) -> Result<OrganizationPlan, GeminiError> {
+        let raw_text = self
+            .read_json_response(res, RequestKind::Categorization, files, started)
+            .await?;
+        let plan_response: OrganizationPlanResponse = serde_json::from_str(&raw_text)?;
+
+        Ok(plan_response.to_organization_plan())
+    }
+
+    /// Records usage for a JSON-mode response that covered `files` files and
+    /// returns its text, failing if the model stopped at the output token
+    /// limit.
+    async fn read_json_response(
+        &self,
+        res: reqwest::Response,
+        kind: RequestKind,
+        files: usize,
+        started: Instant,
+    ) -> Result<String, GeminiError> {
         if !res.status().is_success() {
             return Err(GeminiError::from_response(res).await);
         }
 
-        let gemini_response: GeminiResponse =
-            res.json().await.map_err(GeminiError::NetworkError)?;
+        let gemini_response = Self::read_gemini_response(res).await?;
+        self.record_usage(kind, files, &gemini_response, started);
 
-        let raw_text = self.extract_text_from_response(&gemini_response)?;
-        let plan_response: OrganizationPlanResponse = serde_json::from_str(&raw_text)?;
+        if gemini_response
+            .candidates
+            .first()
+            .and_then(|c| c.finish_reason.as_deref())
+            == Some("MAX_TOKENS")
+        {
+            return Err(GeminiError::ResponseTruncated);
+        }
 
-        Ok(plan_response.to_organization_plan())
+        self.extract_text_from_response(&gemini_response)
+    }
+
+    /// Reads a successful response body, reporting a body that is not a
+    /// `generateContent` response as invalid rather than as a network error.
+    async fn read_gemini_response(res: reqwest::Response) -> Result<GeminiResponse, GeminiError> {
+        let body = res.text().await.map_err(GeminiError::NetworkError)?;
+        serde_json::from_str(&body)
+            .map_err(|e| GeminiError::InvalidResponse(format!("Malformed response body: {}", e)))
+    }
+
+    fn record_usage(
+        &self,
+        kind: RequestKind,
+        files: usize,
+        response: &GeminiResponse,
+        started: Instant,
+    ) {
+        self.usage.record_request(
+            kind,
+            &self.model,
+            files,
+            response.usage_metadata.as_ref(),
+            started.elapsed(),
+        );
     }
 
     fn extract_text_from_response(&self, response: &GeminiResponse) -> Result<String, GeminiError> {
@@ -252,34 +811,53 @@ impl GeminiClient {
             .ok_or_else(|| GeminiError::InvalidResponse("No candidates in response".to_string()))?
             .content
             .parts
-            .first()
-            .ok_or_else(|| GeminiError::InvalidResponse("No parts in content".to_string()))
+            .iter()
+            .find(|p| p.inline_data.is_none())
+            .ok_or_else(|| GeminiError::InvalidResponse("No text parts in content".to_string()))
             .map(|p| p.text.clone())
     }
 
-    async fn send_request_with_retry(
+    /// Sends a request, retrying transient failures. Every attempt is
+    /// reserved against the daily limits, and attempts answered with an
+    /// error are recorded, so retries count toward the request limit.
+    /// Returns the successful response with the reservation of its attempt,
+    /// to be held until its usage is recorded.
+    async fn send_request_with_retry<B: Serialize + ?Sized>(
         &self,
         url: &str,
-        request_body: &serde_json::Value,
-    ) -> Result<reqwest::Response, GeminiError> {
+        request_body: &B,
+        kind: RequestKind,
+        estimated_tokens: usize,
+    ) -> Result<(reqwest::Response, UsageReservation), GeminiError> {
         let mut attempts = 0;
         let mut base_delay = Duration::from_secs(2);
 
         loop {
             attempts += 1;
+            let reservation = self.usage.reserve(estimated_tokens as u64)?;
+            self.rate_limit.wait().await;
 
-            match self.client.post(url).json(request_body).send().await {
+            let started = Instant::now();
+            match self.post(url).json(request_body).send().await {
                 Ok(response) => {
                     if response.status().is_success() {
-                        return Ok(response);
+                        return Ok((response, reservation));
                     }
 
                     let error = GeminiError::from_response(response).await;
+                    self.usage
+                        .record_failed_attempt(kind, &self.model, started.elapsed());
+                    drop(reservation);
 
                     if error.is_retryable() && attempts < MAX_RETRIES {
-                        let delay = error.retry_delay().unwrap_or(base_delay);
+                        let delay = self.cap_retry_delay(error.retry_delay().unwrap_or(base_delay));
                         self.print_retry_message(&error, delay, attempts);
-                        tokio::time::sleep(delay).await;
+                        if matches!(error, GeminiError::RateLimitExceeded { .. }) {
+                            // Hold off every in-flight request, not just this one
+                            self.rate_limit.backoff(delay);
+                        } else {
+                            tokio::time::sleep(delay).await;
+                        }
                         base_delay *= 2;
                         continue;
                     }
@@ -288,17 +866,30 @@ impl GeminiClient {
                 }
                 Err(e) => {
                     if attempts < MAX_RETRIES {
-                        self.print_network_retry(&e, base_delay, attempts);
-                        tokio::time::sleep(base_delay).await;
+                        let delay = self.cap_retry_delay(base_delay);
+                        self.print_network_retry(&e, delay, attempts);
+                        tokio::time::sleep(delay).await;
                         base_delay *= 2;
                         continue;
                     }
+                    if e.is_timeout() {
+                        return Err(GeminiError::Timeout {
+                            seconds: self.timeout.as_secs(),
+                        });
+                    }
                     return Err(GeminiError::NetworkError(e));
                 }
             }
         }
     }
 
+    fn cap_retry_delay(&self, delay: Duration) -> Duration {
+        match self.max_retry_delay {
+            Some(max) => delay.min(max),
+            None => delay,
+        }
+    }
+
     fn print_retry_message(&self, error: &GeminiError, delay: Duration, attempt: u32) {
         if !self.silent {
             println!(
@@ -323,78 +914,362 @@ impl GeminiClient {
         }
     }
 
-    pub async fn get_ai_sub_category(
+    /// Suggests sub-categories for the inspected files.
+    ///
+    /// Text samples are sent many files per request, in batches sized by the
+    /// token budget and at most `max_concurrent` at a time. Attachments are
+    /// sent one file per request. The model is asked to reuse the folders in
+    /// `existing`. Files whose request fails, or that the response leaves
+    /// out, are reported in `failures`.
+    pub async fn inspect_sub_categories(
         &self,
-        filename: &str,
-        parent_category: &str,
-        content: &str,
-    ) -> String {
-        let url = self.build_url();
-        let prompt = PromptBuilder::build_subcategory_prompt(filename, parent_category, content);
-        let request_body = self.build_subcategory_request(&prompt);
+        targets: &[InspectionTarget],
+        existing: &SubFolderIndex,
+    ) -> SubCategoryResults {
+        self.inspect_sub_categories_with_cache(targets, existing, None)
+            .await
+    }
 
-        let res = match self.client.post(&url).json(&request_body).send().await {
-            Ok(res) => res,
-            Err(e) => {
-                if !self.silent {
-                    eprintln!(
-                        "Warning: Failed to get sub-category for {}: {}",
-                        filename, e
+    /// Like `inspect_sub_categories`, but answers files whose contents and
+    /// category were inspected before from `cache`, and sends only one of
+    /// several identical files in the same category.
+    pub async fn inspect_sub_categories_with_cache(
+        &self,
+        targets: &[InspectionTarget],
+        existing: &SubFolderIndex,
+        cache: Option<&mut Cache>,
+    ) -> SubCategoryResults {
+        let fingerprint = self.sub_category_fingerprint().digest();
+        let mut cached = HashMap::new();
+        let mut requested: HashMap<(&str, &str), &str> = HashMap::new();
+        let mut copies = Vec::new();
+        let mut pending = Vec::new();
+        for target in targets {
+            let Some(hash) = target.content_hash.as_deref() else {
+                pending.push(target);
+                continue;
+            };
+            if let Some(sub_category) = cache
+                .as_deref()
+                .and_then(|c| c.lookup_sub_category(hash, &target.category, &fingerprint))
+            {
+                cached.insert(target.filename.clone(), sub_category.to_string());
+            } else if let Some(original) = requested.get(&(hash, target.category.as_str())) {
+                copies.push((target.filename.as_str(), *original));
+            } else {
+                requested.insert((hash, &target.category), &target.filename);
+                pending.push(target);
+            }
+        }
+        if !cached.is_empty() {
+            self.usage
+                .record_cache_hit(RequestKind::SubCategory, &self.model, cached.len());
+        }
+
+        let mut results = self.request_sub_categories(&pending, existing).await;
+
+        if let Some(cache) = cache {
+            for target in &pending {
+                if let (Some(hash), Some(sub_category)) = (
+                    &target.content_hash,
+                    results.sub_categories.get(&target.filename),
+                ) {
+                    cache.cache_sub_category(hash, &target.category, sub_category, &fingerprint);
+                }
+            }
+        }
+        for (filename, original) in copies {
+            if let Some(sub_category) = results.sub_categories.get(original).cloned() {
+                results
+                    .sub_categories
+                    .insert(filename.to_string(), sub_category);
+            } else if let Some(failure) = results.failures.iter().find(|f| f.filename == original) {
+                let error = failure.error.clone();
+                results.fail(filename, &error);
+            }
+        }
+        results.sub_categories.extend(cached);
+        results
+    }
+
+    /// The settings this client's sub-categories depend on.
+    pub fn sub_category_fingerprint(&self) -> CacheFingerprint {
+        CacheFingerprint {
+            prompt_version: self.prompts.sub_category_version(),
+            ..self.cache_fingerprint()
+        }
+    }
+
+    async fn request_sub_categories(
+        &self,
+        targets: &[&InspectionTarget],
+        existing: &SubFolderIndex,
+    ) -> SubCategoryResults {
+        let (text_targets, media_targets): (Vec<_>, Vec<_>) = targets
+            .iter()
+            .copied()
+            .partition(|t| matches!(t.input, InspectionInput::Text(_)));
+
+        let items: Vec<SubCategoryItem> = text_targets
+            .iter()
+            .filter_map(|target| match &target.input {
+                InspectionInput::Text(content) => Some(SubCategoryItem {
+                    filename: target.filename.clone(),
+                    category: target.category.clone(),
+                    snippet: content.chars().take(BATCH_SNIPPET_CHARS).collect(),
+                }),
+                InspectionInput::Media { .. } => None,
+            })
+            .collect();
+        let batches = plan_sub_category_batches(&items, &self.token_budget);
+        let total_batches = batches.len();
+
+        let semaphore = Semaphore::new(self.max_concurrent);
+        let batch_tasks = batches.iter().enumerate().map(|(index, batch)| {
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .expect("batch semaphore is never closed");
+                let result = self.request_sub_category_batch(batch, existing).await;
+                if let Err(e) = &result {
+                    error!(
+                        "Sub-category batch {}/{} failed: {}",
+                        index + 1,
+                        total_batches,
+                        e
                     );
                 }
-                return "General".to_string();
+                (batch, result)
             }
-        };
+        });
 
-        self.parse_subcategory_response(res, filename).await
+        let media_tasks = media_targets.iter().map(|target| {
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .expect("batch semaphore is never closed");
+                let result = self
+                    .inspect_sub_category(
+                        &target.filename,
+                        &target.category,
+                        &target.input,
+                        existing.for_category(&target.category),
+                    )
+                    .await;
+                if let Err(e) = &result {
+                    error!("Sub-category request for {} failed: {}", target.filename, e);
+                }
+                (target, result)
+            }
+        });
+
+        let (batch_results, media_results) =
+            futures::future::join(join_all(batch_tasks), join_all(media_tasks)).await;
+
+        let mut results = SubCategoryResults::default();
+        for (batch, result) in batch_results {
+            match result {
+                Ok(halved) => {
+                    let sub_categories: HashMap<String, String> =
+                        halved.results.into_iter().flatten().collect();
+                    for (item, e) in &halved.failed {
+                        results.fail(&item.filename, &e.to_string());
+                    }
+                    for item in batch {
+                        if !sub_categories.contains_key(&item.filename)
+                            && !halved
+                                .failed
+                                .iter()
+                                .any(|(f, _)| f.filename == item.filename)
+                        {
+                            results.fail(&item.filename, "not returned by Gemini");
+                        }
+                    }
+                    results.sub_categories.extend(sub_categories);
+                }
+                Err(e) => {
+                    let error = e.to_string();
+                    for item in batch {
+                        results.fail(&item.filename, &error);
+                    }
+                }
+            }
+        }
+        for (target, result) in media_results {
+            match result {
+                Ok(sub_category) if sub_category.is_empty() => {}
+                Ok(sub_category) => {
+                    results
+                        .sub_categories
+                        .insert(target.filename.clone(), sub_category);
+                }
+                Err(e) => results.fail(&target.filename, &e.to_string()),
+            }
+        }
+        results
     }
 
-    fn build_subcategory_request(&self, prompt: &str) -> serde_json::Value {
-        json!({
-            "contents": [{ "parts": [{ "text": prompt }] }]
+    /// Requests sub-categories for one batch, halving it and retrying
+    /// whenever the response comes back truncated or unparseable. Items
+    /// that fail on their own are returned in `failed`.
+    async fn request_sub_category_batch(
+        &self,
+        items: &[SubCategoryItem],
+        existing: &SubFolderIndex,
+    ) -> Result<Halved<SubCategoryItem, HashMap<String, String>>, GeminiError> {
+        self.request_halving(items, |chunk| async move {
+            self.request_sub_category_batch_once(&chunk, existing).await
         })
+        .await
     }
 
-    async fn parse_subcategory_response(&self, res: reqwest::Response, filename: &str) -> String {
-        if !res.status().is_success() {
-            if !self.silent {
-                eprintln!(
-                    "Warning: API returned error for {}: {}",
-                    filename,
-                    res.status()
+    async fn request_sub_category_batch_once(
+        &self,
+        items: &[SubCategoryItem],
+        existing: &SubFolderIndex,
+    ) -> Result<HashMap<String, String>, GeminiError> {
+        let url = self.build_url();
+        let prompt = PromptBuilder::build_batch_subcategory_prompt(items, existing, &self.prompts);
+        let request_body = self.build_categorization_request(&prompt);
+
+        let started = Instant::now();
+        let (res, _reservation) = self
+            .send_request_with_retry(
+                &url,
+                &request_body,
+                RequestKind::SubCategory,
+                estimate_tokens(&prompt),
+            )
+            .await?;
+        let raw_text = self
+            .read_json_response(res, RequestKind::SubCategory, items.len(), started)
+            .await?;
+        let returned: HashMap<String, String> = serde_json::from_str(&raw_text)?;
+
+        let requested: Vec<String> = items.iter().map(|i| i.filename.clone()).collect();
+        Ok(reconcile_sub_categories(&requested, returned))
+    }
+
+    /// Suggests a sub-folder for a single file from a sample of its content,
+    /// preferring one of the `existing` sub-folders of its category.
+    pub async fn get_ai_sub_category(
+        &self,
+        filename: &str,
+        parent_category: &str,
+        content: &str,
+        existing: &[String],
+    ) -> Result<String, GeminiError> {
+        let prompt = PromptBuilder::build_subcategory_prompt(
+            filename,
+            parent_category,
+            content,
+            existing,
+            &self.prompts,
+        );
+        let request_body = Self::build_request(vec![RequestPart::Text { text: prompt }]);
+        self.request_sub_category(&request_body).await
+    }
+
+    /// Suggests a sub-category from whatever deep inspection read for the
+    /// file. Attachments that no longer fit in the run's byte budget fall
+    /// back to their text details; without any, the sub-category is empty.
+    pub async fn inspect_sub_category(
+        &self,
+        filename: &str,
+        parent_category: &str,
+        input: &InspectionInput,
+        existing: &[String],
+    ) -> Result<String, GeminiError> {
+        let (part, details) = match input {
+            InspectionInput::Text(content) => {
+                return self
+                    .get_ai_sub_category(filename, parent_category, content, existing)
+                    .await;
+            }
+            InspectionInput::Media { part, details } => (part, details.as_deref()),
+        };
+
+        let size = part.encoded_len();
+        if !self.multimodal.enabled || !self.media_budget.try_reserve(size) {
+            if self.multimodal.enabled {
+                warn!(
+                    "Not attaching {} ({} bytes): multimodal byte budget for this run is used up",
+                    filename, size
                 );
             }
-            return "General".to_string();
+            return match details {
+                Some(details) => {
+                    self.get_ai_sub_category(filename, parent_category, details, existing)
+                        .await
+                }
+                None => Ok(String::new()),
+            };
         }
 
-        let gemini_response: GeminiResponse = match res.json().await {
-            Ok(r) => r,
-            Err(e) => {
-                if !self.silent {
-                    eprintln!("Warning: Failed to parse response for {}: {}", filename, e);
-                }
-                return "General".to_string();
-            }
-        };
+        let prompt = PromptBuilder::build_media_subcategory_prompt(
+            filename,
+            parent_category,
+            details,
+            existing,
+            &self.prompts,
+        );
+        let request_body = Self::build_request(vec![
+            RequestPart::Text { text: prompt },
+            Self::inline_part(part),
+        ]);
+        self.request_sub_category(&request_body).await
+    }
 
-        self.extract_subcategory_from_response(&gemini_response, filename)
+    fn build_request(parts: Vec<RequestPart>) -> GenerateContentRequest {
+        GenerateContentRequest {
+            contents: vec![RequestContent { parts }],
+        }
+    }
+
+    fn inline_part(part: &MediaPart) -> RequestPart {
+        RequestPart::InlineData {
+            inline_data: InlineData {
+                mime_type: part.mime_type.clone(),
+                data: BASE64.encode(&part.data),
+            },
+        }
     }
 
-    fn extract_subcategory_from_response(
+    async fn request_sub_category(
         &self,
-        response: &GeminiResponse,
-        _filename: &str,
-    ) -> String {
-        match self.extract_text_from_response(response) {
-            Ok(text) => {
-                let sub_category = text.trim();
-                if sub_category.is_empty() {
-                    "General".to_string()
-                } else {
-                    sub_category.to_string()
-                }
-            }
-            Err(_) => "General".to_string(),
+        request_body: &GenerateContentRequest,
+    ) -> Result<String, GeminiError> {
+        let url = self.build_url();
+
+        // Attachments are not estimated; their tokens count once recorded
+        let prompt_tokens: usize = request_body
+            .contents
+            .iter()
+            .flat_map(|content| &content.parts)
+            .map(|part| match part {
+                RequestPart::Text { text } => estimate_tokens(text),
+                RequestPart::InlineData { .. } => 0,
+            })
+            .sum();
+        let started = Instant::now();
+        let (res, _reservation) = self
+            .send_request_with_retry(&url, request_body, RequestKind::SubCategory, prompt_tokens)
+            .await?;
+
+        let gemini_response = Self::read_gemini_response(res).await?;
+        self.record_usage(RequestKind::SubCategory, 1, &gemini_response, started);
+
+        let text = self.extract_text_from_response(&gemini_response)?;
+        let sub_category = text.trim();
+        if sub_category.is_empty() {
+            return Err(GeminiError::InvalidResponse(
+                "Empty sub-category in response".to_string(),
+            ));
         }
+        Ok(sub_category.to_string())
     }
 }
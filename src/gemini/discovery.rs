@@ -0,0 +1,39 @@
+use crate::files::sanitize_segment;
+use crate::gemini::types::CategoryProposal;
+use log::warn;
+
+/// Fewest categories discovery asks the model for.
+pub const MIN_PROPOSED_CATEGORIES: usize = 5;
+/// Most categories discovery asks for, and keeps.
+pub const MAX_PROPOSED_CATEGORIES: usize = 12;
+
+/// Cleans up the categories proposed by the model: names are made safe to
+/// use as folder names, unusable names and case-insensitive duplicates are
+/// dropped, and at most `MAX_PROPOSED_CATEGORIES` are kept.
+pub fn normalize_proposals(proposals: Vec<CategoryProposal>) -> Vec<CategoryProposal> {
+    let mut kept: Vec<CategoryProposal> = Vec::new();
+
+    for proposal in proposals {
+        let name = match sanitize_segment(&proposal.name) {
+            Ok(name) => name,
+            Err(e) => {
+                warn!("Dropping proposed category {:?}: {}", proposal.name, e);
+                continue;
+            }
+        };
+        if kept.iter().any(|p| p.name.eq_ignore_ascii_case(&name)) {
+            continue;
+        }
+        kept.push(CategoryProposal {
+            name,
+            description: proposal.description.trim().to_string(),
+        });
+    }
+
+    kept.truncate(MAX_PROPOSED_CATEGORIES);
+    kept
+}
+
+#[cfg(test)]
+#[path = "discovery_test.rs"]
+mod tests;
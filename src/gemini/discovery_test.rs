@@ -0,0 +1,41 @@
+use super::*;
+
+fn proposal(name: &str, description: &str) -> CategoryProposal {
+    CategoryProposal {
+        name: name.to_string(),
+        description: description.to_string(),
+    }
+}
+
+#[test]
+fn test_normalize_repairs_and_deduplicates_names() {
+    let proposals = vec![
+        proposal("Invoices", "  Bills and receipts "),
+        proposal("invoices", "Duplicate"),
+        proposal("../Work/Projects", "Client work"),
+        proposal("CON", "Reserved"),
+        proposal("  ", "Empty"),
+    ];
+
+    let normalized = normalize_proposals(proposals);
+
+    assert_eq!(
+        normalized,
+        vec![
+            proposal("Invoices", "Bills and receipts"),
+            proposal("Work-Projects", "Client work"),
+        ]
+    );
+}
+
+#[test]
+fn test_normalize_keeps_at_most_the_maximum() {
+    let proposals = (0..20)
+        .map(|i| proposal(&format!("Category {}", i), ""))
+        .collect();
+
+    assert_eq!(
+        normalize_proposals(proposals).len(),
+        MAX_PROPOSED_CATEGORIES
+    );
+}
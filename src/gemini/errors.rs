@@ -14,7 +14,9 @@ pub enum GeminiError {
     #[error("Model '{model}' not found or unavailable")]
     ModelNotFound { model: String },
 
-    #[error("Invalid API key. Please check your GEMINI_API_KEY")]
+    #[error(
+        "Invalid API key. Please check GEMINI_API_KEY, api_key_command or api_key in your config"
+    )]
     InvalidApiKey,
 
     #[error("Content policy violation: {reason}")]
@@ -29,6 +31,12 @@ pub enum GeminiError {
     #[error("Invalid response format: {0}")]
     InvalidResponse(String),
 
+    #[error("Response truncated after reaching the output token limit")]
+    ResponseTruncated,
+
+    #[error("Daily usage limit reached: {0}")]
+    UsageLimitReached(String),
+
     #[error("API error (HTTP {status}): {message}")]
     ApiError { status: u16, message: String },
 
@@ -76,6 +84,11 @@ impl GeminiError {
     /// Parse HTTP response and convert to appropriate GeminiError
     pub async fn from_response(response: Response) -> Self {
         let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_delay);
 
         let error_text = match response.text().await {
             Ok(text) => text,
@@ -88,7 +101,12 @@ impl GeminiError {
             return Self::from_gemini_error(gemini_error.error, status.as_u16());
         }
 
-        Self::from_status_code(status, &error_text)
+        match (Self::from_status_code(status, &error_text), retry_after) {
+            (GeminiError::RateLimitExceeded { .. }, Some(retry_after)) => {
+                GeminiError::RateLimitExceeded { retry_after }
+            }
+            (error, _) => error,
+        }
     }
 
     fn from_gemini_error(error_detail: GeminiErrorDetail, status: u16) -> Self {
@@ -98,7 +116,7 @@ impl GeminiError {
             "RESOURCE_EXHAUSTED" => {
                 if let Some(retry_info) = details.iter().find(|d| d.retry_delay.is_some())
                     && let Some(retry_delay) = &retry_info.retry_delay
-                    && let Ok(seconds) = retry_delay.parse::<u32>()
+                    && let Some(seconds) = parse_retry_delay(retry_delay)
                 {
                     return GeminiError::RateLimitExceeded {
                         retry_after: seconds,
@@ -188,6 +206,15 @@ impl GeminiError {
         )
     }
 
+    /// Check if the response was cut off or unparseable, which usually means
+    /// the request asked for more output than the model could return
+    pub fn is_incomplete_response(&self) -> bool {
+        matches!(
+            self,
+            GeminiError::ResponseTruncated | GeminiError::SerializationError(_)
+        )
+    }
+
     /// Get retry delay for retryable errors
     pub fn retry_delay(&self) -> Option<Duration> {
         match self {
@@ -203,6 +230,14 @@ impl GeminiError {
     }
 }
 
+/// Parses a retry hint in whole or fractional seconds, with or without the
+/// `s` suffix used by Gemini's `RetryInfo` (`"39s"`, `"1.5s"`, `"120"`).
+/// Fractions round up so the client never retries early.
+fn parse_retry_delay(value: &str) -> Option<u32> {
+    let seconds: f64 = value.trim().trim_end_matches('s').parse().ok()?;
+    (seconds.is_finite() && seconds >= 0.0).then(|| seconds.ceil() as u32)
+}
+
 fn extract_model_name(message: &str) -> String {
     // Try to extract model name from error message
     // Example: "Model 'gemini-1.5-flash' not found"
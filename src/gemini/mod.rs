@@ -1,10 +1,21 @@
+pub mod batching;
 pub mod client;
+pub mod discovery;
 pub mod errors;
 pub mod prompt;
+pub mod rate_limit;
+pub mod reconcile;
+pub mod templates;
 pub mod types;
+pub mod usage;
 
 pub use client::GeminiClient;
 pub use errors::GeminiError;
+pub use reconcile::{Reconciliation, reconcile_plan, reconcile_sub_categories};
+pub use templates::{PromptTemplates, TemplateError, TemplateKind};
 pub use types::{
-    Candidate, Content, FileCategoryResponse, GeminiResponse, OrganizationPlanResponse, Part,
+    Candidate, CategoryProposal, Content, FileCategoryResponse, GeminiResponse,
+    OrganizationPlanResponse, Part, SubCategoryFailure, SubCategoryItem, SubCategoryResults,
+    UsageMetadata,
 };
+pub use usage::{UsageReservation, UsageTracker};
@@ -1,5 +1,8 @@
-use crate::gemini::types::OrganizationPlanResponse;
-use crate::models::{FileCategory, OrganizationPlan};
+use crate::files::SubFolderIndex;
+use crate::gemini::discovery::{MAX_PROPOSED_CATEGORIES, MIN_PROPOSED_CATEGORIES};
+use crate::gemini::templates::PromptTemplates;
+use crate::gemini::types::{OrganizationPlanResponse, SubCategoryItem};
+use crate::models::{FileCategory, FileContext, OrganizationPlan};
 
 impl OrganizationPlanResponse {
     pub fn to_organization_plan(self) -> OrganizationPlan {
@@ -11,31 +14,81 @@ impl OrganizationPlanResponse {
                     filename: f.filename,
                     category: f.category,
                     sub_category: String::new(),
+                    confidence: f.confidence.map(|c| c.clamp(0.0, 1.0)),
+                    reasoning: f.reasoning.unwrap_or_default().trim().to_string(),
                 })
                 .collect(),
+            ..Default::default()
         }
     }
 }
 
+/// Existing sub-folders listed per category in sub-category prompts.
+const MAX_EXISTING_FOLDERS: usize = 30;
+
+/// Appended to single-file sub-category prompts, whatever the template says,
+/// so the reply can be used as a folder name.
+const SINGLE_NAME_INSTRUCTION: &str =
+    " Return ONLY the name of the sub-folder. Do not use markdown or explanations.";
+
 #[derive(Debug)]
 pub struct PromptBuilder {
     file_list: String,
+    with_context: bool,
 }
 
 impl PromptBuilder {
     pub fn new(file_list: &[String]) -> Self {
         Self {
             file_list: file_list.join(", "),
+            with_context: false,
+        }
+    }
+
+    /// Lists each file as a JSON object carrying its shared details.
+    pub fn with_context(files: &[FileContext]) -> Self {
+        let file_list = files
+            .iter()
+            .map(|f| serde_json::to_string(f).unwrap_or_else(|_| f.filename.clone()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Self {
+            file_list,
+            with_context: true,
         }
     }
 
-    pub fn build_categorization_prompt(&self, categories: &[String]) -> String {
-        let categories_str = categories.join("', '");
+    pub fn build_categorization_prompt(
+        &self,
+        categories: &[String],
+        templates: &PromptTemplates,
+    ) -> String {
+        let categories_str = format!("'{}'", categories.join("', '"));
+        let (files, context_hint) = if self.with_context {
+            (
+                format!(
+                    "(one JSON object per line with details about each file: size_bytes, modified date, \
+                     detected mime_type, parent_dir and a short content snippet when available)\n{}\n",
+                    self.file_list
+                ),
+                "Use the details to tell apart files with similar names. ",
+            )
+        } else {
+            (format!("[{}].", self.file_list), "")
+        };
+
+        let instructions = templates.categorization.render(&[
+            ("files", &files),
+            ("categories", &categories_str),
+            ("locale", &templates.locale),
+        ]);
         format!(
-            "I have these files in my Downloads folder: [{}]. \
-             Categorize them into these folders: '{}'. \
-             Return ONLY a JSON object with this structure: {{ 'files': [ {{ 'filename': 'name', 'category': 'folder' }} ] }}",
-            self.file_list, categories_str
+            "{} {}\
+             Include every file exactly once and copy each filename exactly as given. \
+             For each file also give your confidence in the category from 0.0 to 1.0 and a one-line reason. \
+             Return ONLY a JSON object with this structure: {{ 'files': [ {{ 'filename': 'name', 'category': 'folder', 'confidence': 0.9, 'reasoning': 'short reason' }} ] }}",
+            instructions, context_hint
         )
     }
 
@@ -43,10 +96,120 @@ impl PromptBuilder {
         filename: &str,
         parent_category: &str,
         content: &str,
+        existing: &[String],
+        templates: &PromptTemplates,
+    ) -> String {
+        let instructions = templates.sub_category.render(&[
+            ("filename", filename),
+            ("category", parent_category),
+            ("content", content),
+            (
+                "existing_folders",
+                &Self::existing_folders_hint(parent_category, existing),
+            ),
+            ("locale", &templates.locale),
+        ]);
+        format!("{}{}", instructions, SINGLE_NAME_INSTRUCTION)
+    }
+
+    /// Prompt sent alongside an attached image or PDF page.
+    pub fn build_media_subcategory_prompt(
+        filename: &str,
+        parent_category: &str,
+        details: Option<&str>,
+        existing: &[String],
+        templates: &PromptTemplates,
+    ) -> String {
+        let details = details.map(|d| format!(" {}.", d)).unwrap_or_default();
+        let instructions = templates.media_sub_category.render(&[
+            ("filename", filename),
+            ("category", parent_category),
+            ("details", &details),
+            (
+                "existing_folders",
+                &Self::existing_folders_hint(parent_category, existing),
+            ),
+            ("locale", &templates.locale),
+        ]);
+        format!("{}{}", instructions, SINGLE_NAME_INSTRUCTION)
+    }
+
+    /// Asks for a sub-folder for each of several files at once, answered as
+    /// a JSON object keyed by filename.
+    pub fn build_batch_subcategory_prompt(
+        items: &[SubCategoryItem],
+        existing: &SubFolderIndex,
+        templates: &PromptTemplates,
     ) -> String {
+        let file_list = items
+            .iter()
+            .map(|item| serde_json::to_string(item).unwrap_or_else(|_| item.filename.clone()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut categories: Vec<&str> = items.iter().map(|i| i.category.as_str()).collect();
+        categories.sort_unstable();
+        categories.dedup();
+        let existing_list = categories
+            .into_iter()
+            .filter(|c| !existing.for_category(c).is_empty())
+            .map(|c| format!("- {}: {}", c, Self::quoted_list(existing.for_category(c))))
+            .collect::<Vec<_>>();
+        let existing_hint = if existing_list.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " These sub-folders already exist in each folder:\n{}\n\
+                 Reuse an existing sub-folder whenever one fits the file; only suggest a new name when none does.",
+                existing_list.join("\n")
+            )
+        };
+
+        let instructions = templates.batch_sub_category.render(&[
+            ("files", &file_list),
+            ("existing_folders", &existing_hint),
+            ("locale", &templates.locale),
+        ]);
+        format!(
+            "{} Include every file exactly once and copy each filename exactly as given. \
+             Return ONLY a JSON object mapping each filename to its sub-folder name, like {{ 'report.txt': 'Reports' }}",
+            instructions
+        )
+    }
+
+    /// Asks for a set of top-level folders suited to a folder, given a
+    /// sample of its filenames.
+    pub fn build_discovery_prompt(sample: &[String], total_files: usize) -> String {
         format!(
-            "I have a file named '{}' inside the '{}' folder. Here is the first 1000 characters of content:\n---\n{}\n---\nBased on this, suggest a single short sub-folder name (e.g., 'Invoices', 'Notes', 'Config'). Return ONLY the name of the sub-folder. Do not use markdown or explanations.",
-            filename, parent_category, content
+            "I want to organize a folder of {} files into top-level folders. Here is a sample of their names, one per line:\n{}\n\
+             Propose between {} and {} folder names that together would fit most of these files. \
+             Prefer short, plain names (e.g., 'Invoices', 'Photos', 'Work') and include a catch-all such as 'Misc'. \
+             For each folder give a one-sentence description of what belongs in it. \
+             Return ONLY a JSON object with this structure: {{ 'categories': [ {{ 'name': 'folder', 'description': 'what goes here' }} ] }}",
+            total_files,
+            sample.join("\n"),
+            MIN_PROPOSED_CATEGORIES,
+            MAX_PROPOSED_CATEGORIES
         )
     }
+
+    fn existing_folders_hint(parent_category: &str, existing: &[String]) -> String {
+        if existing.is_empty() {
+            return String::new();
+        }
+        format!(
+            " These sub-folders already exist in '{}': {}. Reuse one of them if it fits the file; only suggest a new name when none does.",
+            parent_category,
+            Self::quoted_list(existing)
+        )
+    }
+
+    fn quoted_list(names: &[String]) -> String {
+        names
+            .iter()
+            .take(MAX_EXISTING_FOLDERS)
+            .map(|name| format!("'{}'", name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
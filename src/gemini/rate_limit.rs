@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Backoff window shared by every clone of a client.
+///
+/// When one request is rate limited, all concurrent requests hold off until
+/// the window has passed instead of hammering the API independently.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitGate {
+    until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimitGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extends the shared backoff window to at least `delay` from now.
+    pub fn backoff(&self, delay: Duration) {
+        let deadline = Instant::now() + delay;
+        let mut until = self.until.lock().unwrap_or_else(|e| e.into_inner());
+        if until.is_none_or(|current| current < deadline) {
+            *until = Some(deadline);
+        }
+    }
+
+    /// Time left in the current backoff window, if any.
+    pub fn remaining(&self) -> Option<Duration> {
+        let until = *self.until.lock().unwrap_or_else(|e| e.into_inner());
+        until
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+            .filter(|d| !d.is_zero())
+    }
+
+    /// Sleeps until the backoff window has passed.
+    pub async fn wait(&self) {
+        while let Some(delay) = self.remaining() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "rate_limit_test.rs"]
+mod tests;
@@ -0,0 +1,38 @@
+use super::*;
+
+#[test]
+fn test_gate_starts_open() {
+    let gate = RateLimitGate::new();
+    assert!(gate.remaining().is_none());
+}
+
+#[test]
+fn test_backoff_is_shared_between_clones() {
+    let gate = RateLimitGate::new();
+    let clone = gate.clone();
+
+    gate.backoff(Duration::from_secs(30));
+
+    let remaining = clone.remaining().unwrap();
+    assert!(remaining > Duration::from_secs(29));
+}
+
+#[test]
+fn test_shorter_backoff_does_not_shrink_window() {
+    let gate = RateLimitGate::new();
+
+    gate.backoff(Duration::from_secs(30));
+    gate.backoff(Duration::from_secs(1));
+
+    assert!(gate.remaining().unwrap() > Duration::from_secs(29));
+}
+
+#[tokio::test]
+async fn test_wait_returns_after_window() {
+    let gate = RateLimitGate::new();
+    gate.backoff(Duration::from_millis(20));
+
+    gate.wait().await;
+
+    assert!(gate.remaining().is_none());
+}
@@ -0,0 +1,137 @@
+use crate::models::FileCategory;
+use log::warn;
+use std::collections::{HashMap, HashSet};
+
+/// Outcome of matching an AI response against the filenames that were sent.
+#[derive(Debug, Default)]
+pub struct Reconciliation {
+    /// Categorized files, renamed to the exact requested filename, in request order.
+    pub matched: Vec<FileCategory>,
+    /// Requested files the response did not mention.
+    pub missing: Vec<String>,
+    /// Returned filenames that do not correspond to any requested file.
+    pub unknown: Vec<String>,
+    /// Requested files the response categorized more than once.
+    pub duplicates: Vec<String>,
+}
+
+/// Diffs the files returned by the AI against the requested list.
+///
+/// Returned names are matched exactly first, then by a normalized form
+/// (trimmed, lowercased, `/` separators) when that form is unambiguous.
+/// Only the first categorization of a file is kept.
+pub fn reconcile_plan(requested: &[String], returned: Vec<FileCategory>) -> Reconciliation {
+    let index = FilenameIndex::new(requested);
+    let mut assigned: Vec<Option<FileCategory>> = vec![None; requested.len()];
+    let mut duplicates = HashSet::new();
+    let mut unknown = Vec::new();
+
+    for mut file in returned {
+        let Some(index) = index.find(&file.filename) else {
+            unknown.push(file.filename);
+            continue;
+        };
+
+        if assigned[index].is_some() {
+            duplicates.insert(index);
+            continue;
+        }
+
+        file.filename = requested[index].clone();
+        assigned[index] = Some(file);
+    }
+
+    let mut result = Reconciliation {
+        unknown,
+        ..Default::default()
+    };
+
+    for (i, slot) in assigned.into_iter().enumerate() {
+        match slot {
+            Some(file) => result.matched.push(file),
+            None => result.missing.push(requested[i].clone()),
+        }
+    }
+
+    let mut duplicates: Vec<_> = duplicates.into_iter().collect();
+    duplicates.sort_unstable();
+    result.duplicates = duplicates
+        .into_iter()
+        .map(|i| requested[i].clone())
+        .collect();
+
+    result
+}
+
+/// Matches a batched sub-category response against the requested
+/// filenames, the same way `reconcile_plan` does. Unknown names and blank
+/// sub-categories are dropped.
+pub fn reconcile_sub_categories(
+    requested: &[String],
+    returned: HashMap<String, String>,
+) -> HashMap<String, String> {
+    let index = FilenameIndex::new(requested);
+    let mut matched = HashMap::new();
+
+    for (filename, sub_category) in returned {
+        let sub_category = sub_category.trim();
+        if sub_category.is_empty() {
+            continue;
+        }
+        match index.find(&filename) {
+            Some(i) => {
+                matched
+                    .entry(requested[i].clone())
+                    .or_insert_with(|| sub_category.to_string());
+            }
+            None => warn!("Dropping unknown file returned by Gemini: {}", filename),
+        }
+    }
+
+    matched
+}
+
+/// Looks up returned filenames by exact match first, then by normalized
+/// form when that form is unambiguous.
+struct FilenameIndex<'a> {
+    exact: HashMap<&'a str, usize>,
+    normalized: HashMap<String, Option<usize>>,
+}
+
+impl<'a> FilenameIndex<'a> {
+    fn new(requested: &'a [String]) -> Self {
+        let exact = requested
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+
+        let mut normalized: HashMap<String, Option<usize>> = HashMap::new();
+        for (i, name) in requested.iter().enumerate() {
+            normalized
+                .entry(normalize_filename(name))
+                .and_modify(|slot| *slot = None)
+                .or_insert(Some(i));
+        }
+
+        Self { exact, normalized }
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        self.exact
+            .get(name)
+            .copied()
+            .or_else(|| self.normalized.get(&normalize_filename(name)).copied()?)
+    }
+}
+
+fn normalize_filename(name: &str) -> String {
+    name.trim()
+        .trim_matches(|c| c == '\'' || c == '"')
+        .replace('\\', "/")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+#[path = "reconcile_test.rs"]
+mod tests;
@@ -0,0 +1,109 @@
+use super::*;
+
+fn category(filename: &str, category: &str) -> FileCategory {
+    FileCategory {
+        filename: filename.to_string(),
+        category: category.to_string(),
+        sub_category: String::new(),
+        ..Default::default()
+    }
+}
+
+fn names(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_reconcile_all_matched_in_request_order() {
+    let requested = names(&["a.pdf", "b.jpg"]);
+    let returned = vec![category("b.jpg", "Images"), category("a.pdf", "Documents")];
+
+    let result = reconcile_plan(&requested, returned);
+
+    assert_eq!(result.matched.len(), 2);
+    assert_eq!(result.matched[0].filename, "a.pdf");
+    assert_eq!(result.matched[1].filename, "b.jpg");
+    assert!(result.missing.is_empty());
+    assert!(result.unknown.is_empty());
+    assert!(result.duplicates.is_empty());
+}
+
+#[test]
+fn test_reconcile_reports_missing_files() {
+    let requested = names(&["a.pdf", "b.jpg", "c.zip"]);
+    let returned = vec![category("a.pdf", "Documents")];
+
+    let result = reconcile_plan(&requested, returned);
+
+    assert_eq!(result.matched.len(), 1);
+    assert_eq!(result.missing, names(&["b.jpg", "c.zip"]));
+}
+
+#[test]
+fn test_reconcile_drops_unknown_names() {
+    let requested = names(&["a.pdf"]);
+    let returned = vec![
+        category("a.pdf", "Documents"),
+        category("invented.txt", "Documents"),
+    ];
+
+    let result = reconcile_plan(&requested, returned);
+
+    assert_eq!(result.matched.len(), 1);
+    assert_eq!(result.unknown, names(&["invented.txt"]));
+}
+
+#[test]
+fn test_reconcile_keeps_first_duplicate() {
+    let requested = names(&["a.pdf"]);
+    let returned = vec![category("a.pdf", "Documents"), category("a.pdf", "Misc")];
+
+    let result = reconcile_plan(&requested, returned);
+
+    assert_eq!(result.matched.len(), 1);
+    assert_eq!(result.matched[0].category, "Documents");
+    assert_eq!(result.duplicates, names(&["a.pdf"]));
+}
+
+#[test]
+fn test_reconcile_repairs_altered_names() {
+    let requested = names(&["Report.PDF", "sub/notes.txt"]);
+    let returned = vec![
+        category(" report.pdf ", "Documents"),
+        category("sub\\notes.txt", "Documents"),
+    ];
+
+    let result = reconcile_plan(&requested, returned);
+
+    assert_eq!(result.matched.len(), 2);
+    assert_eq!(result.matched[0].filename, "Report.PDF");
+    assert_eq!(result.matched[1].filename, "sub/notes.txt");
+    assert!(result.unknown.is_empty());
+}
+
+#[test]
+fn test_reconcile_ambiguous_normalized_name_is_unknown() {
+    let requested = names(&["a.txt", "A.txt"]);
+    let returned = vec![category("a.TXT", "Documents")];
+
+    let result = reconcile_plan(&requested, returned);
+
+    assert!(result.matched.is_empty());
+    assert_eq!(result.unknown, names(&["a.TXT"]));
+    assert_eq!(result.missing.len(), 2);
+}
+
+#[test]
+fn test_reconcile_sub_categories_matches_and_drops() {
+    let requested = names(&["Notes/a.txt", "b.md", "c.md"]);
+    let returned = HashMap::from([
+        ("notes/A.txt".to_string(), " Meetings ".to_string()),
+        ("b.md".to_string(), "".to_string()),
+        ("stranger.txt".to_string(), "Other".to_string()),
+    ]);
+
+    let matched = reconcile_sub_categories(&requested, returned);
+
+    assert_eq!(matched.len(), 1);
+    assert_eq!(matched["Notes/a.txt"], "Meetings");
+}
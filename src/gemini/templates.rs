@@ -0,0 +1,330 @@
+use crate::models::PromptConfig;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Which prompt a template is for, with the placeholders it may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateKind {
+    Categorization,
+    SubCategory,
+    MediaSubCategory,
+    BatchSubCategory,
+}
+
+impl TemplateKind {
+    pub const ALL: [TemplateKind; 4] = [
+        TemplateKind::Categorization,
+        TemplateKind::SubCategory,
+        TemplateKind::MediaSubCategory,
+        TemplateKind::BatchSubCategory,
+    ];
+
+    /// Name used in the config file and for template files.
+    pub fn name(self) -> &'static str {
+        match self {
+            TemplateKind::Categorization => "categorization",
+            TemplateKind::SubCategory => "sub_category",
+            TemplateKind::MediaSubCategory => "media_sub_category",
+            TemplateKind::BatchSubCategory => "batch_sub_category",
+        }
+    }
+
+    /// Placeholders the template may use.
+    pub fn placeholders(self) -> &'static [&'static str] {
+        match self {
+            TemplateKind::Categorization => &["files", "categories", "locale"],
+            TemplateKind::SubCategory => &[
+                "filename",
+                "category",
+                "content",
+                "existing_folders",
+                "locale",
+            ],
+            TemplateKind::MediaSubCategory => &[
+                "filename",
+                "category",
+                "details",
+                "existing_folders",
+                "locale",
+            ],
+            TemplateKind::BatchSubCategory => &["files", "existing_folders", "locale"],
+        }
+    }
+
+    /// Placeholders the template must use, without which the model would
+    /// not see the files it is asked about.
+    fn required(self) -> &'static [&'static str] {
+        match self {
+            TemplateKind::Categorization => &["files", "categories"],
+            TemplateKind::SubCategory => &["filename", "content"],
+            TemplateKind::MediaSubCategory => &["filename"],
+            TemplateKind::BatchSubCategory => &["files"],
+        }
+    }
+
+    fn default_text(self) -> &'static str {
+        match self {
+            TemplateKind::Categorization => {
+                "I have these files in my Downloads folder: {files}\n\
+                 Categorize them into these folders: {categories}."
+            }
+            TemplateKind::SubCategory => {
+                "I have a file named '{filename}' inside the '{category}' folder. \
+                 Here is a sample of its content (or its image metadata):\n---\n{content}\n---\n\
+                 Based on this, suggest a single short sub-folder name (e.g., 'Invoices', 'Notes', 'Config'). \
+                 Write new names in the '{locale}' locale.{existing_folders}"
+            }
+            TemplateKind::MediaSubCategory => {
+                "I have a file named '{filename}' inside the '{category}' folder. \
+                 The attached file is the image itself, or the first page of the document.{details}\n\
+                 Based on what it shows, suggest a single short sub-folder name (e.g., 'Receipts', 'Family', 'Diagrams'). \
+                 Write new names in the '{locale}' locale.{existing_folders}"
+            }
+            TemplateKind::BatchSubCategory => {
+                "I have these files, one JSON object per line with the folder each file was sorted into \
+                 and a sample of its content (or its image metadata):\n{files}\n\
+                 For each file, suggest a single short sub-folder name inside its folder (e.g., 'Invoices', 'Notes', 'Config'). \
+                 Write new names in the '{locale}' locale.{existing_folders}"
+            }
+        }
+    }
+}
+
+impl fmt::Display for TemplateKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("Failed to read {kind} prompt template {path:?}: {source}")]
+    Io {
+        kind: TemplateKind,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Unknown placeholder {{{name}}} in {kind} prompt template (allowed: {allowed})")]
+    UnknownPlaceholder {
+        kind: TemplateKind,
+        name: String,
+        allowed: String,
+    },
+
+    #[error("The {kind} prompt template must include {{{name}}}")]
+    MissingPlaceholder { kind: TemplateKind, name: String },
+
+    #[error(
+        "Unmatched '{brace}' at character {position} in {kind} prompt template (write '{brace}{brace}' for a literal brace)"
+    )]
+    UnmatchedBrace {
+        kind: TemplateKind,
+        brace: char,
+        position: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+/// A validated prompt template. `{name}` is replaced when rendering;
+/// `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parses `text`, checking that it only uses placeholders allowed for
+    /// `kind` and includes the required ones.
+    pub fn parse(kind: TemplateKind, text: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().is_some_and(|&(_, next)| next == '{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().is_some_and(|&(_, next)| next == '}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed || name.contains('{') {
+                        return Err(TemplateError::UnmatchedBrace {
+                            kind,
+                            brace: '{',
+                            position,
+                        });
+                    }
+                    let name = name.trim().to_string();
+                    if !kind.placeholders().contains(&name.as_str()) {
+                        return Err(TemplateError::UnknownPlaceholder {
+                            kind,
+                            name,
+                            allowed: kind.placeholders().join(", "),
+                        });
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder(name));
+                }
+                '}' => {
+                    return Err(TemplateError::UnmatchedBrace {
+                        kind,
+                        brace: '}',
+                        position,
+                    });
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Text(literal));
+        }
+
+        for required in kind.required() {
+            if !segments.contains(&Segment::Placeholder(required.to_string())) {
+                return Err(TemplateError::MissingPlaceholder {
+                    kind,
+                    name: required.to_string(),
+                });
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Fills in the placeholders. Placeholders without a value render empty.
+    pub fn render(&self, values: &[(&str, &str)]) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.as_str(),
+                Segment::Placeholder(name) => values
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| *value)
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    fn hash_into(&self, hasher: &mut blake3::Hasher) {
+        for segment in &self.segments {
+            let (tag, text) = match segment {
+                Segment::Text(text) => (b"t", text),
+                Segment::Placeholder(name) => (b"p", name),
+            };
+            hasher.update(tag);
+            hasher.update(&(text.len() as u64).to_le_bytes());
+            hasher.update(text.as_bytes());
+        }
+    }
+}
+
+/// The templates used to build every prompt, loaded and validated once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplates {
+    pub locale: String,
+    pub categorization: Template,
+    pub sub_category: Template,
+    pub media_sub_category: Template,
+    pub batch_sub_category: Template,
+}
+
+impl PromptTemplates {
+    /// Loads the templates configured in `config`, falling back to the
+    /// built-in wording for any that are not overridden.
+    pub fn load(config: &PromptConfig) -> Result<Self, TemplateError> {
+        let load =
+            |kind: TemplateKind| -> Result<Template, TemplateError> {
+                let inline = match kind {
+                    TemplateKind::Categorization => &config.categorization,
+                    TemplateKind::SubCategory => &config.sub_category,
+                    TemplateKind::MediaSubCategory => &config.media_sub_category,
+                    TemplateKind::BatchSubCategory => &config.batch_sub_category,
+                };
+                if let Some(text) = inline {
+                    return Template::parse(kind, text);
+                }
+
+                if let Some(dir) = &config.dir {
+                    let path = dir.join(format!("{}.txt", kind.name()));
+                    if path.exists() {
+                        let text = fs::read_to_string(&path)
+                            .map_err(|source| TemplateError::Io { kind, path, source })?;
+                        return Template::parse(kind, text.trim_end());
+                    }
+                }
+
+                Template::parse(kind, kind.default_text())
+            };
+
+        let locale = config.locale.trim();
+        Ok(Self {
+            locale: if locale.is_empty() { "en" } else { locale }.to_string(),
+            categorization: load(TemplateKind::Categorization)?,
+            sub_category: load(TemplateKind::SubCategory)?,
+            media_sub_category: load(TemplateKind::MediaSubCategory)?,
+            batch_sub_category: load(TemplateKind::BatchSubCategory)?,
+        })
+    }
+}
+
+impl PromptTemplates {
+    /// Identifies the categorization template and locale, so cached
+    /// categorizations can be dropped when either changes.
+    pub fn categorization_version(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.locale.as_bytes());
+        hasher.update(b"|");
+        self.categorization.hash_into(&mut hasher);
+        hasher.finalize().to_hex()[..16].to_string()
+    }
+
+    /// Identifies the sub-category templates and locale, so cached
+    /// sub-categories can be dropped when any of them changes.
+    pub fn sub_category_version(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.locale.as_bytes());
+        for template in [
+            &self.sub_category,
+            &self.media_sub_category,
+            &self.batch_sub_category,
+        ] {
+            hasher.update(b"|");
+            template.hash_into(&mut hasher);
+        }
+        hasher.finalize().to_hex()[..16].to_string()
+    }
+}
+
+impl Default for PromptTemplates {
+    fn default() -> Self {
+        Self::load(&PromptConfig::default()).expect("built-in prompt templates are valid")
+    }
+}
+
+#[cfg(test)]
+#[path = "templates_test.rs"]
+mod tests;
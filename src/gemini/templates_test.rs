@@ -0,0 +1,158 @@
+use super::*;
+use tempfile::TempDir;
+
+#[test]
+fn test_built_in_templates_are_valid() {
+    for kind in TemplateKind::ALL {
+        assert!(
+            Template::parse(kind, kind.default_text()).is_ok(),
+            "{}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn test_render_fills_placeholders_and_literal_braces() {
+    let template = Template::parse(
+        TemplateKind::Categorization,
+        "Files: {files} into {{ {categories} }} ({ locale })",
+    )
+    .unwrap();
+
+    let rendered = template.render(&[
+        ("files", "[a.txt]"),
+        ("categories", "'Docs'"),
+        ("locale", "de"),
+    ]);
+
+    assert_eq!(rendered, "Files: [a.txt] into { 'Docs' } (de)");
+}
+
+#[test]
+fn test_parse_rejects_unknown_and_missing_placeholders() {
+    let unknown = Template::parse(
+        TemplateKind::Categorization,
+        "{files} {categories} {content}",
+    );
+    assert!(matches!(
+        unknown,
+        Err(TemplateError::UnknownPlaceholder { ref name, .. }) if name == "content"
+    ));
+
+    let missing = Template::parse(TemplateKind::SubCategory, "Sort '{filename}'");
+    assert!(matches!(
+        missing,
+        Err(TemplateError::MissingPlaceholder { ref name, .. }) if name == "content"
+    ));
+}
+
+#[test]
+fn test_parse_rejects_unmatched_braces() {
+    for text in [
+        "{files} {categories",
+        "{files} {categories} }",
+        "{files {categories}}",
+    ] {
+        assert!(
+            matches!(
+                Template::parse(TemplateKind::Categorization, text),
+                Err(TemplateError::UnmatchedBrace { .. })
+            ),
+            "{}",
+            text
+        );
+    }
+}
+
+#[test]
+fn test_load_prefers_inline_then_directory_then_default() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("categorization.txt"),
+        "From file: {files} {categories}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("batch_sub_category.txt"),
+        "From file: {files}",
+    )
+    .unwrap();
+
+    let config = PromptConfig {
+        locale: "fr".to_string(),
+        dir: Some(dir.path().to_path_buf()),
+        batch_sub_category: Some("Inline: {files}".to_string()),
+        ..Default::default()
+    };
+    let templates = PromptTemplates::load(&config).unwrap();
+
+    assert_eq!(templates.locale, "fr");
+    assert_eq!(
+        templates
+            .categorization
+            .render(&[("files", "F"), ("categories", "C")]),
+        "From file: F C"
+    );
+    assert_eq!(
+        templates.batch_sub_category.render(&[("files", "F")]),
+        "Inline: F"
+    );
+    assert_eq!(
+        templates.sub_category,
+        PromptTemplates::default().sub_category
+    );
+}
+
+#[test]
+fn test_load_reports_invalid_template_files() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("sub_category.txt"), "{filename} {oops}").unwrap();
+
+    let config = PromptConfig {
+        dir: Some(dir.path().to_path_buf()),
+        ..Default::default()
+    };
+    let error = PromptTemplates::load(&config).unwrap_err();
+
+    assert!(error.to_string().contains("{oops}"));
+    assert!(error.to_string().contains("sub_category"));
+}
+
+#[test]
+fn test_categorization_version_tracks_template_and_locale() {
+    let default = PromptTemplates::default();
+    assert_eq!(
+        default.categorization_version(),
+        PromptTemplates::default().categorization_version()
+    );
+
+    let custom = PromptTemplates::load(&PromptConfig {
+        categorization: Some("Sort {files} into {categories}".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    let german = PromptTemplates::load(&PromptConfig {
+        locale: "de".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    let other_sub_category = PromptTemplates::load(&PromptConfig {
+        sub_category: Some("{filename} in {category}: {content}".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_ne!(
+        custom.categorization_version(),
+        default.categorization_version()
+    );
+    assert_ne!(
+        german.categorization_version(),
+        default.categorization_version()
+    );
+    assert_eq!(
+        other_sub_category.categorization_version(),
+        default.categorization_version()
+    );
+}
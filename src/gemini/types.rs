@@ -1,13 +1,55 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Body of a `generateContent` request.
+#[derive(Serialize, Debug)]
+pub struct GenerateContentRequest {
+    pub contents: Vec<RequestContent>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RequestContent {
+    pub parts: Vec<RequestPart>,
+}
+
+/// A request part: either prompt text or an attached file.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum RequestPart {
+    Text { text: String },
+    InlineData { inline_data: InlineData },
+}
+
+/// File data sent inline, base64-encoded.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InlineData {
+    #[serde(alias = "mimeType")]
+    pub mime_type: String,
+    pub data: String,
+}
 
 #[derive(Deserialize, Default)]
 pub struct GeminiResponse {
     pub candidates: Vec<Candidate>,
+    #[serde(rename = "usageMetadata", default)]
+    pub usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+pub struct UsageMetadata {
+    #[serde(rename = "promptTokenCount", default)]
+    pub prompt_token_count: u64,
+    #[serde(rename = "candidatesTokenCount", default)]
+    pub candidates_token_count: u64,
+    #[serde(rename = "totalTokenCount", default)]
+    pub total_token_count: u64,
 }
 
 #[derive(Deserialize)]
 pub struct Candidate {
     pub content: Content,
+    #[serde(rename = "finishReason", default)]
+    pub finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -17,13 +59,65 @@ pub struct Content {
 
 #[derive(Deserialize)]
 pub struct Part {
+    #[serde(default)]
     pub text: String,
+    #[serde(rename = "inlineData", alias = "inline_data", default)]
+    pub inline_data: Option<InlineData>,
 }
 
 #[derive(Deserialize)]
 pub struct FileCategoryResponse {
     pub filename: String,
     pub category: String,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    #[serde(default)]
+    pub reasoning: Option<String>,
+}
+
+/// One file in a batched sub-category prompt.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SubCategoryItem {
+    pub filename: String,
+    pub category: String,
+    pub snippet: String,
+}
+
+/// A file deep inspection could not get a sub-category for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubCategoryFailure {
+    pub filename: String,
+    pub error: String,
+}
+
+/// Outcome of sub-categorizing the inspected files.
+#[derive(Debug, Default)]
+pub struct SubCategoryResults {
+    /// Sub-categories keyed by filename.
+    pub sub_categories: HashMap<String, String>,
+    pub failures: Vec<SubCategoryFailure>,
+}
+
+impl SubCategoryResults {
+    pub fn fail(&mut self, filename: &str, error: &str) {
+        self.failures.push(SubCategoryFailure {
+            filename: filename.to_string(),
+            error: error.to_string(),
+        });
+    }
+}
+
+/// A category suggested by `noentropy discover`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CategoryProposal {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+pub struct CategoryProposalResponse {
+    pub categories: Vec<CategoryProposal>,
 }
 
 #[derive(Deserialize)]
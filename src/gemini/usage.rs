@@ -0,0 +1,154 @@
+use crate::gemini::errors::GeminiError;
+use crate::gemini::types::UsageMetadata;
+use crate::models::{RequestKind, UsageLimits, UsageRecord, UsageTotals};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Usage recorded by every clone of a client during a run.
+///
+/// Records are collected in memory and handed to the usage log by the caller.
+/// Daily limits are checked against what was already used today plus
+/// everything recorded since and every request still in flight.
+#[derive(Clone, Debug, Default)]
+pub struct UsageTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    folder: Option<String>,
+    limits: UsageLimits,
+    used_today: UsageTotals,
+    /// Requests and estimated tokens of reservations not yet dropped.
+    reserved: UsageTotals,
+    records: Vec<UsageRecord>,
+}
+
+/// A request counted against the daily limits while it is in flight.
+/// Hold it until the request's usage has been recorded; dropping it
+/// releases the reservation.
+#[must_use]
+#[derive(Debug)]
+pub struct UsageReservation {
+    state: Arc<Mutex<TrackerState>>,
+    tokens: u64,
+}
+
+impl Drop for UsageReservation {
+    fn drop(&mut self) {
+        let mut state = lock_state(&self.state);
+        state.reserved.requests = state.reserved.requests.saturating_sub(1);
+        state.reserved.total_tokens = state.reserved.total_tokens.saturating_sub(self.tokens);
+    }
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the folder that subsequent records are attributed to.
+    pub fn set_folder(&self, folder: &Path) {
+        self.lock().folder = Some(folder.to_string_lossy().into_owned());
+    }
+
+    /// Sets the daily limits and what has been used today before this run.
+    pub fn set_limits(&self, limits: UsageLimits, used_today: UsageTotals) {
+        let mut state = self.lock();
+        state.limits = limits;
+        state.used_today = used_today;
+    }
+
+    /// Fails once a daily limit has been reached.
+    pub fn check_limits(&self) -> Result<(), GeminiError> {
+        self.lock().check_limits()
+    }
+
+    /// Reserves one request and an estimated `tokens` against the daily
+    /// limits, failing if a limit has been reached. The check and the
+    /// reservation happen under one lock, so concurrent requests cannot all
+    /// pass the check before any of them is counted.
+    pub fn reserve(&self, tokens: u64) -> Result<UsageReservation, GeminiError> {
+        let mut state = self.lock();
+        state.check_limits()?;
+        state.reserved.requests += 1;
+        state.reserved.total_tokens += tokens;
+        Ok(UsageReservation {
+            state: Arc::clone(&self.state),
+            tokens,
+        })
+    }
+
+    /// Records a request that sent `files` files.
+    pub fn record_request(
+        &self,
+        kind: RequestKind,
+        model: &str,
+        files: usize,
+        usage: Option<&UsageMetadata>,
+        latency: Duration,
+    ) {
+        let mut state = self.lock();
+        let mut record = UsageRecord::new(kind, model, state.folder.clone());
+        record.files = files as u64;
+        if let Some(usage) = usage {
+            record.prompt_tokens = usage.prompt_token_count;
+            record.output_tokens = usage.candidates_token_count;
+            record.total_tokens = usage.total_token_count;
+        }
+        record.latency_ms = latency.as_millis() as u64;
+        state.push(record);
+    }
+
+    /// Records an attempt that Gemini answered with an error. It used no
+    /// tokens but counts toward the daily request limit.
+    pub fn record_failed_attempt(&self, kind: RequestKind, model: &str, latency: Duration) {
+        let mut state = self.lock();
+        let mut record = UsageRecord::new(kind, model, state.folder.clone());
+        record.latency_ms = latency.as_millis() as u64;
+        state.push(record);
+    }
+
+    /// Records `files` files answered from the cache.
+    pub fn record_cache_hit(&self, kind: RequestKind, model: &str, files: usize) {
+        let mut state = self.lock();
+        let mut record = UsageRecord::new(kind, model, state.folder.clone());
+        record.cache_hit = true;
+        record.files = files as u64;
+        state.push(record);
+    }
+
+    /// Removes and returns everything recorded so far.
+    pub fn take_records(&self) -> Vec<UsageRecord> {
+        std::mem::take(&mut self.lock().records)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TrackerState> {
+        lock_state(&self.state)
+    }
+}
+
+fn lock_state(state: &Mutex<TrackerState>) -> MutexGuard<'_, TrackerState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl TrackerState {
+    fn check_limits(&self) -> Result<(), GeminiError> {
+        let mut used = self.used_today;
+        used.merge(&self.reserved);
+        match self.limits.exceeded_by(&used) {
+            Some(details) => Err(GeminiError::UsageLimitReached(details)),
+            None => Ok(()),
+        }
+    }
+
+    fn push(&mut self, record: UsageRecord) {
+        self.used_today.add(&record);
+        self.records.push(record);
+    }
+}
+
+#[cfg(test)]
+#[path = "usage_test.rs"]
+mod tests;
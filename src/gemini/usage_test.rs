@@ -0,0 +1,165 @@
+use super::*;
+
+fn metadata(total: u64) -> UsageMetadata {
+    UsageMetadata {
+        prompt_token_count: total / 2,
+        candidates_token_count: total / 2,
+        total_token_count: total,
+    }
+}
+
+#[test]
+fn test_records_are_shared_between_clones() {
+    let tracker = UsageTracker::new();
+    let clone = tracker.clone();
+    tracker.set_folder(Path::new("/downloads"));
+
+    clone.record_request(
+        RequestKind::Categorization,
+        "model",
+        1,
+        Some(&metadata(100)),
+        Duration::from_millis(250),
+    );
+
+    let records = tracker.take_records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].total_tokens, 100);
+    assert_eq!(records[0].latency_ms, 250);
+    assert_eq!(records[0].folder.as_deref(), Some("/downloads"));
+    assert!(tracker.take_records().is_empty());
+}
+
+#[test]
+fn test_no_limits_never_blocks() {
+    let tracker = UsageTracker::new();
+    for _ in 0..10 {
+        tracker.record_request(RequestKind::SubCategory, "model", 1, None, Duration::ZERO);
+    }
+    assert!(tracker.check_limits().is_ok());
+}
+
+#[test]
+fn test_request_limit_counts_prior_usage() {
+    let tracker = UsageTracker::new();
+    let used_today = UsageTotals {
+        requests: 1,
+        ..Default::default()
+    };
+    tracker.set_limits(
+        UsageLimits {
+            max_requests_per_day: Some(2),
+            ..Default::default()
+        },
+        used_today,
+    );
+    assert!(tracker.check_limits().is_ok());
+
+    tracker.record_request(
+        RequestKind::Categorization,
+        "model",
+        1,
+        None,
+        Duration::ZERO,
+    );
+
+    assert!(matches!(
+        tracker.check_limits(),
+        Err(GeminiError::UsageLimitReached(_))
+    ));
+}
+
+#[test]
+fn test_token_limit_ignores_cache_hits() {
+    let tracker = UsageTracker::new();
+    tracker.set_limits(
+        UsageLimits {
+            max_tokens_per_day: Some(150),
+            ..Default::default()
+        },
+        UsageTotals::default(),
+    );
+
+    tracker.record_cache_hit(RequestKind::Categorization, "model", 3);
+    tracker.record_request(
+        RequestKind::Categorization,
+        "model",
+        1,
+        Some(&metadata(100)),
+        Duration::ZERO,
+    );
+    assert!(tracker.check_limits().is_ok());
+
+    tracker.record_request(
+        RequestKind::Categorization,
+        "model",
+        1,
+        Some(&metadata(100)),
+        Duration::ZERO,
+    );
+    assert!(tracker.check_limits().is_err());
+}
+
+#[test]
+fn test_hit_rate_counts_files_not_records() {
+    let tracker = UsageTracker::new();
+    tracker.record_cache_hit(RequestKind::Categorization, "model", 500);
+    tracker.record_request(
+        RequestKind::Categorization,
+        "model",
+        1,
+        None,
+        Duration::ZERO,
+    );
+
+    let mut totals = UsageTotals::default();
+    for record in tracker.take_records() {
+        totals.add(&record);
+    }
+    assert_eq!(totals.cache_hits, 1);
+    assert_eq!(totals.requests, 1);
+    assert_eq!(totals.cached_files, 500);
+    assert_eq!(totals.requested_files, 1);
+    assert!(totals.file_hit_rate() > 99.0);
+}
+
+#[test]
+fn test_reservations_count_against_limits_until_dropped() {
+    let tracker = UsageTracker::new();
+    tracker.set_limits(
+        UsageLimits {
+            max_requests_per_day: Some(2),
+            ..Default::default()
+        },
+        UsageTotals::default(),
+    );
+
+    let first = tracker.reserve(0).unwrap();
+    let _second = tracker.reserve(0).unwrap();
+    assert!(matches!(
+        tracker.reserve(0),
+        Err(GeminiError::UsageLimitReached(_))
+    ));
+
+    drop(first);
+    assert!(tracker.reserve(0).is_ok());
+}
+
+#[test]
+fn test_reserved_tokens_count_against_token_limit() {
+    let tracker = UsageTracker::new();
+    tracker.set_limits(
+        UsageLimits {
+            max_tokens_per_day: Some(1_000),
+            ..Default::default()
+        },
+        UsageTotals::default(),
+    );
+
+    let in_flight = tracker.reserve(1_000).unwrap();
+    assert!(tracker.check_limits().is_err());
+    assert!(tracker.reserve(10).is_err());
+
+    drop(in_flight);
+    assert!(tracker.reserve(10).is_ok());
+}
@@ -1,5 +1,8 @@
 use clap::Parser;
-use noentropy::cli::{Args, Command, handle_organization, handle_undo};
+use noentropy::cli::{
+    Args, Command, handle_cache, handle_discover, handle_history, handle_organization, handle_redo,
+    handle_undo, handle_usage,
+};
 use noentropy::error::Result;
 use noentropy::files::duplicate::execute_delete;
 use noentropy::settings::config::change_and_prompt_api_key;
@@ -17,10 +20,27 @@ async fn main() -> Result<()> {
             let config = get_or_prompt_config()?;
             handle_organization(args, config).await?;
         }
+        Some(Command::Discover { .. }) => {
+            let config = get_or_prompt_config()?;
+            handle_discover(args.command.as_ref().unwrap(), config).await?;
+        }
         Some(Command::Undo { .. }) => {
             let download_path = get_or_prompt_download_folder()?;
             handle_undo(args.command.as_ref().unwrap(), download_path).await?;
         }
+        Some(Command::Redo { .. }) => {
+            let download_path = get_or_prompt_download_folder()?;
+            handle_redo(args.command.as_ref().unwrap(), download_path).await?;
+        }
+        Some(Command::History { .. }) => {
+            handle_history(args.command.as_ref().unwrap())?;
+        }
+        Some(Command::Usage { .. }) => {
+            handle_usage(args.command.as_ref().unwrap())?;
+        }
+        Some(Command::Cache { .. }) => {
+            handle_cache(args.command.as_ref().unwrap())?;
+        }
         Some(Command::ChangeKey) => {
             change_and_prompt_api_key()?;
         }
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// Which per-file details are shared with the AI during categorization.
+/// Every field is off unless the user turns it on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FileContextFields {
+    pub size: bool,
+    pub modified: bool,
+    pub mime_type: bool,
+    pub parent_dir: bool,
+    pub snippet: bool,
+}
+
+impl Default for FileContextFields {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl FileContextFields {
+    /// Shares nothing but the filename.
+    pub fn none() -> Self {
+        Self {
+            size: false,
+            modified: false,
+            mime_type: false,
+            parent_dir: false,
+            snippet: false,
+        }
+    }
+
+    /// Names of the enabled fields, comma-separated, so that a change in
+    /// what is shared changes the cache fingerprint.
+    pub fn enabled_names(&self) -> String {
+        [
+            (self.size, "size"),
+            (self.modified, "modified"),
+            (self.mime_type, "mime_type"),
+            (self.parent_dir, "parent_dir"),
+            (self.snippet, "snippet"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+
+    pub fn any(&self) -> bool {
+        self.size || self.modified || self.mime_type || self.parent_dir || self.snippet
+    }
+
+    /// Rough number of extra prompt tokens each file costs with these fields.
+    pub fn estimated_tokens(&self) -> usize {
+        let mut tokens = 0;
+        if self.size {
+            tokens += 8;
+        }
+        if self.modified {
+            tokens += 10;
+        }
+        if self.mime_type {
+            tokens += 12;
+        }
+        if self.parent_dir {
+            tokens += 12;
+        }
+        if self.snippet {
+            tokens += 80;
+        }
+        tokens
+    }
+}
+
+/// Per-file details sent alongside the filename in a categorization prompt.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct FileContext {
+    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
@@ -1,7 +1,6 @@
 use crate::error::Result;
-use crate::models::organization::OrganizationPlan;
+use crate::models::organization::FileCategory;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::time::UNIX_EPOCH;
@@ -24,9 +23,58 @@ impl FileMetadata {
     }
 }
 
+/// The cached categorization of one file.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CacheEntry {
-    pub response: OrganizationPlan,
+    pub category: FileCategory,
     pub timestamp: u64,
-    pub file_metadata: HashMap<String, FileMetadata>,
+    /// Size and modification time when the file was categorized; `None` if
+    /// the file could not be read.
+    #[serde(default)]
+    pub metadata: Option<FileMetadata>,
+    /// Digest of the `CacheFingerprint` the file was categorized under.
+    #[serde(default)]
+    pub fingerprint: String,
+    /// Folder the file was in; `None` for imported entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+}
+
+/// A cached deep-inspection result, shared by every file with the same
+/// contents and top-level category.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubCategoryEntry {
+    pub sub_category: String,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub fingerprint: String,
+}
+
+/// The settings a categorization depends on. Entries cached under a
+/// different fingerprint are not reused.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheFingerprint {
+    pub categories: Vec<String>,
+    pub model: String,
+    pub prompt_version: String,
+    pub backend: String,
+}
+
+impl CacheFingerprint {
+    pub fn digest(&self) -> String {
+        let mut categories = self.categories.clone();
+        categories.sort();
+
+        let mut hasher = blake3::Hasher::new();
+        for part in categories.iter().chain([
+            &String::new(),
+            &self.model,
+            &self.prompt_version,
+            &self.backend,
+        ]) {
+            hasher.update(&(part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        hasher.finalize().to_hex().to_string()
+    }
 }
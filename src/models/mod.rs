@@ -1,7 +1,15 @@
+pub mod file_context;
 pub mod metadata;
 pub mod move_record;
+pub mod multimodal;
 pub mod organization;
+pub mod prompts;
+pub mod usage;
 
-pub use metadata::{CacheEntry, FileMetadata};
-pub use move_record::{FileMoveRecord, MoveStatus};
+pub use file_context::{FileContext, FileContextFields};
+pub use metadata::{CacheEntry, CacheFingerprint, FileMetadata, SubCategoryEntry};
+pub use move_record::{FileMoveRecord, FileStamp, MoveStatus, SessionMode, UndoSession};
+pub use multimodal::MultimodalConfig;
 pub use organization::{FileCategory, OrganizationPlan};
+pub use prompts::PromptConfig;
+pub use usage::{RequestKind, UsageLimits, UsageRecord, UsageTotals};
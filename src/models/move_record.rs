@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -8,6 +10,14 @@ pub struct FileMoveRecord {
     pub destination_path: PathBuf,
     pub timestamp: u64,
     pub status: MoveStatus,
+    /// The `UndoSession` the move was made in; `None` for moves recorded
+    /// before sessions existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<u64>,
+    /// The restored file as it was right after the move was undone, so a
+    /// redo can tell whether it has changed since.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undone_file: Option<FileStamp>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -15,6 +25,35 @@ pub enum MoveStatus {
     Completed,
     Undone,
     Failed,
+    /// An undone move that was applied again; a fresh `Completed` record
+    /// tracks the new move.
+    Redone,
+}
+
+/// Size and modification time of a file, used to tell whether it changed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch
+    pub modified: u64,
+}
+
+impl FileStamp {
+    /// Reads the stamp of the file at `path`, or `None` if it isn't a
+    /// readable file.
+    pub fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some(Self {
+            size: metadata.len(),
+            modified: u64::try_from(modified).ok()?,
+        })
+    }
 }
 
 impl FileMoveRecord {
@@ -29,6 +68,46 @@ impl FileMoveRecord {
             destination_path,
             timestamp,
             status,
+            session: None,
+            undone_file: None,
+        }
+    }
+
+    /// True if both records describe the same move: same paths, time and
+    /// session, whatever their status.
+    pub fn is_same_move(&self, other: &FileMoveRecord) -> bool {
+        self.source_path == other.source_path
+            && self.destination_path == other.destination_path
+            && self.timestamp == other.timestamp
+            && self.session == other.session
+    }
+}
+
+/// How the files in a session were categorized.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionMode {
+    Online,
+    Offline,
+}
+
+impl fmt::Display for SessionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionMode::Online => write!(f, "online"),
+            SessionMode::Offline => write!(f, "offline"),
         }
     }
 }
+
+/// One organize run, whose moves can be undone together.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UndoSession {
+    pub id: u64,
+    pub timestamp: u64,
+    pub target_path: PathBuf,
+    pub mode: SessionMode,
+    /// What the run did, such as "3 moved (Documents 2, Images 1)"
+    #[serde(default)]
+    pub summary: String,
+}
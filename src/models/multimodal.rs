@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for sending images and PDF pages to the AI during deep inspection.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MultimodalConfig {
+    pub enabled: bool,
+    /// Source files larger than this are not loaded.
+    pub max_file_mb: u64,
+    /// Images are downscaled so their longest side fits within this.
+    pub max_image_dimension: u32,
+    /// Prepared attachments larger than this are not sent.
+    pub max_attachment_kb: u64,
+    /// Total attachment bytes sent in one run, counted base64-encoded.
+    pub max_run_mb: u64,
+}
+
+impl Default for MultimodalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_file_mb: 20,
+            max_image_dimension: 1024,
+            max_attachment_kb: 1024,
+            max_run_mb: 25,
+        }
+    }
+}
+
+impl MultimodalConfig {
+    pub fn max_file_bytes(&self) -> u64 {
+        self.max_file_mb.saturating_mul(1024 * 1024)
+    }
+
+    pub fn max_attachment_bytes(&self) -> u64 {
+        self.max_attachment_kb.saturating_mul(1024)
+    }
+
+    pub fn max_run_bytes(&self) -> u64 {
+        self.max_run_mb.saturating_mul(1024 * 1024)
+    }
+}
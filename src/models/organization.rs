@@ -1,13 +1,72 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct FileCategory {
     pub filename: String,
     pub category: String,
     pub sub_category: String,
+    /// Model's confidence in the category, from 0.0 to 1.0, when it reported one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// One-line rationale for the category.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reasoning: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl FileCategory {
+    /// Files without a reported confidence are never considered low confidence.
+    pub fn is_below_confidence(&self, threshold: f32) -> bool {
+        self.confidence.is_some_and(|c| c < threshold)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct OrganizationPlan {
     pub files: Vec<FileCategory>,
+    /// Requested files the AI never returned a category for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uncategorized: Vec<String>,
+    /// Low-confidence files held back from automatic moves until approved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub needs_review: Vec<FileCategory>,
+}
+
+impl OrganizationPlan {
+    /// Moves files whose confidence is below `threshold` into `needs_review`.
+    pub fn hold_low_confidence(&mut self, threshold: f32) {
+        let (held, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.files)
+            .into_iter()
+            .partition(|f| f.is_below_confidence(threshold));
+        self.files = kept;
+        self.needs_review.extend(held);
+    }
+
+    /// Returns every held file to the list of files to move.
+    pub fn approve_review(&mut self) {
+        self.files.append(&mut self.needs_review);
+    }
+
+    /// Distinct categories used by the plan, sorted.
+    pub fn categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = self
+            .files
+            .iter()
+            .chain(&self.needs_review)
+            .map(|f| f.category.clone())
+            .collect();
+        categories.sort_unstable();
+        categories.dedup();
+        categories
+    }
+
+    /// Sets the sub-category of every planned file found in `sub_categories`,
+    /// keyed by filename. Other files are left unchanged.
+    pub fn apply_sub_categories(&mut self, sub_categories: &HashMap<String, String>) {
+        for file in self.files.iter_mut().chain(self.needs_review.iter_mut()) {
+            if let Some(sub_category) = sub_categories.get(&file.filename) {
+                file.sub_category = sub_category.clone();
+            }
+        }
+    }
 }
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// User overrides for the prompts sent to the AI. Templates set here take
+/// precedence over `<dir>/<name>.txt`, which takes precedence over the
+/// built-in wording.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PromptConfig {
+    /// Language or locale for new folder names, such as `en` or `de-DE`.
+    pub locale: String,
+    /// Directory holding template files named after the templates below.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categorization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_sub_category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_sub_category: Option<String>,
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            locale: "en".to_string(),
+            dir: None,
+            categorization: None,
+            sub_category: None,
+            media_sub_category: None,
+            batch_sub_category: None,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What a Gemini request was made for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestKind {
+    Categorization,
+    SubCategory,
+    Discovery,
+}
+
+/// One Gemini request, or one categorization or deep inspection answered
+/// from the cache.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub timestamp: u64,
+    pub kind: RequestKind,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub cache_hit: bool,
+    /// Files answered from the cache, or sent in the request.
+    #[serde(default)]
+    pub files: u64,
+}
+
+impl UsageRecord {
+    pub fn new(kind: RequestKind, model: &str, folder: Option<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Self {
+            timestamp,
+            kind,
+            model: model.to_string(),
+            folder,
+            prompt_tokens: 0,
+            output_tokens: 0,
+            total_tokens: 0,
+            latency_ms: 0,
+            cache_hit: false,
+            files: 0,
+        }
+    }
+}
+
+/// Aggregated usage over a set of records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub cache_hits: u64,
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    pub latency_ms: u64,
+    /// Files answered from the cache.
+    pub cached_files: u64,
+    /// Files sent to the API.
+    pub requested_files: u64,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, record: &UsageRecord) {
+        if record.cache_hit {
+            self.cache_hits += 1;
+            self.cached_files += record.files;
+            return;
+        }
+        self.requests += 1;
+        self.requested_files += record.files;
+        self.prompt_tokens += record.prompt_tokens;
+        self.output_tokens += record.output_tokens;
+        self.total_tokens += record.total_tokens;
+        self.latency_ms += record.latency_ms;
+    }
+
+    pub fn merge(&mut self, other: &UsageTotals) {
+        self.requests += other.requests;
+        self.cache_hits += other.cache_hits;
+        self.prompt_tokens += other.prompt_tokens;
+        self.output_tokens += other.output_tokens;
+        self.total_tokens += other.total_tokens;
+        self.latency_ms += other.latency_ms;
+        self.cached_files += other.cached_files;
+        self.requested_files += other.requested_files;
+    }
+
+    /// Percentage of files answered from the cache rather than sent to
+    /// the API.
+    pub fn file_hit_rate(&self) -> f64 {
+        let files = self.cached_files + self.requested_files;
+        if files == 0 {
+            return 0.0;
+        }
+        self.cached_files as f64 * 100.0 / files as f64
+    }
+
+    pub fn average_latency_ms(&self) -> u64 {
+        self.latency_ms.checked_div(self.requests).unwrap_or(0)
+    }
+}
+
+/// Optional per-day caps on Gemini usage. Days are counted in UTC.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct UsageLimits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_requests_per_day: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens_per_day: Option<u64>,
+}
+
+impl UsageLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_requests_per_day.is_none() && self.max_tokens_per_day.is_none()
+    }
+
+    /// Describes the first limit `used` has reached, if any.
+    pub fn exceeded_by(&self, used: &UsageTotals) -> Option<String> {
+        if let Some(max) = self.max_requests_per_day
+            && used.requests >= max
+        {
+            return Some(format!("{} of {} requests used today", used.requests, max));
+        }
+        if let Some(max) = self.max_tokens_per_day
+            && used.total_tokens >= max
+        {
+            return Some(format!(
+                "{} of {} tokens used today",
+                used.total_tokens, max
+            ));
+        }
+        None
+    }
+}
@@ -1,9 +1,19 @@
 use colored::*;
+use log::debug;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
 
-use crate::error::Result;
+use crate::error::{AppError, Result};
+use crate::gemini::PromptTemplates;
+use crate::models::{FileContextFields, MultimodalConfig, PromptConfig, UsageLimits};
+use crate::storage::atomic_file::{
+    Loaded, backup_path, corrupt_path, read_with_backup, write_atomic,
+};
 
 use super::prompt::Prompter;
 
@@ -20,9 +30,29 @@ pub fn default_categories() -> Vec<String> {
     ]
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Environment variable checked for the API key before anything else.
+pub const API_KEY_ENV: &str = "GEMINI_API_KEY";
+
+/// Output of `api_key_command`, kept so the command runs once per process.
+static COMMAND_API_KEY: Mutex<Option<(String, String)>> = Mutex::new(None);
+
+/// Settings that apply to a single folder, overriding the global ones.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct FolderProfile {
+    /// Categories used instead of `Config::categories` for this folder
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     pub api_key: String,
+    /// Shell command that prints the API key, such as `pass show gemini`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_command: Option<String>,
+    /// Gemini API root to send requests to instead of Google's, such as a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base_url: Option<String>,
     pub download_folder: PathBuf,
     #[serde(default = "default_categories")]
     pub categories: Vec<String>,
@@ -32,6 +62,24 @@ pub struct Config {
     pub offline_first: bool,
     #[serde(default = "default_prefer_online")]
     pub prefer_online: bool,
+    /// Per-file details shared with the AI during categorization
+    #[serde(default)]
+    pub file_context: FileContextFields,
+    /// Files the AI categorizes below this confidence are held for review
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: f32,
+    /// Optional per-day caps on Gemini requests and tokens
+    #[serde(default)]
+    pub usage_limits: UsageLimits,
+    /// Attach images and scanned PDF pages during deep inspection
+    #[serde(default)]
+    pub multimodal: MultimodalConfig,
+    /// Custom wording for the prompts sent to the AI
+    #[serde(default)]
+    pub prompts: PromptConfig,
+    /// Per-folder settings, keyed by the folder's absolute path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, FolderProfile>,
 }
 
 fn default_deep_inspect() -> bool {
@@ -46,17 +94,58 @@ fn default_prefer_online() -> bool {
     false
 }
 
+fn default_min_confidence() -> f32 {
+    0.5
+}
+
 impl Config {
     pub fn load() -> Result<Self> {
-        let config_path = Self::get_config_path()?;
+        Self::load_internal(false)
+    }
+
+    fn load_internal(silent: bool) -> Result<Self> {
+        Self::load_from(&Self::get_config_path()?, silent)
+    }
 
-        if !config_path.exists() {
-            return Err("Config file not found".into());
+    /// Loads the config, or the defaults when there is no usable config file
+    /// yet. Settings that are present but invalid are still an error, so a
+    /// file the user can fix is never saved over with defaults.
+    pub fn load_or_default() -> Result<Self> {
+        match Self::load() {
+            Err(AppError::ConfigError(e)) => Err(AppError::ConfigError(e)),
+            result => Ok(result.unwrap_or_default()),
         }
+    }
 
-        let content = std::fs::read_to_string(&config_path)?;
-        let config: Config = toml::from_str(&content)?;
+    /// Loads and validates the config file at `config_path`. Warnings about
+    /// a restored or corrupted file are printed unless `silent` is set.
+    pub(crate) fn load_from(config_path: &Path, silent: bool) -> Result<Self> {
+        let config = match read_with_backup(config_path, toml::from_str::<Config>)? {
+            Loaded::Missing => return Err("Config file not found".into()),
+            Loaded::Current(config) => config,
+            Loaded::Restored(config) => {
+                if !silent {
+                    eprintln!(
+                        "{} Config file was unreadable; restored the last good version from {}",
+                        "WARN:".yellow(),
+                        backup_path(config_path).display()
+                    );
+                }
+                config
+            }
+            Loaded::Corrupted(e) => {
+                if !silent {
+                    eprintln!(
+                        "{} Config file is not valid TOML; moved it to {}",
+                        "WARN:".yellow(),
+                        corrupt_path(config_path).display()
+                    );
+                }
+                return Err(e.into());
+            }
+        };
 
+        config.validate_min_confidence()?;
         Ok(config)
     }
 
@@ -73,7 +162,7 @@ impl Config {
 
         let toml_string = toml::to_string_pretty(self)?;
 
-        std::fs::write(&config_path, toml_string)?;
+        write_atomic(&config_path, toml_string.as_bytes())?;
 
         if !silent {
             println!(
@@ -88,11 +177,51 @@ impl Config {
 
     pub fn get_api_key() -> Result<String> {
         match Self::load() {
-            Ok(config) => Ok(config.api_key),
+            Ok(config) => config.resolve_api_key(),
             Err(_) => Err("API key not configured".into()),
         }
     }
 
+    /// Returns the API key to use: `GEMINI_API_KEY` when set, otherwise the
+    /// output of `api_key_command`, otherwise `api_key` from the config file.
+    /// Returns an empty string when no key is configured.
+    pub fn resolve_api_key(&self) -> Result<String> {
+        if let Ok(key) = std::env::var(API_KEY_ENV)
+            && !key.trim().is_empty()
+        {
+            debug!("Using API key {} from {}", mask_api_key(&key), API_KEY_ENV);
+            return Ok(key.trim().to_string());
+        }
+
+        if let Some(command) = self.api_key_command.as_deref()
+            && !command.trim().is_empty()
+        {
+            let key = run_api_key_command(command)?;
+            debug!("Using API key {} from api_key_command", mask_api_key(&key));
+            return Ok(key);
+        }
+
+        Ok(self.api_key.clone())
+    }
+
+    /// Loads and validates the prompt templates configured in `[prompts]`.
+    pub fn prompt_templates(&self) -> Result<PromptTemplates> {
+        PromptTemplates::load(&self.prompts).map_err(|e| AppError::ConfigError(e.to_string()))
+    }
+
+    /// Rejects a `min_confidence` outside 0.0–1.0, which would hold every
+    /// file for review or none of them.
+    pub fn validate_min_confidence(&self) -> Result<()> {
+        if (0.0..=1.0).contains(&self.min_confidence) {
+            Ok(())
+        } else {
+            Err(AppError::ConfigError(format!(
+                "min_confidence must be between 0.0 and 1.0, got {}",
+                self.min_confidence
+            )))
+        }
+    }
+
     pub fn get_download_folder() -> Result<PathBuf> {
         match Self::load() {
             Ok(config) => Ok(config.download_folder),
@@ -122,16 +251,76 @@ impl Config {
         Ok(data_dir)
     }
 
+    pub fn get_cache_path() -> Result<PathBuf> {
+        Ok(Self::get_data_dir()?.join(".noentropy_cache.json"))
+    }
+
     pub fn get_undo_log_path() -> Result<PathBuf> {
         Ok(Self::get_data_dir()?.join("undo_log.json"))
     }
+
+    pub fn get_usage_log_path() -> Result<PathBuf> {
+        Ok(Self::get_data_dir()?.join("usage_log.json"))
+    }
+}
+
+/// Runs `command` through the shell and returns the first line it prints.
+fn run_api_key_command(command: &str) -> Result<String> {
+    let mut cached = COMMAND_API_KEY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_command, key)) = cached.as_ref()
+        && cached_command == command
+    {
+        return Ok(key.clone());
+    }
+
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| format!("Failed to run api_key_command: {}", e))?;
+
+    if !output.status.success() {
+        // Only stderr is shown; stdout may hold part of a key
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        if stderr.is_empty() {
+            return Err(format!("api_key_command exited with {}", output.status).into());
+        }
+        return Err(format!("api_key_command exited with {}: {}", output.status, stderr).into());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let key = stdout.lines().next().unwrap_or_default().trim().to_string();
+    if key.is_empty() {
+        return Err("api_key_command printed no API key".into());
+    }
+
+    *cached = Some((command.to_string(), key.clone()));
+    Ok(key)
+}
+
+/// Shortens an API key for display, keeping only its first and last four
+/// characters.
+pub fn mask_api_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.is_empty() {
+        return String::new();
+    }
+    if chars.len() <= 12 {
+        return "****".to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
 }
 
 pub fn get_or_prompt_api_key() -> Result<String> {
     if let Ok(config) = Config::load()
-        && !config.api_key.is_empty()
+        && let Ok(key) = config.resolve_api_key()
+        && !key.is_empty()
     {
-        return Ok(config.api_key);
+        return Ok(key);
     }
 
     println!();
@@ -140,7 +329,7 @@ pub fn get_or_prompt_api_key() -> Result<String> {
 
     let api_key = Prompter::prompt_api_key()?;
 
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_or_default()?;
     config.api_key = api_key.clone();
     config.save()?;
 
@@ -155,7 +344,7 @@ pub fn change_and_prompt_api_key() -> Result<()> {
 
     let api_key = Prompter::prompt_api_key()?;
 
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_or_default()?;
     config.api_key = api_key;
     config.save()?;
 
@@ -176,7 +365,7 @@ pub fn get_or_prompt_download_folder() -> Result<PathBuf> {
 
     let folder_path = Prompter::prompt_download_folder()?;
 
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_or_default()?;
     config.download_folder = folder_path.clone();
     config.save()?;
 
@@ -185,11 +374,14 @@ pub fn get_or_prompt_download_folder() -> Result<PathBuf> {
 }
 
 pub fn get_or_prompt_config() -> Result<Config> {
-    let mut config = Config::load().unwrap_or_default();
+    let mut config = Config::load_or_default()?;
     let mut needs_save = false;
 
     // Check API key - now optional, user can skip and use offline mode
-    if config.api_key.is_empty() {
+    if config.api_key.is_empty()
+        && config.api_key_command.is_none()
+        && !std::env::var(API_KEY_ENV).is_ok_and(|key| !key.trim().is_empty())
+    {
         println!();
         println!("{}", "🔑 NoEntropy Configuration".bold().cyan());
         println!("{}", "─────────────────────────────".cyan());
@@ -214,6 +406,9 @@ pub fn get_or_prompt_config() -> Result<Config> {
         println!();
     }
 
+    // Report broken prompt templates before any files are scanned
+    config.prompt_templates()?;
+
     Ok(config)
 }
 
@@ -221,15 +416,44 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             api_key: String::new(),
+            api_key_command: None,
+            api_base_url: None,
             download_folder: PathBuf::new(),
             categories: default_categories(),
             deep_inspect: default_deep_inspect(),
             offline_first: default_offline_first(),
             prefer_online: default_prefer_online(),
+            file_context: FileContextFields::default(),
+            min_confidence: default_min_confidence(),
+            usage_limits: UsageLimits::default(),
+            multimodal: MultimodalConfig::default(),
+            prompts: PromptConfig::default(),
+            profiles: BTreeMap::new(),
         }
     }
 }
 
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("api_key", &mask_api_key(&self.api_key))
+            .field("api_key_command", &self.api_key_command)
+            .field("api_base_url", &self.api_base_url)
+            .field("download_folder", &self.download_folder)
+            .field("categories", &self.categories)
+            .field("deep_inspect", &self.deep_inspect)
+            .field("offline_first", &self.offline_first)
+            .field("prefer_online", &self.prefer_online)
+            .field("file_context", &self.file_context)
+            .field("min_confidence", &self.min_confidence)
+            .field("usage_limits", &self.usage_limits)
+            .field("multimodal", &self.multimodal)
+            .field("prompts", &self.prompts)
+            .field("profiles", &self.profiles)
+            .finish()
+    }
+}
+
 impl Config {
     pub fn should_deep_inspect(&self, cli_skip: bool, cli_no_skip: bool) -> bool {
         if cli_skip {
@@ -254,13 +478,42 @@ impl Config {
         self.prefer_online
     }
 
+    /// Returns this config with the profile for `folder` applied, if one
+    /// exists. `folder` should be normalized the same way as when the
+    /// profile was saved.
+    pub fn for_folder(&self, folder: &Path) -> Config {
+        let mut config = self.clone();
+        if let Some(profile) = self.profiles.get(folder.to_string_lossy().as_ref())
+            && !profile.categories.is_empty()
+        {
+            config.categories = profile.categories.clone();
+        }
+        config
+    }
+
+    /// Saves `categories` as the categories used for `folder` only.
+    pub fn set_folder_categories(&mut self, folder: &Path, categories: Vec<String>) {
+        self.profiles
+            .entry(folder.to_string_lossy().into_owned())
+            .or_default()
+            .categories = categories;
+    }
+
     pub fn set_prefer_online(&mut self, prefer_online: bool) -> Result<()> {
-        self.prefer_online = prefer_online;
-        self.save()
+        self.set_prefer_online_internal(prefer_online, false)
     }
 
     pub fn set_prefer_online_silent(&mut self, prefer_online: bool) -> Result<()> {
+        self.set_prefer_online_internal(prefer_online, true)
+    }
+
+    /// Updates the preference in the saved config rather than saving `self`,
+    /// which may carry a folder profile's categories. Fails, saving nothing,
+    /// if the saved config can't be loaded.
+    fn set_prefer_online_internal(&mut self, prefer_online: bool, silent: bool) -> Result<()> {
+        let mut saved = Self::load_internal(silent)?;
         self.prefer_online = prefer_online;
-        self.save_silent()
+        saved.prefer_online = prefer_online;
+        saved.save_internal(silent)
     }
 }
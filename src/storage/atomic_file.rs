@@ -0,0 +1,219 @@
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// What `read_with_backup` found at a path.
+#[derive(Debug)]
+pub enum Loaded<T, E> {
+    /// Neither the file nor its backup exists.
+    Missing,
+    /// The file parsed.
+    Current(T),
+    /// The file was missing or did not parse, and its backup was restored
+    /// in its place. A file that did not parse is kept at `corrupt_path`.
+    Restored(T),
+    /// Neither the file nor its backup parsed. The file was moved to
+    /// `corrupt_path` so that saving a fresh one does not destroy it.
+    Corrupted(E),
+}
+
+/// The copy of the last good version of `path`, refreshed on every save.
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, "bak")
+}
+
+/// Where a file that failed to parse is moved before it is replaced.
+pub fn corrupt_path(path: &Path) -> PathBuf {
+    with_suffix(path, "corrupt")
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    with_suffix(path, "lock")
+}
+
+fn session_lock_path(path: &Path) -> PathBuf {
+    with_suffix(path, "session.lock")
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    with_suffix(path, "tmp")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// An advisory lock on `<path>.lock`, held until dropped. Other NoEntropy
+/// processes wait for it before reading or writing `path`.
+///
+/// The lock covers a single `read_with_backup` or `write_atomic` call, so a
+/// reader never sees a half-written file. Processes that load, modify and
+/// save `path` also hold a `SessionLock` for the whole cycle.
+struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    fn acquire(path: &Path) -> io::Result<Self> {
+        let file = Self::open(&lock_path(path))?;
+        file.lock()?;
+        Ok(Self { _file: file })
+    }
+
+    fn open(lock_path: &Path) -> io::Result<File> {
+        if let Some(parent) = lock_path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)
+    }
+}
+
+/// An advisory lock on `<path>.session.lock`, held from loading `path`
+/// until after it is saved, so that two NoEntropy runs updating the same
+/// file cannot lose each other's changes. It is separate from the lock
+/// each read and write takes, so commands that only read `path` are not
+/// held up by a run in progress.
+pub struct SessionLock {
+    _file: File,
+}
+
+impl SessionLock {
+    /// Waits until no other process holds the session lock on `path`. A
+    /// message is printed before waiting unless `silent` is set.
+    pub fn acquire(path: &Path, silent: bool) -> io::Result<Self> {
+        let file = FileLock::open(&session_lock_path(path))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                if !silent {
+                    println!(
+                        "Another NoEntropy run is using {}; waiting for it to finish...",
+                        path.display()
+                    );
+                }
+                file.lock()?;
+            }
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+        Ok(Self { _file: file })
+    }
+}
+
+/// Replaces `path` with `content` so that a crash leaves either the old or
+/// the new file, never a partial one. The previous file is kept as the
+/// backup, and both keep the previous file's permissions, so a config file
+/// restricted to its owner stays that way.
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let _lock = FileLock::acquire(path)?;
+    if path.exists() {
+        let backup = backup_path(path);
+        fs::copy(path, &backup)?;
+        // `copy` keeps the permissions of a backup that already existed
+        fs::set_permissions(&backup, fs::metadata(path)?.permissions())?;
+    }
+    replace(path, content, path)
+}
+
+/// Writes `content` to `path` through a temp file, giving it the
+/// permissions of `permissions_from` when that file exists.
+fn replace(path: &Path, content: &[u8], permissions_from: &Path) -> io::Result<()> {
+    let temp = temp_path(path);
+    let mut file = File::create(&temp)?;
+    if let Ok(metadata) = fs::metadata(permissions_from) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.write_all(content)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp, path)?;
+    sync_parent(path);
+    Ok(())
+}
+
+/// Makes the rename itself durable. Not possible on every platform, so
+/// failures are ignored.
+fn sync_parent(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent()
+        && let Ok(dir) = File::open(if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        })
+    {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// Reads and parses `path`, falling back to its backup when the file is
+/// missing or does not parse. A usable backup is written back in place of
+/// the file.
+pub fn read_with_backup<T, E>(
+    path: &Path,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> io::Result<Loaded<T, E>> {
+    if !path.exists() && !backup_path(path).exists() {
+        return Ok(Loaded::Missing);
+    }
+
+    let _lock = FileLock::acquire(path)?;
+    let error = match read_optional(path)? {
+        Some(content) => match parse(&content) {
+            Ok(value) => return Ok(Loaded::Current(value)),
+            Err(e) => Some(e),
+        },
+        None => None,
+    };
+
+    let backup = backup_path(path);
+    if let Some(content) = read_optional(&backup)?
+        && let Ok(value) = parse(&content)
+    {
+        if error.is_some() {
+            fs::rename(path, corrupt_path(path))?;
+        }
+        replace(path, content.as_bytes(), &backup)?;
+        return Ok(Loaded::Restored(value));
+    }
+
+    match error {
+        Some(e) => {
+            fs::rename(path, corrupt_path(path))?;
+            Ok(Loaded::Corrupted(e))
+        }
+        // Only an unreadable backup is left; start over without touching it
+        None => Ok(Loaded::Missing),
+    }
+}
+
+fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    // Invalid UTF-8 is left for `parse` to reject like any other damage
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+#[path = "atomic_file_test.rs"]
+mod tests;
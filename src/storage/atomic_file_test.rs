@@ -0,0 +1,154 @@
+use super::*;
+use tempfile::TempDir;
+
+fn parse_number(content: &str) -> Result<u32, std::num::ParseIntError> {
+    content.trim().parse()
+}
+
+#[test]
+fn test_write_atomic_keeps_previous_version_as_backup() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.json");
+
+    write_atomic(&path, b"1").unwrap();
+    assert!(!backup_path(&path).exists());
+    write_atomic(&path, b"2").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+    assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "1");
+    assert!(!temp_path(&path).exists());
+}
+
+#[test]
+fn test_write_atomic_creates_parent_directories() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("nested").join("data.json");
+
+    write_atomic(&path, b"1").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "1");
+}
+
+#[test]
+fn test_read_with_backup_missing() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.json");
+
+    let loaded = read_with_backup(&path, parse_number).unwrap();
+
+    assert!(matches!(loaded, Loaded::Missing));
+}
+
+#[test]
+fn test_read_with_backup_current() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.json");
+    write_atomic(&path, b"7").unwrap();
+
+    let loaded = read_with_backup(&path, parse_number).unwrap();
+
+    assert!(matches!(loaded, Loaded::Current(7)));
+}
+
+#[test]
+fn test_read_with_backup_restores_corrupted_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.json");
+    write_atomic(&path, b"1").unwrap();
+    write_atomic(&path, b"2").unwrap();
+    fs::write(&path, b"2 truncat").unwrap();
+
+    let loaded = read_with_backup(&path, parse_number).unwrap();
+
+    assert!(matches!(loaded, Loaded::Restored(1)));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "1");
+    assert_eq!(
+        fs::read_to_string(corrupt_path(&path)).unwrap(),
+        "2 truncat"
+    );
+}
+
+#[test]
+fn test_read_with_backup_restores_deleted_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.json");
+    write_atomic(&path, b"1").unwrap();
+    write_atomic(&path, b"2").unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let loaded = read_with_backup(&path, parse_number).unwrap();
+
+    assert!(matches!(loaded, Loaded::Restored(1)));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "1");
+}
+
+#[test]
+fn test_read_with_backup_keeps_unrecoverable_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.json");
+    fs::write(&path, [0xff, 0xfe]).unwrap();
+
+    let loaded = read_with_backup(&path, parse_number).unwrap();
+
+    assert!(matches!(loaded, Loaded::Corrupted(_)));
+    assert!(!path.exists());
+    assert_eq!(fs::read(corrupt_path(&path)).unwrap(), [0xff, 0xfe]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_write_atomic_keeps_restricted_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+
+    write_atomic(&path, b"1").unwrap();
+    write_atomic(&path, b"2").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    write_atomic(&path, b"3").unwrap();
+    assert_eq!(mode(&path), 0o600);
+    assert_eq!(mode(&backup_path(&path)), 0o600);
+
+    // A restored file and the kept corrupt copy stay private too
+    fs::write(&path, "not a number").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    assert!(matches!(
+        read_with_backup(&path, parse_number).unwrap(),
+        Loaded::Restored(2)
+    ));
+    assert_eq!(mode(&path), 0o600);
+    assert_eq!(mode(&corrupt_path(&path)), 0o600);
+}
+
+#[test]
+fn test_session_lock_waits_for_the_holder() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.json");
+    let held = SessionLock::acquire(&path, true).unwrap();
+
+    // Reads and writes only take the per-call lock, so the holder can save
+    write_atomic(&path, b"1").unwrap();
+    assert!(matches!(
+        read_with_backup(&path, parse_number).unwrap(),
+        Loaded::Current(1)
+    ));
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let waiting_path = path.clone();
+    let waiter = std::thread::spawn(move || {
+        let _lock = SessionLock::acquire(&waiting_path, true).unwrap();
+        sender.send(()).unwrap();
+    });
+
+    let timeout = std::time::Duration::from_millis(200);
+    assert!(receiver.recv_timeout(timeout).is_err());
+    drop(held);
+    assert!(
+        receiver
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .is_ok()
+    );
+    waiter.join().unwrap();
+}
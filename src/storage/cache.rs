@@ -1,18 +1,50 @@
 use crate::error::Result;
-use crate::models::{CacheEntry, FileMetadata, OrganizationPlan};
+use crate::models::{CacheEntry, FileCategory, FileMetadata, OrganizationPlan, SubCategoryEntry};
+use crate::storage::atomic_file::{Loaded, read_with_backup, write_atomic};
 use blake3::Hasher;
+use log::debug;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Categorizations stored per file, keyed by the file's name, size and
+/// modification time. A new download only costs a request for itself, and a
+/// file that changes on disk is categorized again. Entries stored under a
+/// different fingerprint (categories, model, prompt or backend) are misses.
+///
+/// Deep-inspection sub-categories are stored separately, keyed by the file's
+/// content hash, so renamed or re-downloaded copies reuse them too.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Cache {
-    entries: HashMap<String, CacheEntry>,
+    // Caches written before per-file entries used an `entries` field; they
+    // load as empty rather than as corrupted.
+    #[serde(default)]
+    files: HashMap<String, CacheEntry>,
+    #[serde(default)]
+    sub_categories: HashMap<String, SubCategoryEntry>,
     max_entries: usize,
 }
 
+/// Portable cache contents written by `noentropy cache export`. Entries
+/// are matched by filename and fingerprint once imported, so a team can
+/// share categorizations of common installer and document names.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CacheExport {
+    pub files: Vec<CacheEntry>,
+    #[serde(default)]
+    pub sub_categories: HashMap<String, SubCategoryEntry>,
+}
+
+/// Files split into cached categorizations and files that need a request.
+#[derive(Debug, Default)]
+pub struct CacheLookup {
+    /// Cached categorizations, in the order the files were requested.
+    pub cached: Vec<FileCategory>,
+    /// Files with no usable entry, in the order they were requested.
+    pub missing: Vec<String>,
+}
+
 impl Default for Cache {
     fn default() -> Self {
         Self::new()
@@ -21,167 +53,354 @@ impl Default for Cache {
 
 impl Cache {
     pub fn new() -> Self {
-        Self::with_max_entries(1000)
+        Self::with_max_entries(10_000)
     }
 
     pub fn with_max_entries(max_entries: usize) -> Self {
         Self {
-            entries: HashMap::new(),
+            files: HashMap::new(),
+            sub_categories: HashMap::new(),
             max_entries,
         }
     }
 
     pub fn load_or_create(cache_path: &Path, silent: bool) -> Self {
-        if !cache_path.exists() {
-            return Self::new();
-        }
-
-        match fs::read_to_string(cache_path) {
-            Ok(content) => match serde_json::from_str::<Cache>(&content) {
-                Ok(cache) => {
-                    if !silent {
-                        println!("Loaded cache with {} entries", cache.entries.len());
-                    }
-                    cache
-                }
-                Err(_) => {
-                    if !silent {
-                        println!("Cache corrupted, creating new cache");
-                    }
-                    Self::new()
+        let message = match read_with_backup(cache_path, |c| serde_json::from_str::<Cache>(c)) {
+            Ok(Loaded::Missing) => return Self::new(),
+            Ok(Loaded::Current(cache)) => {
+                if !silent {
+                    println!("Loaded cache with {} entries", cache.files.len());
                 }
-            },
-            Err(_) => {
+                return cache;
+            }
+            Ok(Loaded::Restored(cache)) => {
                 if !silent {
-                    println!("Failed to read cache, creating new cache");
+                    println!(
+                        "Cache corrupted, restored {} entries from backup",
+                        cache.files.len()
+                    );
                 }
-                Self::new()
+                return cache;
             }
+            Ok(Loaded::Corrupted(_)) => "Cache corrupted, creating new cache",
+            Err(_) => "Failed to read cache, creating new cache",
+        };
+
+        if !silent {
+            println!("{}", message);
         }
+        Self::new()
     }
 
     pub fn save(&self, cache_path: &Path) -> Result<()> {
-        if let Some(parent) = cache_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(cache_path, content)?;
+        write_atomic(cache_path, content.as_bytes())?;
         Ok(())
     }
 
-    pub fn check_cache(&self, filenames: &[String], base_path: &Path) -> Option<OrganizationPlan> {
-        let cache_key = Self::generate_cache_key(filenames);
-        let entry = self.entries.get(&cache_key)?;
-
-        let all_unchanged = filenames.iter().all(|filename| {
-            let file_path = base_path.join(filename);
-            FileMetadata::from_path(&file_path).ok().as_ref() == entry.file_metadata.get(filename)
-        });
+    /// Splits `filenames` into files whose categorization is cached under
+    /// `fingerprint`, a `CacheFingerprint` digest, and files that are new,
+    /// have changed, or were categorized under other settings.
+    pub fn lookup(&self, filenames: &[String], base_path: &Path, fingerprint: &str) -> CacheLookup {
+        let mut lookup = CacheLookup::default();
 
-        if all_unchanged {
-            println!("Using cached response (timestamp: {})", entry.timestamp);
-            Some(entry.response.clone())
-        } else {
-            None
+        for filename in filenames {
+            let metadata = FileMetadata::from_path(&base_path.join(filename)).ok();
+            // Imported entries are keyed by name and fingerprint
+            let entry = [
+                Self::file_key(filename, metadata.as_ref()),
+                Self::imported_key(filename, fingerprint),
+            ]
+            .iter()
+            .filter_map(|key| self.files.get(key))
+            .find(|entry| entry.fingerprint == fingerprint);
+            match entry {
+                Some(entry) => lookup.cached.push(entry.category.clone()),
+                None => lookup.missing.push(filename.clone()),
+            }
         }
+
+        lookup
     }
 
+    /// Stores the categorization of each file in `response` that is listed
+    /// in `filenames`, under the `fingerprint` digest. Returns how many
+    /// files were stored; callers decide whether to report it.
     pub fn cache_response(
         &mut self,
         filenames: &[String],
         response: OrganizationPlan,
         base_path: &Path,
-    ) {
-        let cache_key = Self::generate_cache_key(filenames);
+        fingerprint: &str,
+    ) -> usize {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
 
-        let file_metadata: HashMap<String, FileMetadata> = filenames
-            .iter()
-            .filter_map(|filename| {
-                let file_path = base_path.join(filename);
-                FileMetadata::from_path(&file_path)
-                    .ok()
-                    .map(|m| (filename.clone(), m))
-            })
-            .collect();
+        let mut stored = 0;
+        for category in response.files.into_iter().chain(response.needs_review) {
+            if !filenames.contains(&category.filename) {
+                continue;
+            }
+            let metadata = FileMetadata::from_path(&base_path.join(&category.filename)).ok();
+            let key = Self::file_key(&category.filename, metadata.as_ref());
+            self.files.insert(
+                key,
+                CacheEntry {
+                    category,
+                    timestamp,
+                    metadata,
+                    fingerprint: fingerprint.to_string(),
+                    folder: Some(base_path.to_string_lossy().into_owned()),
+                },
+            );
+            stored += 1;
+        }
+
+        self.evict_over_limit();
+        stored
+    }
 
+    /// Returns the cached sub-category for a file with `content_hash` in
+    /// `category`, if it was stored under `fingerprint`.
+    pub fn lookup_sub_category(
+        &self,
+        content_hash: &str,
+        category: &str,
+        fingerprint: &str,
+    ) -> Option<&str> {
+        self.sub_categories
+            .get(&Self::sub_category_key(content_hash, category))
+            .filter(|entry| entry.fingerprint == fingerprint)
+            .map(|entry| entry.sub_category.as_str())
+    }
+
+    pub fn cache_sub_category(
+        &mut self,
+        content_hash: &str,
+        category: &str,
+        sub_category: &str,
+        fingerprint: &str,
+    ) {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
+        self.sub_categories.insert(
+            Self::sub_category_key(content_hash, category),
+            SubCategoryEntry {
+                sub_category: sub_category.to_string(),
+                timestamp,
+                fingerprint: fingerprint.to_string(),
+            },
+        );
 
-        let entry = CacheEntry {
-            response,
-            timestamp,
-            file_metadata,
-        };
+        self.evict_over_limit();
+    }
 
-        self.entries.insert(cache_key, entry);
+    fn sub_category_key(content_hash: &str, category: &str) -> String {
+        format!("{}|{}", content_hash, category)
+    }
 
-        if self.entries.len() > self.max_entries {
-            self.evict_oldest();
+    /// Identifies a file by its name and, when it exists, its size and
+    /// modification time.
+    fn file_key(filename: &str, metadata: Option<&FileMetadata>) -> String {
+        let mut hasher = Hasher::new();
+        hasher.update(filename.as_bytes());
+        if let Some(metadata) = metadata {
+            hasher.update(b"|");
+            hasher.update(&metadata.size.to_le_bytes());
+            hasher.update(&metadata.modified.to_le_bytes());
         }
-
-        println!("Cached response for {} files", filenames.len());
+        hasher.finalize().to_hex().to_string()
     }
 
-    fn generate_cache_key(filenames: &[String]) -> String {
+    /// Identifies an imported entry by its name and fingerprint, so entries
+    /// exported under different settings do not replace one another.
+    fn imported_key(filename: &str, fingerprint: &str) -> String {
         let mut hasher = Hasher::new();
+        hasher.update(b"import|");
+        hasher.update(filename.as_bytes());
+        hasher.update(b"|");
+        hasher.update(fingerprint.as_bytes());
+        hasher.finalize().to_hex().to_string()
+    }
 
-        // Only sort if we have multiple files to ensure consistent ordering
-        if filenames.len() > 1 {
-            let mut sorted: Vec<_> = filenames.iter().collect();
-            sorted.sort();
+    /// Removes every entry, returning how many there were.
+    pub fn clear(&mut self) -> usize {
+        let removed = self.files.len() + self.sub_categories.len();
+        self.files.clear();
+        self.sub_categories.clear();
+        removed
+    }
 
-            for filename in sorted {
-                hasher.update(filename.as_bytes());
-                hasher.update(b"|");
+    /// Cached file categorizations, in no particular order.
+    pub fn entries(&self) -> impl Iterator<Item = &CacheEntry> {
+        self.files.values()
+    }
+
+    /// Cached deep-inspection sub-categories, in no particular order.
+    pub fn sub_category_entries(&self) -> impl Iterator<Item = &SubCategoryEntry> {
+        self.sub_categories.values()
+    }
+
+    /// Copies the cache for sharing, leaving out local file details and
+    /// folder paths. Each filename appears once, with its newest entry.
+    pub fn export(&self) -> CacheExport {
+        let mut files: HashMap<(&str, &str), &CacheEntry> = HashMap::new();
+        for entry in self.files.values() {
+            let key = (entry.category.filename.as_str(), entry.fingerprint.as_str());
+            if files
+                .get(&key)
+                .is_none_or(|e| e.timestamp < entry.timestamp)
+            {
+                files.insert(key, entry);
             }
-        } else if let Some(filename) = filenames.first() {
-            hasher.update(filename.as_bytes());
         }
 
-        hasher.finalize().to_hex().to_string()
+        let mut files: Vec<CacheEntry> = files
+            .into_values()
+            .map(|entry| CacheEntry {
+                metadata: None,
+                folder: None,
+                ..entry.clone()
+            })
+            .collect();
+        files.sort_by(|a, b| a.category.filename.cmp(&b.category.filename));
+
+        CacheExport {
+            files,
+            sub_categories: self.sub_categories.clone(),
+        }
     }
 
-    pub fn cleanup_old_entries(&mut self, max_age_seconds: u64) {
+    /// Adds exported entries, keeping whichever of two entries for the same
+    /// file and fingerprint is newer. Added entries are stamped with the import time so that
+    /// age-based cleanup counts from when they arrived, not from when the
+    /// exporter made them. Returns how many entries were added or replaced.
+    pub fn import(&mut self, export: CacheExport) -> usize {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut imported = 0;
+
+        for entry in export.files {
+            let key = Self::imported_key(&entry.category.filename, &entry.fingerprint);
+            if self
+                .files
+                .get(&key)
+                .is_none_or(|existing| existing.timestamp < entry.timestamp)
+            {
+                self.files.insert(
+                    key,
+                    CacheEntry {
+                        timestamp: now,
+                        metadata: None,
+                        folder: None,
+                        ..entry
+                    },
+                );
+                imported += 1;
+            }
+        }
+        for (key, entry) in export.sub_categories {
+            if self
+                .sub_categories
+                .get(&key)
+                .is_none_or(|existing| existing.timestamp < entry.timestamp)
+            {
+                self.sub_categories.insert(
+                    key,
+                    SubCategoryEntry {
+                        timestamp: now,
+                        ..entry
+                    },
+                );
+                imported += 1;
+            }
+        }
+
+        self.evict_over_limit();
+        imported
+    }
+
+    /// Removes entries older than `max_age_seconds`, returning how many were
+    /// removed. Nothing is printed, so the TUI can call it too.
+    pub fn cleanup_old_entries(&mut self, max_age_seconds: u64) -> usize {
         let current_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
 
-        let initial_count = self.entries.len();
+        let initial_count = self.files.len();
 
-        self.entries
-            .retain(|_, entry| current_time - entry.timestamp < max_age_seconds);
+        self.files
+            .retain(|_, entry| current_time.saturating_sub(entry.timestamp) < max_age_seconds);
 
-        let removed_count = initial_count - self.entries.len();
-        if removed_count > 0 {
-            println!("Cleaned up {} old cache entries", removed_count);
-        }
+        let removed_count = initial_count - self.files.len();
 
-        while self.entries.len() > self.max_entries {
+        let initial_sub_categories = self.sub_categories.len();
+        self.sub_categories
+            .retain(|_, entry| current_time.saturating_sub(entry.timestamp) < max_age_seconds);
+        let removed_sub_categories = initial_sub_categories - self.sub_categories.len();
+
+        self.evict_over_limit();
+        removed_count + removed_sub_categories
+    }
+
+    /// Drops the oldest entries until both maps fit `max_entries`.
+    fn evict_over_limit(&mut self) {
+        let mut evicted = 0;
+        while self.files.len() > self.max_entries {
             self.evict_oldest();
+            evicted += 1;
+        }
+        while self.sub_categories.len() > self.max_entries {
+            self.evict_oldest_sub_category();
+            evicted += 1;
+        }
+        if evicted > 0 {
+            debug!(
+                "Evicted {} oldest cache entries to stay within the limit",
+                evicted
+            );
+        }
+    }
+
+    fn evict_oldest_sub_category(&mut self) {
+        if let Some(oldest_key) = self
+            .sub_categories
+            .iter()
+            .min_by_key(|(_, entry)| entry.timestamp)
+            .map(|(k, _)| k.clone())
+        {
+            self.sub_categories.remove(&oldest_key);
         }
     }
 
     fn evict_oldest(&mut self) {
         if let Some(oldest_key) = self
-            .entries
+            .files
             .iter()
             .min_by_key(|(_, entry)| entry.timestamp)
             .map(|(k, _)| k.clone())
         {
-            self.entries.remove(&oldest_key);
-            println!("Evicted oldest cache entry to maintain limit");
+            self.files.remove(&oldest_key);
         }
     }
 
     pub fn len(&self) -> usize {
-        self.entries.len()
+        self.files.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+        self.files.is_empty()
+    }
+
+    /// Number of cached deep-inspection sub-categories.
+    pub fn sub_category_len(&self) -> usize {
+        self.sub_categories.len()
     }
 }
@@ -1,14 +1,18 @@
+pub mod atomic_file;
 pub mod cache;
 pub mod undo_log;
+pub mod usage_log;
 
-pub use cache::Cache;
+pub use atomic_file::SessionLock;
+pub use cache::{Cache, CacheExport, CacheLookup};
 pub use undo_log::UndoLog;
+pub use usage_log::{USAGE_LOG_RETENTION_SECONDS, UsageLog};
 
 #[cfg(test)]
 mod tests {
-    use crate::models::{FileMoveRecord, MoveStatus};
-    use crate::storage::{Cache, UndoLog};
-    use std::path::PathBuf;
+    use crate::models::{FileMoveRecord, MoveStatus, RequestKind, SessionMode, UsageRecord};
+    use crate::storage::{Cache, UndoLog, UsageLog};
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn test_cache_new() {
@@ -97,4 +101,190 @@ mod tests {
         let usage = log.get_directory_usage(PathBuf::from("/").as_path());
         assert!(usage.is_empty());
     }
+
+    fn usage_record(timestamp: u64, folder: &str, tokens: u64, cache_hit: bool) -> UsageRecord {
+        let mut record = UsageRecord::new(
+            RequestKind::Categorization,
+            "model",
+            Some(folder.to_string()),
+        );
+        record.timestamp = timestamp;
+        record.total_tokens = tokens;
+        record.cache_hit = cache_hit;
+        record
+    }
+
+    #[test]
+    fn test_usage_log_groups_by_day_and_folder() {
+        let mut log = UsageLog::new();
+        log.extend(vec![
+            usage_record(0, "/a", 100, false),
+            usage_record(60, "/b", 50, false),
+            usage_record(86_400, "/a", 0, true),
+        ]);
+
+        let by_day = log.by_day(0);
+        assert_eq!(by_day.len(), 2);
+        assert_eq!(by_day[0].0, "1970-01-01");
+        assert_eq!(by_day[0].1.requests, 2);
+        assert_eq!(by_day[0].1.total_tokens, 150);
+        assert_eq!(by_day[1].1.cache_hits, 1);
+
+        let by_folder = log.by_folder(0);
+        assert_eq!(by_folder[0].0, "/a");
+        assert_eq!(by_folder[0].1.requests, 1);
+        assert_eq!(by_folder[0].1.cache_hits, 1);
+    }
+
+    #[test]
+    fn test_usage_log_totals_since_skips_older_entries() {
+        let mut log = UsageLog::new();
+        log.extend(vec![
+            usage_record(10, "/a", 100, false),
+            usage_record(20, "/a", 40, false),
+        ]);
+
+        let totals = log.totals_since(15);
+        assert_eq!(totals.requests, 1);
+        assert_eq!(totals.total_tokens, 40);
+    }
+
+    #[test]
+    fn test_usage_log_persistence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("usage_log.json");
+
+        let mut log = UsageLog::new();
+        log.extend(vec![usage_record(10, "/a", 100, false)]);
+        log.save(&path).unwrap();
+
+        let loaded = UsageLog::load_or_create(&path, true).unwrap();
+        assert_eq!(loaded.entries(), log.entries());
+    }
+
+    #[test]
+    fn test_unreadable_usage_log_is_an_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // A directory in place of the file can't be read as one
+        let path = dir.path().join("usage_log.json");
+        std::fs::create_dir(&path).unwrap();
+
+        assert!(UsageLog::load_or_create(&path, true).is_err());
+        assert!(path.is_dir());
+    }
+
+    #[test]
+    fn test_undo_log_restored_from_backup_after_torn_write() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("undo_log.json");
+
+        let mut log = UndoLog::new();
+        log.record_move(PathBuf::from("/a.txt"), PathBuf::from("/Docs/a.txt"));
+        log.save(&path).unwrap();
+        log.record_move(PathBuf::from("/b.txt"), PathBuf::from("/Docs/b.txt"));
+        log.save(&path).unwrap();
+
+        // Simulate a write cut off halfway through
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &content[..content.len() / 2]).unwrap();
+
+        let loaded = UndoLog::load_or_create(&path, true);
+        assert_eq!(loaded.get_completed_count(), 1);
+        assert!(crate::storage::atomic_file::corrupt_path(&path).exists());
+    }
+
+    #[test]
+    fn test_undo_log_sessions_tag_and_summarize_moves() {
+        let mut log = UndoLog::new();
+        log.record_move(
+            PathBuf::from("/dl/old.txt"),
+            PathBuf::from("/dl/Docs/old.txt"),
+        );
+
+        let id = log.begin_session(Path::new("/dl"), SessionMode::Offline);
+        log.record_move(PathBuf::from("/dl/a.pdf"), PathBuf::from("/dl/Docs/a.pdf"));
+        log.record_move(
+            PathBuf::from("/dl/b.pdf"),
+            PathBuf::from("/dl/Docs/Work/b.pdf"),
+        );
+        log.record_move(
+            PathBuf::from("/dl/c.png"),
+            PathBuf::from("/dl/Images/c.png"),
+        );
+        log.record_failed_move(
+            PathBuf::from("/dl/d.png"),
+            PathBuf::from("/dl/Images/d.png"),
+        );
+        log.finish_session();
+        log.record_move(PathBuf::from("/dl/e.txt"), PathBuf::from("/dl/Docs/e.txt"));
+
+        assert_eq!(log.session_records(id).len(), 4);
+        let session = log.session(id).unwrap();
+        assert_eq!(session.mode, SessionMode::Offline);
+        assert_eq!(session.target_path, PathBuf::from("/dl"));
+        assert_eq!(session.summary, "3 moved (Docs 2, Images 1); 1 failed");
+        assert_eq!(log.last_session_id(), Some(id));
+    }
+
+    #[test]
+    fn test_undo_log_drops_empty_sessions_and_skips_undone_ones() {
+        let mut log = UndoLog::new();
+
+        let first = log.begin_session(Path::new("/dl"), SessionMode::Online);
+        log.record_move(PathBuf::from("/dl/a.pdf"), PathBuf::from("/dl/Docs/a.pdf"));
+        log.finish_session();
+
+        let empty = log.begin_session(Path::new("/dl"), SessionMode::Online);
+        log.finish_session();
+        assert!(log.session(empty).is_none());
+
+        let second = log.begin_session(Path::new("/dl"), SessionMode::Online);
+        assert!(second > first);
+        log.record_move(PathBuf::from("/dl/b.pdf"), PathBuf::from("/dl/Docs/b.pdf"));
+        log.finish_session();
+        assert_eq!(log.last_session_id(), Some(second));
+
+        log.mark_as_undone(Path::new("/dl/Docs/b.pdf"));
+        assert_eq!(log.last_session_id(), Some(first));
+    }
+
+    #[test]
+    fn test_undo_log_marks_the_exact_record_undone() {
+        let mut log = UndoLog::new();
+        let mut sessions = Vec::new();
+        for _ in 0..2 {
+            sessions.push(log.begin_session(Path::new("/dl"), SessionMode::Offline));
+            log.record_move(PathBuf::from("/dl/a.pdf"), PathBuf::from("/dl/Docs/a.pdf"));
+            log.finish_session();
+        }
+
+        let newer = log.session_records(sessions[1])[0].clone();
+        log.mark_record_as_undone(&newer, None);
+
+        assert_eq!(
+            log.session_records(sessions[0])[0].status,
+            MoveStatus::Completed
+        );
+        assert_eq!(
+            log.session_records(sessions[1])[0].status,
+            MoveStatus::Undone
+        );
+        assert_eq!(log.last_session_id(), Some(sessions[0]));
+    }
+
+    #[test]
+    fn test_undo_log_without_sessions_still_loads() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("undo_log.json");
+        std::fs::write(
+            &path,
+            r#"{"entries":[{"source_path":"/a.txt","destination_path":"/Docs/a.txt","timestamp":1,"status":"Completed"}],"max_entries":1000}"#,
+        )
+        .unwrap();
+
+        let log = UndoLog::load_or_create(&path, true);
+        assert_eq!(log.get_completed_count(), 1);
+        assert!(log.sessions().is_empty());
+        assert_eq!(log.get_completed_moves()[0].session, None);
+    }
 }
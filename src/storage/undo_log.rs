@@ -1,8 +1,8 @@
 use crate::error::Result;
-use crate::models::{FileMoveRecord, MoveStatus};
+use crate::models::{FileMoveRecord, FileStamp, MoveStatus, SessionMode, UndoSession};
+use crate::storage::atomic_file::{Loaded, corrupt_path, read_with_backup, write_atomic};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -10,6 +10,11 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub struct UndoLog {
     entries: Vec<FileMoveRecord>,
     max_entries: usize,
+    #[serde(default)]
+    sessions: Vec<UndoSession>,
+    /// Session that new records are tagged with, set by `begin_session`
+    #[serde(skip)]
+    current_session: Option<u64>,
 }
 
 impl Default for UndoLog {
@@ -27,66 +32,139 @@ impl UndoLog {
         Self {
             entries: Vec::new(),
             max_entries,
+            sessions: Vec::new(),
+            current_session: None,
         }
     }
 
     pub fn load_or_create(undo_log_path: &Path, silent: bool) -> Self {
-        if undo_log_path.exists() {
-            match fs::read_to_string(undo_log_path) {
-                Ok(content) => match serde_json::from_str::<UndoLog>(&content) {
-                    Ok(log) => {
-                        if !silent {
-                            println!("Loaded undo log with {} entries", log.get_completed_count());
-                        }
-                        log
-                    }
-                    Err(_) => {
-                        if !silent {
-                            println!("Undo log corrupted, creating new log");
-                        }
-                        Self::new()
-                    }
-                },
-                Err(_) => {
-                    if !silent {
-                        println!("Failed to read undo log, creating new log");
-                    }
-                    Self::new()
+        let message = match read_with_backup(undo_log_path, |c| serde_json::from_str::<UndoLog>(c))
+        {
+            Ok(Loaded::Missing) => "Creating new undo log file".to_string(),
+            Ok(Loaded::Current(log)) => {
+                if !silent {
+                    println!("Loaded undo log with {} entries", log.get_completed_count());
                 }
+                return log;
             }
-        } else {
-            if !silent {
-                println!("Creating new undo log file");
+            Ok(Loaded::Restored(log)) => {
+                // Reported on stderr: moves made since the backup can't be undone
+                if !silent {
+                    eprintln!(
+                        "Undo log corrupted, restored {} entries from backup",
+                        log.get_completed_count()
+                    );
+                }
+                return log;
             }
-            Self::new()
+            Ok(Loaded::Corrupted(_)) => {
+                if !silent {
+                    eprintln!(
+                        "Undo log corrupted, kept it at {} and created a new log",
+                        corrupt_path(undo_log_path).display()
+                    );
+                }
+                return Self::new();
+            }
+            Err(e) => format!("Failed to read undo log ({}), creating new log", e),
+        };
+
+        if !silent {
+            println!("{}", message);
         }
+        Self::new()
     }
 
     pub fn save(&self, undo_log_path: &Path) -> Result<()> {
-        if let Some(parent) = undo_log_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(undo_log_path, content)?;
+        write_atomic(undo_log_path, content.as_bytes())?;
         Ok(())
     }
 
-    pub fn record_move(&mut self, source_path: PathBuf, destination_path: PathBuf) {
-        let record = FileMoveRecord::new(source_path, destination_path, MoveStatus::Completed);
-        self.entries.push(record);
+    /// Starts a new session; moves recorded until `finish_session` belong to it.
+    pub fn begin_session(&mut self, target_path: &Path, mode: SessionMode) -> u64 {
+        let id = self.sessions.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        self.sessions.push(UndoSession {
+            id,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            target_path: target_path.to_path_buf(),
+            mode,
+            summary: String::new(),
+        });
+        self.current_session = Some(id);
+        id
+    }
 
-        if self.entries.len() > self.max_entries {
-            self.evict_oldest();
+    /// Ends the current session and stores its summary. A session that
+    /// recorded nothing is dropped.
+    pub fn finish_session(&mut self) {
+        let Some(id) = self.current_session.take() else {
+            return;
+        };
+
+        let records = self.session_records(id);
+        if records.is_empty() {
+            self.sessions.retain(|s| s.id != id);
+            return;
         }
+
+        let target_path = self
+            .session(id)
+            .map(|s| s.target_path.clone())
+            .unwrap_or_default();
+        let summary = summarize(&records, &target_path);
+        if let Some(session) = self.sessions.iter_mut().find(|s| s.id == id) {
+            session.summary = summary;
+        }
+    }
+
+    pub fn sessions(&self) -> &[UndoSession] {
+        &self.sessions
+    }
+
+    pub fn session(&self, id: u64) -> Option<&UndoSession> {
+        self.sessions.iter().find(|s| s.id == id)
+    }
+
+    pub fn session_records(&self, id: u64) -> Vec<&FileMoveRecord> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.session == Some(id))
+            .collect()
+    }
+
+    /// The newest session that still has moves to undo.
+    pub fn last_session_id(&self) -> Option<u64> {
+        self.sessions
+            .iter()
+            .filter(|s| {
+                self.entries
+                    .iter()
+                    .any(|e| e.session == Some(s.id) && e.status == MoveStatus::Completed)
+            })
+            .max_by_key(|s| (s.timestamp, s.id))
+            .map(|s| s.id)
+    }
+
+    pub fn record_move(&mut self, source_path: PathBuf, destination_path: PathBuf) {
+        self.push_record(source_path, destination_path, MoveStatus::Completed);
     }
 
     pub fn record_failed_move(&mut self, source_path: PathBuf, destination_path: PathBuf) {
-        let record = FileMoveRecord::new(source_path, destination_path, MoveStatus::Failed);
+        self.push_record(source_path, destination_path, MoveStatus::Failed);
+    }
+
+    fn push_record(&mut self, source_path: PathBuf, destination_path: PathBuf, status: MoveStatus) {
+        let mut record = FileMoveRecord::new(source_path, destination_path, status);
+        record.session = self.current_session;
         self.entries.push(record);
 
         if self.entries.len() > self.max_entries {
             self.evict_oldest();
+            self.prune_sessions();
         }
     }
 
@@ -106,6 +184,54 @@ impl UndoLog {
         }
     }
 
+    /// Marks `record`, a completed move taken from this log, as undone,
+    /// remembering the restored file's `stamp` so it can be redone later.
+    /// Unlike `mark_as_undone`, another session's move to the same
+    /// destination is left alone.
+    pub fn mark_record_as_undone(&mut self, record: &FileMoveRecord, stamp: Option<FileStamp>) {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|e| e.status == MoveStatus::Completed && e.is_same_move(record))
+        {
+            entry.status = MoveStatus::Undone;
+            entry.undone_file = stamp;
+        }
+    }
+
+    pub fn get_undone_moves(&self) -> Vec<&FileMoveRecord> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status == MoveStatus::Undone)
+            .collect()
+    }
+
+    /// Marks `record`, an undone move taken from this log, as redone and
+    /// records the move again as a fresh `Completed` entry in its session.
+    pub fn mark_record_as_redone(&mut self, record: &FileMoveRecord) {
+        let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|e| e.status == MoveStatus::Undone && e.is_same_move(record))
+        else {
+            return;
+        };
+        entry.status = MoveStatus::Redone;
+
+        let mut record = FileMoveRecord::new(
+            entry.source_path.clone(),
+            entry.destination_path.clone(),
+            MoveStatus::Completed,
+        );
+        record.session = entry.session;
+        self.entries.push(record);
+
+        if self.entries.len() > self.max_entries {
+            self.evict_oldest();
+            self.prune_sessions();
+        }
+    }
+
     pub fn get_completed_count(&self) -> usize {
         self.entries
             .iter()
@@ -138,6 +264,15 @@ impl UndoLog {
         if self.entries.len() > self.max_entries {
             self.compact_log();
         }
+        self.prune_sessions();
+    }
+
+    /// Drops sessions whose records have all been removed.
+    fn prune_sessions(&mut self) {
+        let entries = &self.entries;
+        let current = self.current_session;
+        self.sessions
+            .retain(|s| Some(s.id) == current || entries.iter().any(|e| e.session == Some(s.id)));
     }
 
     fn evict_oldest(&mut self) {
@@ -145,7 +280,7 @@ impl UndoLog {
             .entries
             .iter()
             .enumerate()
-            .filter(|(_, entry)| entry.status == MoveStatus::Undone)
+            .filter(|(_, entry)| matches!(entry.status, MoveStatus::Undone | MoveStatus::Redone))
             .min_by_key(|(_, entry)| entry.timestamp)
             .map(|(i, _)| i)
         {
@@ -170,6 +305,7 @@ impl UndoLog {
         while self.entries.len() > self.max_entries {
             self.evict_oldest();
         }
+        self.prune_sessions();
     }
 
     pub fn get_directory_usage(&self, base_path: &Path) -> HashMap<String, usize> {
@@ -188,3 +324,40 @@ impl UndoLog {
         usage
     }
 }
+
+/// Describes a session's records, e.g. "3 moved (Documents 2, Images 1); 1 failed".
+fn summarize(records: &[&FileMoveRecord], target_path: &Path) -> String {
+    let mut folders: BTreeMap<String, usize> = BTreeMap::new();
+    let mut failed = 0;
+    for record in records {
+        if record.status == MoveStatus::Failed {
+            failed += 1;
+            continue;
+        }
+        let folder = record
+            .destination_path
+            .strip_prefix(target_path)
+            .ok()
+            .and_then(|rel| rel.components().next())
+            .filter(|_| record.destination_path.parent() != Some(target_path))
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .unwrap_or_else(|| "other".to_string());
+        *folders.entry(folder).or_default() += 1;
+    }
+
+    let moved: usize = folders.values().sum();
+    let mut summary = format!("{} moved", moved);
+    if !folders.is_empty() {
+        let mut folders: Vec<_> = folders.into_iter().collect();
+        folders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let parts: Vec<String> = folders
+            .iter()
+            .map(|(folder, count)| format!("{} {}", folder, count))
+            .collect();
+        summary.push_str(&format!(" ({})", parts.join(", ")));
+    }
+    if failed > 0 {
+        summary.push_str(&format!("; {} failed", failed));
+    }
+    summary
+}
@@ -0,0 +1,113 @@
+use crate::error::{AppError, Result};
+use crate::files::context::format_date;
+use crate::models::{UsageRecord, UsageTotals};
+use crate::storage::atomic_file::{Loaded, read_with_backup, write_atomic};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// How long usage records are kept before `cleanup_old_entries` drops them.
+pub const USAGE_LOG_RETENTION_SECONDS: u64 = 90 * SECONDS_PER_DAY;
+
+/// Persistent record of Gemini requests, kept in the data directory.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UsageLog {
+    entries: Vec<UsageRecord>,
+}
+
+impl UsageLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the usage log, starting a new one if it is missing or
+    /// corrupted. Fails if the file can't be read, so that saving does not
+    /// replace a log that is still there.
+    pub fn load_or_create(usage_log_path: &Path, silent: bool) -> Result<Self> {
+        match read_with_backup(usage_log_path, |c| serde_json::from_str::<UsageLog>(c)) {
+            Ok(Loaded::Current(log) | Loaded::Restored(log)) => Ok(log),
+            Ok(Loaded::Missing) => Ok(Self::new()),
+            Ok(Loaded::Corrupted(_)) => {
+                if !silent {
+                    println!("Usage log corrupted, creating new log");
+                }
+                Ok(Self::new())
+            }
+            Err(e) => Err(AppError::UsageLogError(format!(
+                "Failed to read {}: {}",
+                usage_log_path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn save(&self, usage_log_path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        write_atomic(usage_log_path, content.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn extend(&mut self, records: Vec<UsageRecord>) {
+        self.entries.extend(records);
+    }
+
+    pub fn entries(&self) -> &[UsageRecord] {
+        &self.entries
+    }
+
+    pub fn cleanup_old_entries(&mut self, max_age_seconds: u64) {
+        let current_time = current_time();
+        self.entries
+            .retain(|entry| current_time.saturating_sub(entry.timestamp) < max_age_seconds);
+    }
+
+    /// Totals for the current UTC day, used to enforce daily limits.
+    pub fn today(&self) -> UsageTotals {
+        let now = current_time();
+        self.totals_since(now - now % SECONDS_PER_DAY)
+    }
+
+    pub fn totals_since(&self, since: u64) -> UsageTotals {
+        let mut totals = UsageTotals::default();
+        for entry in self.entries.iter().filter(|e| e.timestamp >= since) {
+            totals.add(entry);
+        }
+        totals
+    }
+
+    /// Totals per UTC day (`YYYY-MM-DD`), oldest first.
+    pub fn by_day(&self, since: u64) -> Vec<(String, UsageTotals)> {
+        self.group_since(since, |entry| format_date(entry.timestamp))
+    }
+
+    /// Totals per organized folder, in path order.
+    pub fn by_folder(&self, since: u64) -> Vec<(String, UsageTotals)> {
+        self.group_since(since, |entry| {
+            entry
+                .folder
+                .clone()
+                .unwrap_or_else(|| "(unknown)".to_string())
+        })
+    }
+
+    fn group_since<F>(&self, since: u64, key: F) -> Vec<(String, UsageTotals)>
+    where
+        F: Fn(&UsageRecord) -> String,
+    {
+        let mut groups: BTreeMap<String, UsageTotals> = BTreeMap::new();
+        for entry in self.entries.iter().filter(|e| e.timestamp >= since) {
+            groups.entry(key(entry)).or_default().add(entry);
+        }
+        groups.into_iter().collect()
+    }
+}
+
+fn current_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -107,10 +107,37 @@ impl App {
     }
 
     pub fn set_plan(&mut self, plan: OrganizationPlan) {
+        let mut notes = Vec::new();
+        if !plan.uncategorized.is_empty() {
+            notes.push(format!(
+                "{} file(s) left uncategorized",
+                plan.uncategorized.len()
+            ));
+        }
+        if !plan.needs_review.is_empty() {
+            notes.push(format!(
+                "{} file(s) need review, press 'a' to include them",
+                plan.needs_review.len()
+            ));
+        }
+        self.status_message = if notes.is_empty() {
+            "Review the organization plan".to_string()
+        } else {
+            format!("Review the organization plan ({})", notes.join(", "))
+        };
         self.plan = Some(plan);
         self.state = AppState::PlanReview;
         self.tab = Tab::Plan;
-        self.status_message = "Review the organization plan".to_string();
+    }
+
+    /// Mentions files deep inspection couldn't sub-categorize in the status bar.
+    pub fn note_sub_category_failures(&mut self, count: usize) {
+        if count > 0 {
+            self.status_message.push_str(&format!(
+                " - {} file(s) could not be sub-categorized",
+                count
+            ));
+        }
     }
 
     pub fn set_error(&mut self, error: String) {
@@ -163,7 +190,7 @@ impl App {
 
     pub fn next_plan_item(&mut self) {
         if let Some(ref plan) = self.plan
-            && self.plan_list_state < plan.files.len().saturating_sub(1)
+            && self.plan_list_state < (plan.files.len() + plan.needs_review.len()).saturating_sub(1)
         {
             self.plan_list_state += 1;
         }
@@ -200,9 +227,24 @@ impl App {
         })
     }
 
+    /// Moves the files held for review into the plan so they are moved on confirm.
+    pub fn approve_review(&mut self) {
+        if let Some(ref mut plan) = self.plan
+            && !plan.needs_review.is_empty()
+        {
+            let count = plan.needs_review.len();
+            plan.approve_review();
+            self.status_message = format!("Included {} low-confidence file(s) in the plan", count);
+        }
+    }
+
+    /// Selected item across the plan followed by the files held for review.
     pub fn get_selected_plan_item(&self) -> Option<&FileCategory> {
-        self.plan
-            .as_ref()
-            .and_then(|plan| plan.files.get(self.plan_list_state))
+        self.plan.as_ref().and_then(|plan| {
+            plan.files
+                .iter()
+                .chain(plan.needs_review.iter())
+                .nth(self.plan_list_state)
+        })
     }
 }
@@ -1,10 +1,10 @@
 use crate::cli::path_utils::validate_and_normalize_path;
 use crate::error::Result;
-use crate::files::{execute_move_silent, is_text_file, read_file_sample};
+use crate::files::{SubFolderIndex, execute_move_silent, read_inspection_targets, sanitize_plan};
 use crate::gemini::GeminiClient;
-use crate::models::OrganizationPlan;
+use crate::models::{OrganizationPlan, SessionMode};
 use crate::settings::Config;
-use crate::storage::{Cache, UndoLog};
+use crate::storage::{Cache, SessionLock, USAGE_LOG_RETENTION_SECONDS, UndoLog, UsageLog};
 use crate::tui::app::{App, AppState, Tab};
 use crate::tui::ui::draw;
 use crossterm::{
@@ -12,11 +12,11 @@ use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use futures::future::join_all;
+use log::warn;
 use ratatui::{Terminal, backend::CrosstermBackend};
+use std::collections::HashMap;
 use std::io;
 use std::path::PathBuf;
-use std::sync::Arc;
 use std::time::Duration;
 
 const CACHE_RETENTION_SECONDS: u64 = 7 * 24 * 60 * 60;
@@ -33,6 +33,25 @@ pub async fn run_app(
         Some(p) => validate_and_normalize_path(&p).await?,
         None => validate_and_normalize_path(&config.download_folder).await?,
     };
+    let config = config.for_folder(&target_path);
+
+    // Initialize cache and undo log before taking over the screen, so a
+    // wait for another run's session locks is reported. The locks are held
+    // until the files are saved on exit.
+    let cache_path = Config::get_cache_path()?;
+    let _cache_lock = SessionLock::acquire(&cache_path, false)?;
+    let mut cache = Cache::load_or_create(&cache_path, true);
+    cache.cleanup_old_entries(CACHE_RETENTION_SECONDS);
+
+    let undo_log_path = Config::get_undo_log_path()?;
+    let _undo_log_lock = SessionLock::acquire(&undo_log_path, false)?;
+    let mut undo_log = UndoLog::load_or_create(&undo_log_path, true);
+    undo_log.cleanup_old_entries(UNDO_LOG_RETENTION_SECONDS);
+
+    let usage_log_path = Config::get_usage_log_path()?;
+    let _usage_log_lock = SessionLock::acquire(&usage_log_path, false)?;
+    let mut usage_log = UsageLog::load_or_create(&usage_log_path, true)?;
+    usage_log.cleanup_old_entries(USAGE_LOG_RETENTION_SECONDS);
 
     // Setup terminal
     enable_raw_mode()?;
@@ -47,18 +66,16 @@ pub async fn run_app(
     // Scan files initially
     app.scan_files();
 
-    // Initialize cache and undo log
-    let data_dir = Config::get_data_dir()?;
-    let cache_path = data_dir.join(".noentropy_cache.json");
-    let mut cache = Cache::load_or_create(&cache_path, true);
-    cache.cleanup_old_entries(CACHE_RETENTION_SECONDS);
-
-    let undo_log_path = Config::get_undo_log_path()?;
-    let mut undo_log = UndoLog::load_or_create(&undo_log_path, true);
-    undo_log.cleanup_old_entries(UNDO_LOG_RETENTION_SECONDS);
-
     // Main event loop
-    let result = run_event_loop(&mut terminal, &mut app, &config, &mut cache, &mut undo_log).await;
+    let result = run_event_loop(
+        &mut terminal,
+        &mut app,
+        &config,
+        &mut cache,
+        &mut undo_log,
+        &mut usage_log,
+    )
+    .await;
 
     // Restore terminal
     disable_raw_mode()?;
@@ -76,6 +93,9 @@ pub async fn run_app(
     if let Err(e) = undo_log.save(&undo_log_path) {
         eprintln!("Warning: Failed to save undo log: {}", e);
     }
+    if let Err(e) = usage_log.save(&usage_log_path) {
+        eprintln!("Warning: Failed to save usage log: {}", e);
+    }
 
     result
 }
@@ -86,6 +106,7 @@ async fn run_event_loop(
     config: &Config,
     cache: &mut Cache,
     undo_log: &mut UndoLog,
+    usage_log: &mut UsageLog,
 ) -> Result<()> {
     loop {
         terminal.draw(|frame| draw(frame, app))?;
@@ -124,9 +145,11 @@ async fn run_event_loop(
                         app.start_fetching();
                         terminal.draw(|frame| draw(frame, app))?;
 
-                        match fetch_organization_plan(app, config, cache).await {
-                            Ok(plan) => {
+                        match fetch_organization_plan(app, config, cache, undo_log, usage_log).await
+                        {
+                            Ok((plan, failed)) => {
                                 app.set_plan(plan);
+                                app.note_sub_category_failures(failed);
                             }
                             Err(e) => {
                                 app.set_error(e.to_string());
@@ -144,6 +167,11 @@ async fn run_event_loop(
                         app.finish();
                     }
                 }
+                KeyCode::Char('a') => {
+                    if matches!(app.state, AppState::PlanReview) {
+                        app.approve_review();
+                    }
+                }
                 KeyCode::Char('r') => {
                     if matches!(app.state, AppState::Done | AppState::Error(_)) {
                         // Restart
@@ -176,68 +204,75 @@ async fn fetch_organization_plan(
     app: &App,
     config: &Config,
     cache: &mut Cache,
-) -> Result<OrganizationPlan> {
+    undo_log: &UndoLog,
+    usage_log: &mut UsageLog,
+) -> Result<(OrganizationPlan, usize)> {
     let batch = app.batch.as_ref().ok_or("No files to organize")?;
 
     if app.offline {
         // Use offline categorization
-        use crate::files::categorize_files_offline;
-        let result = categorize_files_offline(batch.filenames.clone());
-        return Ok(result.plan);
+        use crate::files::{apply_content_sub_categories, categorize_files_offline};
+        let mut result = categorize_files_offline(batch.filenames.clone());
+        apply_content_sub_categories(&mut result.plan, &app.target_path, config.deep_inspect);
+        return Ok((result.plan, 0));
     }
 
     // Online AI categorization
-    let client = GeminiClient::new_silent(&config.api_key, &config.categories);
+    let mut client = GeminiClient::from_config(config)?;
+    client.set_silent(true);
+    client.usage().set_folder(&app.target_path);
+    client
+        .usage()
+        .set_limits(config.usage_limits.clone(), usage_log.today());
 
     // Check connectivity first
     client.check_connectivity().await?;
 
     // Get initial plan
-    let mut plan = client
+    let result = client
         .organize_files_in_batches(batch.filenames.clone(), Some(cache), Some(&app.target_path))
-        .await?;
+        .await;
+    usage_log.extend(client.usage().take_records());
+    let mut plan = result?;
 
     // Deep inspection only if enabled in config (default: disabled)
+    let mut failed = 0;
     if config.deep_inspect {
-        let client_arc = Arc::new(client);
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(5));
-
-        let tasks: Vec<_> = plan
-            .files
-            .iter_mut()
-            .zip(batch.paths.iter())
-            .map(|(file_category, path)| {
-                let client = Arc::clone(&client_arc);
-                let filename = file_category.filename.clone();
-                let category = file_category.category.clone();
-                let path = path.clone();
-                let semaphore = Arc::clone(&semaphore);
-
-                async move {
-                    if is_text_file(&path) {
-                        let _permit = semaphore.acquire().await.unwrap();
-                        if let Some(content) = read_file_sample(&path, 5000) {
-                            client
-                                .get_ai_sub_category(&filename, &category, &content)
-                                .await
-                        } else {
-                            String::new()
-                        }
-                    } else {
-                        String::new()
-                    }
-                }
-            })
+        let files = plan.files.clone();
+        let paths: HashMap<String, PathBuf> = batch
+            .filenames
+            .iter()
+            .cloned()
+            .zip(batch.paths.iter().cloned())
             .collect();
+        let multimodal = config.multimodal.clone();
+        let budget = client.media_budget().clone();
+        let targets = tokio::task::spawn_blocking(move || {
+            read_inspection_targets(&files, &paths, 5000, Some(&multimodal), &budget)
+        })
+        .await
+        .unwrap_or_default();
+
+        let existing = SubFolderIndex::scan(
+            &app.target_path,
+            &plan.categories(),
+            &undo_log.get_directory_usage(&app.target_path),
+        );
+        let results = client
+            .inspect_sub_categories_with_cache(&targets, &existing, Some(cache))
+            .await;
+        plan.apply_sub_categories(&results.sub_categories);
+        existing.unify_sub_categories(&mut plan);
+        failed = results.failures.len();
+        usage_log.extend(client.usage().take_records());
+    }
 
-        let sub_categories: Vec<String> = join_all(tasks).await;
-
-        for (file_category, sub_category) in plan.files.iter_mut().zip(sub_categories) {
-            file_category.sub_category = sub_category;
-        }
+    for fix in sanitize_plan(&mut plan) {
+        warn!("{}", fix);
     }
+    plan.hold_low_confidence(config.min_confidence);
 
-    Ok(plan)
+    Ok((plan, failed))
 }
 
 fn execute_organization(app: &mut App, undo_log: &mut UndoLog) {
@@ -255,7 +290,15 @@ fn execute_organization(app: &mut App, undo_log: &mut UndoLog) {
     }
 
     // Execute the move using silent version (no console output)
-    match execute_move_silent(&app.target_path, plan.clone(), Some(undo_log)) {
+    let mode = if app.offline {
+        SessionMode::Offline
+    } else {
+        SessionMode::Online
+    };
+    undo_log.begin_session(&app.target_path, mode);
+    let result = execute_move_silent(&app.target_path, plan.clone(), Some(undo_log));
+    undo_log.finish_session();
+    match result {
         Ok(summary) => {
             app.moved_count = summary.moved_count();
             app.error_count = summary.error_count();
@@ -285,12 +328,19 @@ async fn toggle_online_mode(app: &mut App, config: &Config) {
         // Currently offline, try to switch to online
         app.status_message = "Checking online availability...".to_string();
 
-        if config.api_key.is_empty() {
-            app.status_message = "Cannot go online: API key not configured".to_string();
-            return;
-        }
+        let mut client = match GeminiClient::from_config(config) {
+            Ok(client) if client.has_api_key() => client,
+            Ok(_) => {
+                app.status_message = "Cannot go online: API key not configured".to_string();
+                return;
+            }
+            Err(e) => {
+                app.status_message = format!("Cannot go online: {}", e);
+                return;
+            }
+        };
 
-        let client = GeminiClient::new_silent(&config.api_key, &config.categories);
+        client.set_silent(true);
         match client.check_connectivity().await {
             Ok(()) => {
                 app.online_requested = true;
@@ -152,15 +152,27 @@ fn draw_plan_tab(frame: &mut Frame, app: &App, area: Rect) {
 
     // Plan list
     let items: Vec<ListItem> = match &app.plan {
-        Some(plan) => plan
-            .files
-            .iter()
-            .enumerate()
-            .map(|(i, item)| {
+        Some(plan) => {
+            let mut items = Vec::new();
+            for (i, item) in plan
+                .files
+                .iter()
+                .chain(plan.needs_review.iter())
+                .enumerate()
+            {
+                let held = i >= plan.files.len();
+                if held && i == plan.files.len() {
+                    items.push(
+                        ListItem::new(format!("-- Needs Review ({}) --", plan.needs_review.len()))
+                            .style(Style::default().fg(Color::Magenta)),
+                    );
+                }
                 let style = if i == app.plan_list_state {
                     Style::default()
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::BOLD)
+                } else if held {
+                    Style::default().fg(Color::DarkGray)
                 } else {
                     Style::default().fg(Color::White)
                 };
@@ -170,9 +182,13 @@ fn draw_plan_tab(frame: &mut Frame, app: &App, area: Rect) {
                 } else {
                     format!("{}/{}", item.category, item.sub_category)
                 };
-                ListItem::new(format!("{}{} -> {}", prefix, item.filename, target)).style(style)
-            })
-            .collect(),
+                items.push(
+                    ListItem::new(format!("{}{} -> {}", prefix, item.filename, target))
+                        .style(style),
+                );
+            }
+            items
+        }
         None => vec![
             ListItem::new("No plan available. Press 'o' to organize.")
                 .style(Style::default().fg(Color::DarkGray)),
@@ -215,6 +231,22 @@ fn draw_plan_tab(frame: &mut Frame, app: &App, area: Rect) {
                     Span::styled("Target: ", Style::default().fg(Color::Green)),
                     Span::raw(target),
                 ]),
+                Line::from(vec![
+                    Span::styled("Confidence: ", Style::default().fg(Color::Cyan)),
+                    Span::raw(
+                        item.confidence
+                            .map(|c| format!("{:.0}%", c * 100.0))
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                ]),
+                Line::from(vec![
+                    Span::styled("Reason: ", Style::default().fg(Color::Cyan)),
+                    Span::raw(if item.reasoning.is_empty() {
+                        "-"
+                    } else {
+                        &item.reasoning
+                    }),
+                ]),
             ]
         }
         None => vec![Line::from("No item selected")],
@@ -349,7 +381,7 @@ fn draw_help_bar(frame: &mut Frame, app: &App, area: Rect) {
         }
         AppState::Fetching => "Fetching... Please wait",
         AppState::PlanReview => {
-            "[c] Confirm  [t] Toggle mode  [Tab] Switch view  [j/k] Navigate  [q] Quit"
+            "[c] Confirm  [a] Approve held  [t] Toggle mode  [Tab] Switch view  [j/k] Navigate  [q] Quit"
         }
         AppState::Moving => "Moving files... Please wait",
         AppState::Done => "[q] Quit  [r] Restart",
@@ -3,7 +3,7 @@
 //! These tests verify the end-to-end behavior of offline organization,
 //! including actual file moves and directory structure creation.
 
-use noentropy::files::{FileBatch, categorize_files_offline};
+use noentropy::files::{FileBatch, categorize_files_offline, execute_move_silent, sanitize_plan};
 use noentropy::models::{FileCategory, OrganizationPlan};
 use noentropy::storage::UndoLog;
 use std::fs::{self, File};
@@ -204,13 +204,16 @@ fn test_organization_plan_with_subcategories() {
                 filename: "project.rs".to_string(),
                 category: "Code".to_string(),
                 sub_category: "Rust".to_string(),
+                ..Default::default()
             },
             FileCategory {
                 filename: "script.py".to_string(),
                 category: "Code".to_string(),
                 sub_category: "Python".to_string(),
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
 
     assert_eq!(plan.files.len(), 2);
@@ -218,6 +221,49 @@ fn test_organization_plan_with_subcategories() {
     assert_eq!(plan.files[1].sub_category, "Python");
 }
 
+#[test]
+fn test_move_refuses_unsafe_folder_names() {
+    let outer = TempDir::new().unwrap();
+    let base = outer.path().join("downloads");
+    fs::create_dir(&base).unwrap();
+    File::create(base.join("a.pdf")).unwrap();
+    File::create(base.join("b.pdf")).unwrap();
+
+    let plan = OrganizationPlan {
+        files: vec![
+            FileCategory {
+                filename: "a.pdf".to_string(),
+                category: "../Escaped".to_string(),
+                sub_category: "/etc".to_string(),
+                ..Default::default()
+            },
+            FileCategory {
+                filename: "b.pdf".to_string(),
+                category: "..".to_string(),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let summary = execute_move_silent(&base, plan.clone(), None).unwrap();
+
+    assert_eq!(summary.moved_count(), 0);
+    assert_eq!(summary.error_count(), 2);
+    assert_file_exists(&base, "a.pdf");
+    assert_file_exists(&base, "b.pdf");
+    assert_file_not_exists(outer.path(), "Escaped");
+
+    // Sanitizing the plan first repairs what can be repaired
+    let mut plan = plan;
+    assert_eq!(sanitize_plan(&mut plan).len(), 3);
+    let summary = execute_move_silent(&base, plan, None).unwrap();
+
+    assert_eq!(summary.moved_count(), 1);
+    assert_file_exists(&base, "Escaped/etc/a.pdf");
+    assert_file_exists(&base, "b.pdf");
+}
+
 // ============================================================================
 // LARGE SCALE TESTS
 // ============================================================================
@@ -236,7 +282,7 @@ fn test_categorization_handles_large_file_lists() {
     let result = categorize_files_offline(filenames);
 
     // Should categorize most files (10/11 extensions are known)
-    let expected_categorized = (1000 / 11) * 10 + (1000 % 11).min(10);
+    let expected_categorized = (1000 / 11) * 10 + 1000 % 11;
     assert!(result.plan.files.len() >= expected_categorized - 10); // Allow some margin
     assert!(!result.skipped.is_empty()); // .xyz files should be skipped
 }
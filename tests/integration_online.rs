@@ -1,9 +1,9 @@
 //! Integration tests for online (AI-powered) file organization
 //!
 //! These tests focus on the testable components of the online organization flow.
-//! For full end-to-end tests with the Gemini API, you'll need to:
-//! 1. Set up a valid API key
-//! 2. Use mock servers or test fixtures
+//! Requests to the Gemini API itself are tested against the mock server in
+//! `support/mock_gemini.rs` (see `test_gemini_client.rs` and
+//! `test_online_handler.rs`).
 //!
 //! The tests below cover:
 //! - Cache behavior
@@ -19,6 +19,9 @@ use std::fs::{self, File};
 use std::io::Write;
 use tempfile::TempDir;
 
+/// Digest the entries in these tests are stored and looked up under
+const FINGERPRINT: &str = "test-fingerprint";
+
 /// Helper to create a temp directory with test files
 fn setup_test_directory(files: &[(&str, &[u8])]) -> TempDir {
     let temp_dir = TempDir::new().unwrap();
@@ -50,23 +53,23 @@ fn test_cache_stores_and_retrieves_organization_plans() {
             filename: "test.txt".to_string(),
             category: "Documents".to_string(),
             sub_category: "".to_string(),
+            ..Default::default()
         }],
+        ..Default::default()
     };
 
-    // Check cache (returns None on miss)
-    let cached = cache.check_cache(&filenames, temp_dir.path());
-    assert!(cached.is_none());
+    // Check cache (lists the file as missing on miss)
+    let cached = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert_eq!(cached.missing, filenames);
 
     // Store in cache
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
     // Retrieve from cache
-    let cached2 = cache.check_cache(&filenames, temp_dir.path());
-    assert!(cached2.is_some());
-
-    let cached = cached2.unwrap();
-    assert_eq!(cached.files.len(), 1);
-    assert_eq!(cached.files[0].filename, "test.txt");
+    let cached2 = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert!(cached2.missing.is_empty());
+    assert_eq!(cached2.cached.len(), 1);
+    assert_eq!(cached2.cached[0].filename, "test.txt");
 }
 
 #[test]
@@ -80,11 +83,13 @@ fn test_cache_invalidates_on_file_modification() {
             filename: "test.txt".to_string(),
             category: "Documents".to_string(),
             sub_category: "".to_string(),
+            ..Default::default()
         }],
+        ..Default::default()
     };
 
     // Cache the response
-    cache.cache_response(&filenames, plan, temp_dir.path());
+    cache.cache_response(&filenames, plan, temp_dir.path(), FINGERPRINT);
 
     // Wait longer to ensure filesystem timestamp changes (at least 1 second for most filesystems)
     std::thread::sleep(std::time::Duration::from_secs(2));
@@ -100,14 +105,14 @@ fn test_cache_invalidates_on_file_modification() {
     let _ = fs::metadata(temp_dir.path().join("test.txt"));
 
     // Cache should be invalidated due to modification time change
-    let cached = cache.check_cache(&filenames, temp_dir.path());
+    let cached = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
 
     // Note: Cache invalidation depends on file metadata (size/mtime) changing.
     // If the filesystem has coarse timestamp granularity, this test may be flaky.
     // The important behavior is that the cache CAN detect file changes.
     // For a more robust test, we check that the cache at least loads without error.
     // In production, files are typically modified minutes/hours apart.
-    if cached.is_some() {
+    if cached.missing.is_empty() {
         // If cache wasn't invalidated, it means the filesystem timestamp
         // didn't change within our sleep window - this is acceptable
         // as long as the mechanism works for real-world use cases
@@ -136,25 +141,29 @@ fn test_cache_handles_multiple_files() {
                 filename: "file1.txt".to_string(),
                 category: "Documents".to_string(),
                 sub_category: "".to_string(),
+                ..Default::default()
             },
             FileCategory {
                 filename: "file2.pdf".to_string(),
                 category: "Documents".to_string(),
                 sub_category: "".to_string(),
+                ..Default::default()
             },
             FileCategory {
                 filename: "file3.rs".to_string(),
                 category: "Code".to_string(),
                 sub_category: "".to_string(),
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
 
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
-    let cached = cache.check_cache(&filenames, temp_dir.path());
-    assert!(cached.is_some());
-    assert_eq!(cached.unwrap().files.len(), 3);
+    let cached = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert!(cached.missing.is_empty());
+    assert_eq!(cached.cached.len(), 3);
 }
 
 #[test]
@@ -363,5 +372,4 @@ fn test_complete_offline_flow_dry_run() {
 fn test_api_integration_placeholder() {
     // This test documents where API integration tests would go
     // Implement with mock server or trait-based mocking
-    assert!(true);
 }
@@ -0,0 +1,271 @@
+//! A minimal HTTP server that stands in for the Gemini `generateContent`
+//! endpoint, so the online flow can be tested without an API key.
+//!
+//! Point a client at it with `GeminiClient::set_base_url(server.base_url())`
+//! or `Config::api_base_url`. Every request is recorded and answered by a
+//! responder, either a fixed queue of canned responses or a closure.
+
+#![allow(dead_code)]
+
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A canned HTTP response.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub headers: Vec<(String, String)>,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// A successful `generateContent` response whose only part is `text`.
+    pub fn text(text: &str) -> Self {
+        let body = json!({
+            "candidates": [{
+                "content": { "parts": [{ "text": text }] },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 100,
+                "candidatesTokenCount": 20,
+                "totalTokenCount": 120
+            }
+        });
+        Self::new(200, body.to_string())
+    }
+
+    /// A categorization response placing each `(filename, category)` pair.
+    pub fn plan(files: &[(&str, &str)]) -> Self {
+        let files: Vec<Value> = files
+            .iter()
+            .map(|(filename, category)| {
+                json!({
+                    "filename": filename,
+                    "category": category,
+                    "confidence": 0.9,
+                    "reasoning": "mock"
+                })
+            })
+            .collect();
+        Self::text(&json!({ "files": files }).to_string())
+    }
+
+    /// A 429 carrying a `RetryInfo` hint, as Gemini sends when rate limited.
+    pub fn rate_limited(retry_secs: u32) -> Self {
+        let body = json!({
+            "error": {
+                "code": 429,
+                "message": "Resource has been exhausted",
+                "status": "RESOURCE_EXHAUSTED",
+                "details": [{
+                    "@type": "type.googleapis.com/google.rpc.RetryInfo",
+                    "retryDelay": format!("{}s", retry_secs)
+                }]
+            }
+        });
+        Self::new(429, body.to_string())
+    }
+
+    pub fn server_error() -> Self {
+        let body = json!({
+            "error": {
+                "code": 500,
+                "message": "An internal error has occurred",
+                "status": "INTERNAL"
+            }
+        });
+        Self::new(500, body.to_string())
+    }
+
+    pub fn unauthenticated() -> Self {
+        let body = json!({
+            "error": {
+                "code": 401,
+                "message": "API key not valid",
+                "status": "UNAUTHENTICATED"
+            }
+        });
+        Self::new(401, body.to_string())
+    }
+
+    /// A 200 whose body is not JSON at all.
+    pub fn malformed() -> Self {
+        Self::new(200, "{\"candidates\": [ this is not json")
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Waits `delay` before answering.
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased.
+    pub headers: HashMap<String, String>,
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    /// The text parts of the request, joined with newlines.
+    pub fn prompt(&self) -> String {
+        self.body["contents"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|content| content["parts"].as_array().into_iter().flatten())
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+type Responder = dyn Fn(&RecordedRequest) -> MockResponse + Send + Sync;
+
+pub struct MockGemini {
+    base_url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockGemini {
+    /// Starts a server that answers every request with `responder`.
+    pub fn start(
+        responder: impl Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let base_url = format!("http://{}/v1beta", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responder: Arc<Responder> = Arc::new(responder);
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let recorded = Arc::clone(&recorded);
+                let responder = Arc::clone(&responder);
+                thread::spawn(move || handle_connection(stream, &recorded, responder.as_ref()));
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    /// Starts a server that answers requests with `responses` in order, then
+    /// with a 500 once they run out.
+    pub fn with_responses(responses: Vec<MockResponse>) -> Self {
+        let queue = Mutex::new(VecDeque::from(responses));
+        Self::start(move |_| {
+            queue
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| MockResponse::new(500, "no canned response left"))
+        })
+    }
+
+    /// API root to pass to `GeminiClient::set_base_url`.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    recorded: &Mutex<Vec<RecordedRequest>>,
+    responder: &Responder,
+) {
+    let Some(request) = read_request(&stream) else {
+        return;
+    };
+    recorded.lock().unwrap().push(request.clone());
+
+    let response = responder(&request);
+    thread::sleep(response.delay);
+    write_response(stream, &response);
+}
+
+fn read_request(stream: &TcpStream) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+    })
+}
+
+fn write_response(mut stream: TcpStream, response: &MockResponse) {
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    // The client may have given up on a slow response already
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+    let _ = stream.flush();
+}
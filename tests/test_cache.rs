@@ -1,19 +1,24 @@
 //! Unit tests for storage cache module
 //!
 //! Tests the Cache struct and its methods including:
-//! - check_cache hit/miss behavior
+//! - lookup hit/miss behavior
 //! - cache_response storage
 //! - Cache key generation
 //! - Cache eviction
 //! - Cache persistence and loading
+//! - Fingerprint mismatches (categories, model, prompt, backend)
+//! - Sub-category entries keyed by content hash
 
-use noentropy::models::{FileCategory, OrganizationPlan};
+use noentropy::models::{CacheFingerprint, FileCategory, OrganizationPlan};
 use noentropy::storage::Cache;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 use tempfile::TempDir;
 
+/// Digest the entries in these tests are stored and looked up under
+const FINGERPRINT: &str = "test-fingerprint";
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
@@ -45,8 +50,10 @@ fn create_test_plan(filenames: &[&str]) -> OrganizationPlan {
                 filename: f.to_string(),
                 category: "TestCategory".to_string(),
                 sub_category: "".to_string(),
+                ..Default::default()
             })
             .collect(),
+        ..Default::default()
     }
 }
 
@@ -75,19 +82,19 @@ fn test_cache_key_order_independent() {
 
     // Cache and check
     let mut cache1 = Cache::new();
-    cache1.cache_response(&filenames1, plan.clone(), Path::new("/tmp"));
+    cache1.cache_response(&filenames1, plan.clone(), Path::new("/tmp"), FINGERPRINT);
 
     let mut cache2 = Cache::new();
-    cache2.cache_response(&filenames2, plan.clone(), Path::new("/tmp"));
+    cache2.cache_response(&filenames2, plan.clone(), Path::new("/tmp"), FINGERPRINT);
 
     // Verify both caches return the same result for same content
-    let cached1 = cache1.check_cache(&filenames1, Path::new("/tmp"));
-    let cached2 = cache2.check_cache(&filenames2, Path::new("/tmp"));
+    let cached1 = cache1.lookup(&filenames1, Path::new("/tmp"), FINGERPRINT);
+    let cached2 = cache2.lookup(&filenames2, Path::new("/tmp"), FINGERPRINT);
 
-    assert!(cached1.is_some());
-    assert!(cached2.is_some());
-    assert_eq!(cached1.unwrap().files.len(), 3);
-    assert_eq!(cached2.unwrap().files.len(), 3);
+    assert!(cached1.missing.is_empty());
+    assert!(cached2.missing.is_empty());
+    assert_eq!(cached1.cached.len(), 3);
+    assert_eq!(cached2.cached.len(), 3);
 }
 
 #[test]
@@ -100,17 +107,17 @@ fn test_cache_key_different_for_different_files() {
     let plan1 = create_test_plan(&["file1.txt"]);
     let plan2 = create_test_plan(&["file2.txt"]);
 
-    cache.cache_response(&filenames1, plan1, Path::new("/tmp"));
-    cache.cache_response(&filenames2, plan2, Path::new("/tmp"));
+    cache.cache_response(&filenames1, plan1, Path::new("/tmp"), FINGERPRINT);
+    cache.cache_response(&filenames2, plan2, Path::new("/tmp"), FINGERPRINT);
 
     // Both should be retrievable
-    let cached1 = cache.check_cache(&filenames1, Path::new("/tmp"));
-    let cached2 = cache.check_cache(&filenames2, Path::new("/tmp"));
+    let cached1 = cache.lookup(&filenames1, Path::new("/tmp"), FINGERPRINT);
+    let cached2 = cache.lookup(&filenames2, Path::new("/tmp"), FINGERPRINT);
 
-    assert!(cached1.is_some());
-    assert!(cached2.is_some());
-    assert_eq!(cached1.unwrap().files[0].filename, "file1.txt");
-    assert_eq!(cached2.unwrap().files[0].filename, "file2.txt");
+    assert!(cached1.missing.is_empty());
+    assert!(cached2.missing.is_empty());
+    assert_eq!(cached1.cached[0].filename, "file1.txt");
+    assert_eq!(cached2.cached[0].filename, "file2.txt");
 }
 
 // ============================================================================
@@ -118,36 +125,36 @@ fn test_cache_key_different_for_different_files() {
 // ============================================================================
 
 #[test]
-fn test_check_cache_miss_on_empty_cache() {
+fn test_lookup_miss_on_empty_cache() {
     let cache = Cache::new();
     let filenames = vec!["test.txt".to_string()];
 
-    let result = cache.check_cache(&filenames, Path::new("/tmp"));
+    let result = cache.lookup(&filenames, Path::new("/tmp"), FINGERPRINT);
 
-    assert!(result.is_none());
+    assert_eq!(result.missing, filenames);
 }
 
 #[test]
-fn test_check_cache_hit_after_caching() {
+fn test_lookup_hit_after_caching() {
     let (temp_dir, filenames) = setup_test_directory(&[("test.txt", b"content")]);
     let mut cache = Cache::new();
 
     let plan = create_test_plan(&["test.txt"]);
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
-    let result = cache.check_cache(&filenames, temp_dir.path());
+    let result = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
 
-    assert!(result.is_some());
-    assert_eq!(result.unwrap().files.len(), 1);
+    assert!(result.missing.is_empty());
+    assert_eq!(result.cached.len(), 1);
 }
 
 #[test]
-fn test_check_cache_miss_after_file_modification() {
+fn test_lookup_miss_after_file_modification() {
     let (temp_dir, filenames) = setup_test_directory(&[("test.txt", b"original")]);
     let mut cache = Cache::new();
 
     let plan = create_test_plan(&["test.txt"]);
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
     // Wait for filesystem timestamp to update
     std::thread::sleep(std::time::Duration::from_secs(2));
@@ -158,7 +165,7 @@ fn test_check_cache_miss_after_file_modification() {
     // Force metadata sync
     let _ = fs::metadata(temp_dir.path().join("test.txt"));
 
-    let result = cache.check_cache(&filenames, temp_dir.path());
+    let result = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
 
     // Cache may or may not be invalidated depending on filesystem timestamp granularity
     // This is acceptable behavior - the important thing is no panic occurs
@@ -166,15 +173,78 @@ fn test_check_cache_miss_after_file_modification() {
 }
 
 #[test]
-fn test_check_cache_miss_on_missing_file() {
+fn test_lookup_miss_on_missing_file() {
     let temp_dir = TempDir::new().unwrap();
     let cache = Cache::new();
 
     let filenames = vec!["nonexistent.txt".to_string()];
 
-    let result = cache.check_cache(&filenames, temp_dir.path());
+    let result = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+
+    assert_eq!(result.missing, filenames);
+}
+
+#[test]
+fn test_lookup_misses_only_new_files() {
+    let (temp_dir, filenames) = setup_test_directory(&[("a.txt", b"a"), ("b.txt", b"b")]);
+    let mut cache = Cache::new();
+    cache.cache_response(
+        &filenames,
+        create_test_plan(&["a.txt", "b.txt"]),
+        temp_dir.path(),
+        FINGERPRINT,
+    );
+
+    File::create(temp_dir.path().join("new.txt")).unwrap();
+    let requested = vec![
+        "new.txt".to_string(),
+        "a.txt".to_string(),
+        "b.txt".to_string(),
+    ];
+    let lookup = cache.lookup(&requested, temp_dir.path(), FINGERPRINT);
+
+    assert_eq!(lookup.missing, vec!["new.txt".to_string()]);
+    let cached: Vec<_> = lookup.cached.iter().map(|f| f.filename.as_str()).collect();
+    assert_eq!(cached, ["a.txt", "b.txt"]);
+    assert!(
+        !cache
+            .lookup(&requested, temp_dir.path(), FINGERPRINT)
+            .missing
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_lookup_misses_only_changed_file() {
+    let (temp_dir, filenames) = setup_test_directory(&[("a.txt", b"a"), ("b.txt", b"b")]);
+    let mut cache = Cache::new();
+    cache.cache_response(
+        &filenames,
+        create_test_plan(&["a.txt", "b.txt"]),
+        temp_dir.path(),
+        FINGERPRINT,
+    );
+
+    fs::write(temp_dir.path().join("b.txt"), b"grown since caching").unwrap();
+    let lookup = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+
+    assert_eq!(lookup.missing, vec!["b.txt".to_string()]);
+    assert_eq!(lookup.cached.len(), 1);
+    assert_eq!(lookup.cached[0].filename, "a.txt");
+}
+
+#[test]
+fn test_uncategorized_files_are_not_cached() {
+    let mut cache = Cache::new();
+    let filenames = vec!["a.txt".to_string(), "skipped.txt".to_string()];
+    let mut plan = create_test_plan(&["a.txt"]);
+    plan.uncategorized.push("skipped.txt".to_string());
+
+    cache.cache_response(&filenames, plan, Path::new("/tmp"), FINGERPRINT);
+    let lookup = cache.lookup(&filenames, Path::new("/tmp"), FINGERPRINT);
 
-    assert!(result.is_none());
+    assert_eq!(cache.len(), 1);
+    assert_eq!(lookup.missing, vec!["skipped.txt".to_string()]);
 }
 
 // ============================================================================
@@ -187,15 +257,14 @@ fn test_cache_response_stores_plan() {
     let mut cache = Cache::new();
 
     let plan = create_test_plan(&["file1.txt", "file2.txt"]);
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
-
-    let cached = cache.check_cache(&filenames, temp_dir.path());
-    assert!(cached.is_some());
-
-    let cached_plan = cached.unwrap();
-    assert_eq!(cached_plan.files.len(), 2);
-    assert_eq!(cached_plan.files[0].filename, "file1.txt");
-    assert_eq!(cached_plan.files[1].filename, "file2.txt");
+    let stored = cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
+    assert_eq!(stored, 2);
+
+    let cached = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert!(cached.missing.is_empty());
+    assert_eq!(cached.cached.len(), 2);
+    assert_eq!(cached.cached[0].filename, "file1.txt");
+    assert_eq!(cached.cached[1].filename, "file2.txt");
 }
 
 #[test]
@@ -206,11 +275,11 @@ fn test_cache_response_empty_filenames() {
     let filenames: Vec<String> = vec![];
     let plan = create_test_plan(&[]);
 
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
-    let result = cache.check_cache(&filenames, temp_dir.path());
-    assert!(result.is_some());
-    assert_eq!(result.unwrap().files.len(), 0);
+    let result = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert!(result.missing.is_empty());
+    assert_eq!(result.cached.len(), 0);
 }
 
 #[test]
@@ -231,11 +300,11 @@ fn test_cache_response_large_number_of_files() {
     }
 
     let plan = create_test_plan(&files.iter().map(|s| s.as_str()).collect::<Vec<_>>());
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
-    let result = cache.check_cache(&filenames, temp_dir.path());
-    assert!(result.is_some());
-    assert_eq!(result.unwrap().files.len(), count);
+    let result = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert!(result.missing.is_empty());
+    assert_eq!(result.cached.len(), count);
 }
 
 // ============================================================================
@@ -251,13 +320,10 @@ fn test_cache_eviction_when_max_entries_exceeded() {
     for i in 0..10 {
         let filenames = vec![format!("file{}.txt", i)];
         let plan = create_test_plan(&[&format!("file{}.txt", i)]);
-        cache.cache_response(&filenames, plan, temp_dir.path());
+        cache.cache_response(&filenames, plan, temp_dir.path(), FINGERPRINT);
     }
 
-    // Should have at most max_entries
-    // (We can't guarantee exact count due to HashMap iteration order,
-    // but we know it shouldn't grow unbounded)
-    assert!(true); // Just verify no panic
+    assert_eq!(cache.len(), 5);
 }
 
 // ============================================================================
@@ -273,7 +339,12 @@ fn test_cache_save_and_load() {
     {
         let mut cache = Cache::new();
         let plan = create_test_plan(&["test.txt"]);
-        cache.cache_response(&vec!["test.txt".to_string()], plan, Path::new("/tmp"));
+        cache.cache_response(
+            &["test.txt".to_string()],
+            plan,
+            Path::new("/tmp"),
+            FINGERPRINT,
+        );
         cache.save(&cache_path).unwrap();
     }
 
@@ -281,8 +352,8 @@ fn test_cache_save_and_load() {
     let loaded_cache = Cache::load_or_create(&cache_path, false);
 
     // Should have the entry
-    let result = loaded_cache.check_cache(&vec!["test.txt".to_string()], Path::new("/tmp"));
-    assert!(result.is_some());
+    let result = loaded_cache.lookup(&["test.txt".to_string()], Path::new("/tmp"), FINGERPRINT);
+    assert!(result.missing.is_empty());
 }
 
 #[test]
@@ -298,6 +369,26 @@ fn test_cache_load_corrupted_file() {
     assert!(cache.is_empty());
 }
 
+#[test]
+fn test_cache_load_restores_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_path = temp_dir.path().join("cache.json");
+
+    let mut cache = Cache::new();
+    cache.cache_response(
+        &["a.txt".to_string()],
+        create_test_plan(&["a.txt"]),
+        Path::new("/tmp"),
+        FINGERPRINT,
+    );
+    cache.save(&cache_path).unwrap();
+    cache.save(&cache_path).unwrap();
+    fs::write(&cache_path, "{ \"files\": {").unwrap();
+
+    let loaded = Cache::load_or_create(&cache_path, true);
+    assert_eq!(loaded.len(), 1);
+}
+
 #[test]
 fn test_cache_load_nonexistent_file() {
     let temp_dir = TempDir::new().unwrap();
@@ -324,7 +415,7 @@ fn test_cache_is_not_empty_after_storing() {
     let mut cache = Cache::new();
 
     let plan = create_test_plan(&["test.txt"]);
-    cache.cache_response(&filenames, plan, temp_dir.path());
+    cache.cache_response(&filenames, plan, temp_dir.path(), FINGERPRINT);
 
     assert!(!cache.is_empty());
 }
@@ -343,7 +434,7 @@ fn test_cache_len_after_multiple_stores() {
     for i in 0..5 {
         let filenames = vec![format!("file{}.txt", i)];
         let plan = create_test_plan(&[&format!("file{}.txt", i)]);
-        cache.cache_response(&filenames, plan, temp_dir.path());
+        cache.cache_response(&filenames, plan, temp_dir.path(), FINGERPRINT);
     }
 
     assert_eq!(cache.len(), 5);
@@ -362,7 +453,7 @@ fn test_cleanup_old_entries() {
     for i in 0..5 {
         let filenames = vec![format!("file{}.txt", i)];
         let plan = create_test_plan(&[&format!("file{}.txt", i)]);
-        cache.cache_response(&filenames, plan, temp_dir.path());
+        cache.cache_response(&filenames, plan, temp_dir.path(), FINGERPRINT);
     }
 
     // Clean up entries older than 0 seconds (should remove all)
@@ -385,10 +476,10 @@ fn test_cache_with_special_characters_in_filename() {
     File::create(&file_path).unwrap();
 
     let plan = create_test_plan(&["file-with-dashes.txt"]);
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
-    let result = cache.check_cache(&filenames, temp_dir.path());
-    assert!(result.is_some());
+    let result = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert!(result.missing.is_empty());
 }
 
 #[test]
@@ -401,10 +492,10 @@ fn test_cache_with_unicode_filenames() {
     File::create(&file_path).unwrap();
 
     let plan = create_test_plan(&["测试文件.txt"]);
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
-    let result = cache.check_cache(&filenames, temp_dir.path());
-    assert!(result.is_some());
+    let result = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+    assert!(result.missing.is_empty());
 }
 
 #[test]
@@ -419,8 +510,135 @@ fn test_cache_handles_duplicate_filenames() {
 
     let plan = create_test_plan(&["file.txt", "file.txt"]);
     // This may not make practical sense but should not panic
-    cache.cache_response(&filenames, plan.clone(), temp_dir.path());
+    cache.cache_response(&filenames, plan.clone(), temp_dir.path(), FINGERPRINT);
 
     // Just verify no panic
-    let _ = cache.check_cache(&filenames, temp_dir.path());
+    let _ = cache.lookup(&filenames, temp_dir.path(), FINGERPRINT);
+}
+
+// ============================================================================
+// FINGERPRINT TESTS
+// ============================================================================
+
+fn fingerprint(categories: &[&str], model: &str) -> CacheFingerprint {
+    CacheFingerprint {
+        categories: categories.iter().map(|c| c.to_string()).collect(),
+        model: model.to_string(),
+        prompt_version: "v1".to_string(),
+        backend: "https://example.test".to_string(),
+    }
+}
+
+#[test]
+fn test_lookup_miss_when_fingerprint_changes() {
+    let (temp_dir, filenames) = setup_test_directory(&[("a.txt", b"a")]);
+    let path = temp_dir.path();
+    let mut cache = Cache::new();
+    let stored = fingerprint(&["Documents", "Images"], "gemini-flash").digest();
+    cache.cache_response(&filenames, create_test_plan(&["a.txt"]), path, &stored);
+    assert!(cache.lookup(&filenames, path, &stored).missing.is_empty());
+
+    let renamed = fingerprint(&["Documents", "Photos"], "gemini-flash").digest();
+    assert!(!cache.lookup(&filenames, path, &renamed).missing.is_empty());
+
+    let other_model = fingerprint(&["Documents", "Images"], "gemini-pro").digest();
+    assert!(
+        !cache
+            .lookup(&filenames, path, &other_model)
+            .missing
+            .is_empty()
+    );
+
+    let reordered = fingerprint(&["Images", "Documents"], "gemini-flash").digest();
+    assert!(
+        cache
+            .lookup(&filenames, path, &reordered)
+            .missing
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_fingerprint_survives_save_and_load() {
+    let (temp_dir, filenames) = setup_test_directory(&[("a.txt", b"a")]);
+    let cache_path = temp_dir.path().join("cache.json");
+    let current = fingerprint(&["Documents"], "gemini-flash");
+
+    let mut cache = Cache::new();
+    cache.cache_response(
+        &filenames,
+        create_test_plan(&["a.txt"]),
+        temp_dir.path(),
+        &current.digest(),
+    );
+    cache.save(&cache_path).unwrap();
+
+    let loaded = Cache::load_or_create(&cache_path, true);
+    let other_backend = CacheFingerprint {
+        backend: "http://127.0.0.1:8080".to_string(),
+        ..current.clone()
+    };
+    assert!(
+        !loaded
+            .lookup(&filenames, temp_dir.path(), &other_backend.digest())
+            .missing
+            .is_empty()
+    );
+    assert!(
+        loaded
+            .lookup(&filenames, temp_dir.path(), &current.digest())
+            .missing
+            .is_empty()
+    );
+}
+
+// ============================================================================
+// SUB-CATEGORY TESTS
+// ============================================================================
+
+#[test]
+fn test_sub_category_lookup_by_content_hash_and_category() {
+    let mut cache = Cache::new();
+    cache.cache_sub_category("abc123", "Documents", "Invoices", FINGERPRINT);
+
+    assert_eq!(
+        cache.lookup_sub_category("abc123", "Documents", FINGERPRINT),
+        Some("Invoices")
+    );
+    assert_eq!(
+        cache.lookup_sub_category("abc123", "Images", FINGERPRINT),
+        None
+    );
+    assert_eq!(
+        cache.lookup_sub_category("def456", "Documents", FINGERPRINT),
+        None
+    );
+    assert_eq!(cache.sub_category_len(), 1);
+    // Sub-categories do not count as categorized files
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_sub_category_miss_when_fingerprint_changes() {
+    let mut cache = Cache::new();
+    let flash = fingerprint(&["Documents"], "gemini-flash").digest();
+    cache.cache_sub_category("abc123", "Documents", "Invoices", &flash);
+
+    let pro = fingerprint(&["Documents"], "gemini-pro").digest();
+    assert_eq!(cache.lookup_sub_category("abc123", "Documents", &pro), None);
+}
+
+#[test]
+fn test_sub_categories_survive_save_and_load() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_path = temp_dir.path().join("cache.json");
+    let mut cache = Cache::new();
+    cache.cache_sub_category("abc123", "Documents", "Invoices", FINGERPRINT);
+    cache.save(&cache_path).unwrap();
+
+    let loaded = Cache::load_or_create(&cache_path, true);
+    assert_eq!(
+        loaded.lookup_sub_category("abc123", "Documents", FINGERPRINT),
+        Some("Invoices")
+    );
 }
@@ -0,0 +1,255 @@
+//! Tests for the `noentropy cache` subcommand
+//!
+//! Covers:
+//! - Parsing the cache actions
+//! - Clearing and pruning the cache file
+//! - Exporting a cache and importing it on another machine
+
+use clap::Parser;
+use noentropy::cli::Args;
+use noentropy::cli::Command;
+use noentropy::cli::args::CacheAction;
+use noentropy::cli::handlers::run_cache_action;
+use noentropy::models::{FileCategory, OrganizationPlan};
+use noentropy::storage::Cache;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Digest the entries in these tests are stored and looked up under
+const FINGERPRINT: &str = "test-fingerprint";
+
+fn plan(files: &[(&str, &str)]) -> OrganizationPlan {
+    OrganizationPlan {
+        files: files
+            .iter()
+            .map(|(filename, category)| FileCategory {
+                filename: filename.to_string(),
+                category: category.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Writes a cache at `<dir>/cache.json` categorizing real files in `<dir>/downloads`.
+fn write_cache(dir: &Path, files: &[(&str, &str)]) -> (PathBuf, PathBuf) {
+    let downloads = dir.join("downloads");
+    fs::create_dir_all(&downloads).unwrap();
+    let names: Vec<String> = files.iter().map(|(name, _)| name.to_string()).collect();
+    for name in &names {
+        fs::write(downloads.join(name), name).unwrap();
+    }
+
+    let cache_path = dir.join("cache.json");
+    let mut cache = Cache::new();
+    cache.cache_response(&names, plan(files), &downloads, FINGERPRINT);
+    cache.cache_sub_category("abc123", "Documents", "Invoices", FINGERPRINT);
+    cache.save(&cache_path).unwrap();
+    (cache_path, downloads)
+}
+
+fn run(action: CacheAction, cache_path: &Path) {
+    run_cache_action(
+        &action,
+        cache_path,
+        &cache_path.with_file_name("usage.json"),
+    )
+    .unwrap();
+}
+
+// ============================================================================
+// ARGUMENT PARSING TESTS
+// ============================================================================
+
+#[test]
+fn test_parse_cache_actions() {
+    let parse = |args: &[&str]| match Args::try_parse_from(args).unwrap().command {
+        Some(Command::Cache { action }) => action,
+        other => panic!("unexpected command: {:?}", other),
+    };
+
+    assert_eq!(parse(&["noentropy", "cache", "stats"]), CacheAction::Stats);
+    assert_eq!(
+        parse(&["noentropy", "cache", "prune", "--days", "3"]),
+        CacheAction::Prune { days: 3 }
+    );
+    assert_eq!(
+        parse(&["noentropy", "cache", "export", "team.json"]),
+        CacheAction::Export {
+            file: PathBuf::from("team.json")
+        }
+    );
+}
+
+// ============================================================================
+// MAINTENANCE TESTS
+// ============================================================================
+
+#[test]
+fn test_clear_removes_every_entry() {
+    let dir = TempDir::new().unwrap();
+    let (cache_path, _) = write_cache(dir.path(), &[("a.pdf", "Documents")]);
+
+    run(CacheAction::Clear, &cache_path);
+
+    let cache = Cache::load_or_create(&cache_path, true);
+    assert!(cache.is_empty());
+    assert_eq!(cache.sub_category_len(), 0);
+}
+
+#[test]
+fn test_prune_keeps_recent_entries() {
+    let dir = TempDir::new().unwrap();
+    let (cache_path, _) = write_cache(dir.path(), &[("a.pdf", "Documents")]);
+
+    run(CacheAction::Prune { days: 7 }, &cache_path);
+    assert_eq!(Cache::load_or_create(&cache_path, true).len(), 1);
+
+    run(CacheAction::Prune { days: 0 }, &cache_path);
+    assert!(Cache::load_or_create(&cache_path, true).is_empty());
+}
+
+#[test]
+fn test_stats_and_list_do_not_modify_cache() {
+    let dir = TempDir::new().unwrap();
+    let (cache_path, _) = write_cache(dir.path(), &[("a.pdf", "Documents")]);
+    let before = fs::read_to_string(&cache_path).unwrap();
+
+    run(CacheAction::Stats, &cache_path);
+    run(CacheAction::List { limit: 10 }, &cache_path);
+
+    assert_eq!(fs::read_to_string(&cache_path).unwrap(), before);
+}
+
+// ============================================================================
+// EXPORT AND IMPORT TESTS
+// ============================================================================
+
+#[test]
+fn test_export_leaves_out_local_paths() {
+    let dir = TempDir::new().unwrap();
+    let (cache_path, downloads) = write_cache(dir.path(), &[("setup.exe", "Installers")]);
+    let export_path = dir.path().join("team.json");
+
+    run(
+        CacheAction::Export {
+            file: export_path.clone(),
+        },
+        &cache_path,
+    );
+
+    let exported = fs::read_to_string(&export_path).unwrap();
+    assert!(exported.contains("setup.exe"));
+    assert!(!exported.contains(downloads.to_str().unwrap()));
+}
+
+#[test]
+fn test_imported_entries_match_by_filename() {
+    let dir = TempDir::new().unwrap();
+    let (cache_path, _) = write_cache(
+        dir.path(),
+        &[("setup.exe", "Installers"), ("report.pdf", "Documents")],
+    );
+    let export_path = dir.path().join("team.json");
+    run(
+        CacheAction::Export {
+            file: export_path.clone(),
+        },
+        &cache_path,
+    );
+
+    // A teammate's copy of the same download, with a different size and time
+    let other = TempDir::new().unwrap();
+    fs::write(other.path().join("setup.exe"), "a different build").unwrap();
+    let other_cache = other.path().join("cache.json");
+    run(
+        CacheAction::Import {
+            file: export_path.clone(),
+        },
+        &other_cache,
+    );
+
+    let cache = Cache::load_or_create(&other_cache, true);
+    let lookup = cache.lookup(&["setup.exe".to_string()], other.path(), FINGERPRINT);
+    assert!(lookup.missing.is_empty());
+    assert_eq!(lookup.cached[0].category, "Installers");
+    assert_eq!(
+        cache.lookup_sub_category("abc123", "Documents", FINGERPRINT),
+        Some("Invoices")
+    );
+}
+
+#[test]
+fn test_imported_entries_keep_each_fingerprint() {
+    let dir = TempDir::new().unwrap();
+    let (cache_path, _) = write_cache(dir.path(), &[("setup.exe", "Installers")]);
+    // Another copy of the download, categorized under other settings
+    let desktop = dir.path().join("desktop");
+    fs::create_dir_all(&desktop).unwrap();
+    fs::write(desktop.join("setup.exe"), "a different build").unwrap();
+    let mut cache = Cache::load_or_create(&cache_path, true);
+    cache.cache_response(
+        &["setup.exe".to_string()],
+        plan(&[("setup.exe", "Software")]),
+        &desktop,
+        "other-fingerprint",
+    );
+    cache.save(&cache_path).unwrap();
+    let export_path = dir.path().join("team.json");
+    run(
+        CacheAction::Export {
+            file: export_path.clone(),
+        },
+        &cache_path,
+    );
+
+    let other = TempDir::new().unwrap();
+    let other_cache = other.path().join("cache.json");
+    run(CacheAction::Import { file: export_path }, &other_cache);
+
+    let cache = Cache::load_or_create(&other_cache, true);
+    let names = ["setup.exe".to_string()];
+    let current = cache.lookup(&names, other.path(), FINGERPRINT);
+    assert_eq!(current.cached[0].category, "Installers");
+    let other_settings = cache.lookup(&names, other.path(), "other-fingerprint");
+    assert_eq!(other_settings.cached[0].category, "Software");
+}
+
+#[test]
+fn test_imported_entries_survive_age_cleanup() {
+    let dir = TempDir::new().unwrap();
+    let (cache_path, _) = write_cache(dir.path(), &[("setup.exe", "Installers")]);
+    let export_path = dir.path().join("team.json");
+    run(
+        CacheAction::Export {
+            file: export_path.clone(),
+        },
+        &cache_path,
+    );
+
+    // Entries the exporter made long ago
+    let mut export: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&export_path).unwrap()).unwrap();
+    for entry in export["files"].as_array_mut().unwrap() {
+        entry["timestamp"] = 1.into();
+    }
+    for entry in export["sub_categories"]
+        .as_object_mut()
+        .unwrap()
+        .values_mut()
+    {
+        entry["timestamp"] = 1.into();
+    }
+    fs::write(&export_path, export.to_string()).unwrap();
+
+    let other = TempDir::new().unwrap();
+    let other_cache = other.path().join("cache.json");
+    run(CacheAction::Import { file: export_path }, &other_cache);
+
+    let mut cache = Cache::load_or_create(&other_cache, true);
+    assert_eq!(cache.cleanup_old_entries(7 * 24 * 60 * 60), 0);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.sub_category_len(), 1);
+}
@@ -0,0 +1,168 @@
+//! Tests for the category discovery step
+//!
+//! Covers:
+//! - Sampling filenames from a FileBatch
+//! - Editing the proposed categories before they are saved
+//! - Holding the proposal request to the daily usage limits
+
+#[path = "support/mock_gemini.rs"]
+mod mock_gemini;
+
+use mock_gemini::{MockGemini, MockResponse};
+use noentropy::cli::handlers::{EditOutcome, apply_category_edit, request_proposals};
+use noentropy::files::FileBatch;
+use noentropy::gemini::{CategoryProposal, GeminiClient, GeminiError};
+use noentropy::models::{RequestKind, UsageLimits, UsageRecord};
+use noentropy::settings::Config;
+use noentropy::storage::UsageLog;
+use std::path::{Path, PathBuf};
+
+fn proposals(names: &[&str]) -> Vec<CategoryProposal> {
+    names
+        .iter()
+        .map(|name| CategoryProposal {
+            name: name.to_string(),
+            description: format!("{} files", name),
+        })
+        .collect()
+}
+
+fn names(categories: &[CategoryProposal]) -> Vec<&str> {
+    categories.iter().map(|c| c.name.as_str()).collect()
+}
+
+// ============================================================================
+// SAMPLING TESTS
+// ============================================================================
+
+#[test]
+fn test_sample_spreads_across_sorted_names() {
+    let filenames: Vec<String> = (0..100).rev().map(|i| format!("f{:03}", i)).collect();
+    let batch = FileBatch {
+        paths: filenames.iter().map(PathBuf::from).collect(),
+        filenames,
+    };
+
+    let sample = batch.sample(4);
+
+    assert_eq!(sample, ["f000", "f025", "f050", "f075"]);
+}
+
+#[test]
+fn test_sample_returns_everything_for_small_batches() {
+    let batch = FileBatch {
+        filenames: vec!["b.txt".to_string(), "a.txt".to_string()],
+        paths: vec![PathBuf::from("b.txt"), PathBuf::from("a.txt")],
+    };
+
+    assert_eq!(batch.sample(10), ["a.txt", "b.txt"]);
+}
+
+// ============================================================================
+// EDITING TESTS
+// ============================================================================
+
+#[test]
+fn test_empty_input_accepts() {
+    let mut categories = proposals(&["Work", "Photos"]);
+
+    assert_eq!(
+        apply_category_edit(&mut categories, "\n"),
+        Ok(EditOutcome::Accept)
+    );
+    assert_eq!(
+        apply_category_edit(&mut categories, "q"),
+        Ok(EditOutcome::Cancel)
+    );
+}
+
+#[test]
+fn test_add_rename_and_delete() {
+    let mut categories = proposals(&["Work", "Photos", "Misc"]);
+
+    apply_category_edit(&mut categories, "a Tax Returns").unwrap();
+    apply_category_edit(&mut categories, "r 2 Pictures").unwrap();
+    apply_category_edit(&mut categories, "d 3").unwrap();
+    apply_category_edit(&mut categories, "e 1 Client projects").unwrap();
+
+    assert_eq!(names(&categories), ["Work", "Pictures", "Tax Returns"]);
+    assert_eq!(categories[0].description, "Client projects");
+    assert_eq!(categories[2].description, "");
+}
+
+#[test]
+fn test_invalid_edits_are_rejected() {
+    let mut categories = proposals(&["Work", "Photos"]);
+
+    assert!(apply_category_edit(&mut categories, "a work").is_err());
+    assert!(apply_category_edit(&mut categories, "a ..").is_err());
+    assert!(apply_category_edit(&mut categories, "r 3 Other").is_err());
+    assert!(apply_category_edit(&mut categories, "d zero").is_err());
+    assert!(apply_category_edit(&mut categories, "x").is_err());
+    assert_eq!(names(&categories), ["Work", "Photos"]);
+
+    // Renaming a category to its own name in another case is allowed
+    apply_category_edit(&mut categories, "r 1 WORK").unwrap();
+    assert_eq!(names(&categories), ["WORK", "Photos"]);
+}
+
+#[test]
+fn test_added_names_are_sanitized() {
+    let mut categories = proposals(&["Work"]);
+
+    apply_category_edit(&mut categories, "a ../Bills/2024").unwrap();
+
+    assert_eq!(names(&categories), ["Work", "Bills-2024"]);
+}
+
+#[test]
+fn test_cannot_accept_an_empty_list() {
+    let mut categories = proposals(&["Work"]);
+
+    apply_category_edit(&mut categories, "d 1").unwrap();
+
+    assert!(apply_category_edit(&mut categories, "").is_err());
+}
+
+// ============================================================================
+// USAGE LIMIT TESTS
+// ============================================================================
+
+#[tokio::test]
+async fn test_proposals_stop_at_the_daily_limit() {
+    let server = MockGemini::with_responses(vec![MockResponse::text("{}")]);
+    let config = Config {
+        api_key: "test-key".to_string(),
+        api_base_url: Some(server.base_url().to_string()),
+        usage_limits: UsageLimits {
+            max_requests_per_day: Some(1),
+            max_tokens_per_day: None,
+        },
+        ..Default::default()
+    };
+    let client = GeminiClient::from_config(&config).unwrap();
+    let batch = FileBatch {
+        filenames: vec!["invoice.pdf".to_string()],
+        paths: vec![PathBuf::from("invoice.pdf")],
+    };
+    let mut usage_log = UsageLog::new();
+    usage_log.extend(vec![UsageRecord::new(
+        RequestKind::Discovery,
+        "gemini",
+        None,
+    )]);
+
+    let result = request_proposals(
+        &client,
+        &config,
+        Path::new("/downloads"),
+        &batch,
+        10,
+        &mut usage_log,
+    )
+    .await;
+
+    assert!(matches!(result, Err(GeminiError::UsageLimitReached(_))));
+    assert_eq!(server.request_count(), 0);
+    assert_eq!(usage_log.entries().len(), 1);
+}
@@ -0,0 +1,590 @@
+//! Tests for GeminiClient against a local mock of the Gemini API
+//!
+//! Covers:
+//! - Request shape (endpoint path, API key header)
+//! - Building a client from the config
+//! - Retries on rate limits and server errors
+//! - Error mapping for failed, malformed and slow responses
+//! - Batching of large file lists
+//! - Holding concurrent batches to the daily request limit
+//! - Splitting a batch whose response was incomplete
+//! - Leaving a file uncategorized when it fails on its own
+//! - Requesting only files missing from the cache
+//! - Caching deep-inspection sub-categories by content hash
+
+#[path = "support/mock_gemini.rs"]
+mod mock_gemini;
+
+use mock_gemini::{MockGemini, MockResponse};
+use noentropy::files::{MediaBudget, SubFolderIndex, read_inspection_targets};
+use noentropy::gemini::batching::TokenBudget;
+use noentropy::gemini::{GeminiClient, GeminiError, PromptTemplates};
+use noentropy::models::{FileCategory, FileContextFields, PromptConfig, UsageLimits, UsageTotals};
+use noentropy::settings::Config;
+use noentropy::storage::Cache;
+use std::collections::HashMap;
+use std::time::Duration;
+use tempfile::TempDir;
+
+const API_KEY: &str = "test-key-0123456789";
+
+fn client_for(server: &MockGemini) -> GeminiClient {
+    let mut client = GeminiClient::new_silent(API_KEY, &["Documents".to_string()]);
+    client.set_base_url(server.base_url());
+    client.set_max_retry_delay(Duration::from_millis(10));
+    client
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_sends_api_key_in_header_not_url() {
+    let server =
+        MockGemini::with_responses(vec![MockResponse::plan(&[("report.pdf", "Documents")])]);
+    let client = client_for(&server);
+
+    let plan = client.organize_files(names(&["report.pdf"])).await.unwrap();
+
+    assert_eq!(plan.files.len(), 1);
+    assert_eq!(plan.files[0].category, "Documents");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert!(requests[0].path.starts_with("/v1beta/models/"));
+    assert!(requests[0].path.ends_with(":generateContent"));
+    assert!(!requests[0].path.contains(API_KEY));
+    assert_eq!(requests[0].headers["x-goog-api-key"], API_KEY);
+    assert!(requests[0].prompt().contains("report.pdf"));
+}
+
+#[tokio::test]
+async fn test_retries_after_rate_limit() {
+    let server = MockGemini::with_responses(vec![
+        MockResponse::rate_limited(30),
+        MockResponse::plan(&[("a.txt", "Documents")]),
+    ]);
+    let client = client_for(&server);
+
+    let plan = client.organize_files(names(&["a.txt"])).await.unwrap();
+
+    assert_eq!(plan.files.len(), 1);
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn test_rate_limit_error_keeps_retry_hint() {
+    let server = MockGemini::with_responses(vec![MockResponse::rate_limited(39); 3]);
+    let client = client_for(&server);
+
+    let error = client.organize_files(names(&["a.txt"])).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        GeminiError::RateLimitExceeded { retry_after: 39 }
+    ));
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn test_retry_after_header_is_used_without_json_body() {
+    let server = MockGemini::with_responses(vec![
+        MockResponse::new(429, "slow down")
+            .with_header("Retry-After", "7");
+        3
+    ]);
+    let client = client_for(&server);
+
+    let error = client.organize_files(names(&["a.txt"])).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        GeminiError::RateLimitExceeded { retry_after: 7 }
+    ));
+}
+
+#[tokio::test]
+async fn test_server_errors_are_retried_then_reported() {
+    let server = MockGemini::with_responses(vec![MockResponse::server_error(); 3]);
+    let client = client_for(&server);
+
+    let error = client.organize_files(names(&["a.txt"])).await.unwrap_err();
+
+    assert!(matches!(error, GeminiError::InternalError { .. }));
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn test_server_error_then_success() {
+    let server = MockGemini::with_responses(vec![
+        MockResponse::server_error(),
+        MockResponse::plan(&[("a.txt", "Documents")]),
+    ]);
+    let client = client_for(&server);
+
+    assert!(client.organize_files(names(&["a.txt"])).await.is_ok());
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn test_malformed_body_is_an_invalid_response() {
+    let server = MockGemini::with_responses(vec![MockResponse::malformed()]);
+    let client = client_for(&server);
+
+    let error = client.organize_files(names(&["a.txt"])).await.unwrap_err();
+
+    assert!(matches!(error, GeminiError::InvalidResponse(_)));
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_slow_response_times_out() {
+    let slow = MockResponse::plan(&[("a.txt", "Documents")]).delayed(Duration::from_secs(2));
+    let server = MockGemini::with_responses(vec![slow; 3]);
+    let mut client = client_for(&server);
+    client.set_timeout(Duration::from_millis(200));
+
+    let error = client.organize_files(names(&["a.txt"])).await.unwrap_err();
+
+    assert!(matches!(error, GeminiError::Timeout { .. }));
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn test_client_from_config_uses_its_key_and_base_url() {
+    if std::env::var("GEMINI_API_KEY").is_ok() {
+        return;
+    }
+    let server = MockGemini::with_responses(vec![MockResponse::text("pong")]);
+    let config = Config {
+        api_key: API_KEY.to_string(),
+        api_base_url: Some(server.base_url().to_string()),
+        ..Default::default()
+    };
+
+    let client = GeminiClient::from_config(&config).unwrap();
+    client.check_connectivity().await.unwrap();
+
+    assert!(client.has_api_key());
+    assert_eq!(server.requests()[0].headers["x-goog-api-key"], API_KEY);
+    assert!(
+        !GeminiClient::from_config(&Config::default())
+            .unwrap()
+            .has_api_key()
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_key_fails_connectivity_check() {
+    let server = MockGemini::with_responses(vec![MockResponse::unauthenticated()]);
+    let client = client_for(&server);
+
+    let error = client.check_connectivity().await.unwrap_err();
+
+    assert!(matches!(error, GeminiError::InvalidApiKey));
+}
+
+#[tokio::test]
+async fn test_large_file_lists_are_batched() {
+    let files: Vec<String> = (0..10).map(|i| format!("file_{:02}.txt", i)).collect();
+    let expected = files.clone();
+    let server = MockGemini::start(move |request| {
+        let prompt = request.prompt();
+        let batch: Vec<(&str, &str)> = expected
+            .iter()
+            .filter(|name| prompt.contains(name.as_str()))
+            .map(|name| (name.as_str(), "Documents"))
+            .collect();
+        MockResponse::plan(&batch)
+    });
+    let mut client = client_for(&server);
+    client.set_token_budget(TokenBudget {
+        max_files: 3,
+        ..Default::default()
+    });
+
+    let plan = client
+        .organize_files_in_batches(files.clone(), None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(server.request_count(), 4);
+    let categorized: Vec<_> = plan.files.iter().map(|f| f.filename.clone()).collect();
+    assert_eq!(categorized, files);
+    assert!(plan.uncategorized.is_empty());
+}
+
+#[tokio::test]
+async fn test_concurrent_batches_stop_at_the_request_limit() {
+    let files: Vec<String> = (0..6).map(|i| format!("file_{:02}.txt", i)).collect();
+    let expected = files.clone();
+    let server = MockGemini::start(move |request| {
+        let prompt = request.prompt();
+        let batch: Vec<(&str, &str)> = expected
+            .iter()
+            .filter(|name| prompt.contains(name.as_str()))
+            .map(|name| (name.as_str(), "Documents"))
+            .collect();
+        MockResponse::plan(&batch)
+    });
+    let mut client = client_for(&server);
+    client.set_max_concurrent(6);
+    client.set_token_budget(TokenBudget {
+        max_files: 1,
+        ..Default::default()
+    });
+    client.usage().set_limits(
+        UsageLimits {
+            max_requests_per_day: Some(2),
+            ..Default::default()
+        },
+        UsageTotals::default(),
+    );
+
+    let result = client.organize_files_in_batches(files, None, None).await;
+
+    assert!(matches!(result, Err(GeminiError::UsageLimitReached(_))));
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn test_retries_count_toward_the_request_limit() {
+    let server = MockGemini::with_responses(vec![
+        MockResponse::server_error(),
+        MockResponse::server_error(),
+        MockResponse::plan(&[("a.txt", "Documents")]),
+    ]);
+    let client = client_for(&server);
+    client.usage().set_limits(
+        UsageLimits {
+            max_requests_per_day: Some(2),
+            ..Default::default()
+        },
+        UsageTotals::default(),
+    );
+
+    let result = client.organize_files(names(&["a.txt"])).await;
+
+    assert!(matches!(result, Err(GeminiError::UsageLimitReached(_))));
+    assert_eq!(server.request_count(), 2);
+    let records = client.usage().take_records();
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|r| r.total_tokens == 0 && r.files == 0));
+}
+
+#[tokio::test]
+async fn test_only_uncached_files_are_requested() {
+    let dir = TempDir::new().unwrap();
+    for name in ["a.txt", "b.txt", "new.txt"] {
+        std::fs::write(dir.path().join(name), name).unwrap();
+    }
+    let server = MockGemini::with_responses(vec![
+        MockResponse::plan(&[("a.txt", "Documents"), ("b.txt", "Documents")]),
+        MockResponse::plan(&[("new.txt", "Documents")]),
+    ]);
+    let client = client_for(&server);
+    let mut cache = Cache::new();
+
+    client
+        .organize_files_with_cache(
+            names(&["a.txt", "b.txt"]),
+            Some(&mut cache),
+            Some(dir.path()),
+        )
+        .await
+        .unwrap();
+    let plan = client
+        .organize_files_with_cache(
+            names(&["a.txt", "new.txt", "b.txt"]),
+            Some(&mut cache),
+            Some(dir.path()),
+        )
+        .await
+        .unwrap();
+
+    let categorized: Vec<_> = plan.files.iter().map(|f| f.filename.as_str()).collect();
+    assert_eq!(categorized, ["a.txt", "new.txt", "b.txt"]);
+    assert_eq!(server.request_count(), 2);
+    let second = server.requests()[1].prompt();
+    assert!(second.contains("new.txt"));
+    assert!(!second.contains("a.txt"));
+    assert!(!second.contains("b.txt"));
+}
+
+#[tokio::test]
+async fn test_changing_categories_invalidates_cache() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+    let server = MockGemini::with_responses(vec![
+        MockResponse::plan(&[("a.txt", "Documents")]),
+        MockResponse::plan(&[("a.txt", "Notes")]),
+    ]);
+    let client = client_for(&server);
+    let mut cache = Cache::new();
+
+    client
+        .organize_files_with_cache(names(&["a.txt"]), Some(&mut cache), Some(dir.path()))
+        .await
+        .unwrap();
+
+    let mut client = GeminiClient::new_silent(API_KEY, &names(&["Documents", "Notes"]));
+    client.set_base_url(server.base_url());
+    let plan = client
+        .organize_files_with_cache(names(&["a.txt"]), Some(&mut cache), Some(dir.path()))
+        .await
+        .unwrap();
+
+    assert_eq!(server.request_count(), 2);
+    assert_eq!(plan.files[0].category, "Notes");
+}
+
+#[tokio::test]
+async fn test_changing_file_context_invalidates_cache() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+    let server = MockGemini::with_responses(vec![MockResponse::plan(&[("a.txt", "Documents")])]);
+    let mut client = client_for(&server);
+    let mut cache = Cache::new();
+
+    client
+        .organize_files_with_cache(names(&["a.txt"]), Some(&mut cache), Some(dir.path()))
+        .await
+        .unwrap();
+    let fingerprint = client.cache_fingerprint().digest();
+    assert!(
+        cache
+            .lookup(&names(&["a.txt"]), dir.path(), &fingerprint)
+            .missing
+            .is_empty()
+    );
+
+    client.set_file_context(FileContextFields {
+        size: true,
+        ..FileContextFields::none()
+    });
+    let fingerprint = client.cache_fingerprint().digest();
+    let lookup = cache.lookup(&names(&["a.txt"]), dir.path(), &fingerprint);
+
+    assert!(lookup.cached.is_empty());
+    assert_eq!(lookup.missing, names(&["a.txt"]));
+}
+
+#[tokio::test]
+async fn test_batches_skip_cached_files() {
+    let dir = TempDir::new().unwrap();
+    let files: Vec<String> = (0..6).map(|i| format!("file_{:02}.txt", i)).collect();
+    for name in &files {
+        std::fs::write(dir.path().join(name), name).unwrap();
+    }
+    let expected = files.clone();
+    let server = MockGemini::start(move |request| {
+        let prompt = request.prompt();
+        let batch: Vec<(&str, &str)> = expected
+            .iter()
+            .filter(|name| prompt.contains(name.as_str()))
+            .map(|name| (name.as_str(), "Documents"))
+            .collect();
+        MockResponse::plan(&batch)
+    });
+    let mut client = client_for(&server);
+    client.set_token_budget(TokenBudget {
+        max_files: 2,
+        ..Default::default()
+    });
+    let mut cache = Cache::new();
+
+    client
+        .organize_files_in_batches(files[..4].to_vec(), Some(&mut cache), Some(dir.path()))
+        .await
+        .unwrap();
+    assert_eq!(server.request_count(), 2);
+
+    let plan = client
+        .organize_files_in_batches(files.clone(), Some(&mut cache), Some(dir.path()))
+        .await
+        .unwrap();
+
+    // Only the two new files needed a request
+    assert_eq!(server.request_count(), 3);
+    let categorized: Vec<_> = plan.files.iter().map(|f| f.filename.clone()).collect();
+    assert_eq!(categorized, files);
+}
+
+#[tokio::test]
+async fn test_sub_categories_are_cached_by_content() {
+    let dir = TempDir::new().unwrap();
+    let contents = [
+        ("invoice.txt", "Invoice #1 from ACME"),
+        ("invoice (1).txt", "Invoice #1 from ACME"),
+        ("notes.txt", "Meeting notes"),
+    ];
+    let mut files = Vec::new();
+    let mut paths = HashMap::new();
+    for (name, text) in contents {
+        let path = dir.path().join(name);
+        std::fs::write(&path, text).unwrap();
+        paths.insert(name.to_string(), path);
+        files.push(FileCategory {
+            filename: name.to_string(),
+            category: "Documents".to_string(),
+            ..Default::default()
+        });
+    }
+    let targets = read_inspection_targets(&files, &paths, 500, None, &MediaBudget::new(0));
+    let response = serde_json::json!({ "invoice.txt": "Invoices", "notes.txt": "Meetings" });
+    let server = MockGemini::with_responses(vec![MockResponse::text(&response.to_string())]);
+    let client = client_for(&server);
+    let mut cache = Cache::new();
+
+    let first = client
+        .inspect_sub_categories_with_cache(&targets, &SubFolderIndex::default(), Some(&mut cache))
+        .await;
+
+    // The duplicate download is not sent, but gets the same sub-category
+    assert_eq!(server.request_count(), 1);
+    assert!(!server.requests()[0].prompt().contains("invoice (1).txt"));
+    assert_eq!(first.sub_categories["invoice (1).txt"], "Invoices");
+    assert!(first.failures.is_empty());
+
+    let second = client
+        .inspect_sub_categories_with_cache(&targets, &SubFolderIndex::default(), Some(&mut cache))
+        .await;
+
+    assert_eq!(server.request_count(), 1);
+    assert_eq!(second.sub_categories, first.sub_categories);
+}
+
+#[tokio::test]
+async fn test_skipped_files_are_requested_again() {
+    let server = MockGemini::with_responses(vec![
+        MockResponse::plan(&[("a.txt", "Documents")]),
+        MockResponse::plan(&[("b.txt", "Documents")]),
+    ]);
+    let client = client_for(&server);
+
+    let plan = client
+        .organize_files(names(&["a.txt", "b.txt"]))
+        .await
+        .unwrap();
+
+    assert_eq!(plan.files.len(), 2);
+    let second = &server.requests()[1];
+    assert!(second.prompt().contains("b.txt"));
+    assert!(!second.prompt().contains("a.txt"));
+}
+
+#[tokio::test]
+async fn test_incomplete_response_splits_the_batch() {
+    let server = MockGemini::with_responses(vec![
+        MockResponse::text("{\"files\": [{\"filename\": \"a.txt\""),
+        MockResponse::plan(&[("a.txt", "Documents"), ("b.txt", "Documents")]),
+        MockResponse::plan(&[("c.txt", "Documents"), ("d.txt", "Documents")]),
+    ]);
+    let client = client_for(&server);
+
+    let plan = client
+        .organize_files(names(&["a.txt", "b.txt", "c.txt", "d.txt"]))
+        .await
+        .unwrap();
+
+    let files: Vec<_> = plan.files.iter().map(|f| f.filename.as_str()).collect();
+    assert_eq!(files, ["a.txt", "b.txt", "c.txt", "d.txt"]);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[1].prompt().contains("b.txt"));
+    assert!(!requests[1].prompt().contains("c.txt"));
+    assert!(requests[2].prompt().contains("d.txt"));
+    assert!(!requests[2].prompt().contains("a.txt"));
+}
+
+#[tokio::test]
+async fn test_file_failing_alone_is_left_uncategorized() {
+    let files: Vec<String> = (0..6).map(|i| format!("file_{:02}.txt", i)).collect();
+    let expected = files.clone();
+    let server = MockGemini::start(move |request| {
+        let prompt = request.prompt();
+        // Any request including this file gets a cut-off reply
+        if prompt.contains("file_04.txt") {
+            return MockResponse::text("{\"files\": [{\"filename\"");
+        }
+        let batch: Vec<(&str, &str)> = expected
+            .iter()
+            .filter(|name| prompt.contains(name.as_str()))
+            .map(|name| (name.as_str(), "Documents"))
+            .collect();
+        MockResponse::plan(&batch)
+    });
+    let mut client = client_for(&server);
+    client.set_token_budget(TokenBudget {
+        max_files: 3,
+        ..Default::default()
+    });
+
+    let plan = client
+        .organize_files_in_batches(files.clone(), None, None)
+        .await
+        .unwrap();
+
+    let categorized: Vec<_> = plan.files.iter().map(|f| f.filename.as_str()).collect();
+    assert_eq!(
+        categorized,
+        [
+            "file_00.txt",
+            "file_01.txt",
+            "file_02.txt",
+            "file_03.txt",
+            "file_05.txt"
+        ]
+    );
+    assert_eq!(plan.uncategorized, ["file_04.txt"]);
+    // One request for the first batch and five while halving the second;
+    // the failing file is not asked for again once it failed alone
+    assert_eq!(server.request_count(), 6);
+}
+
+#[tokio::test]
+async fn test_propose_categories() {
+    let response = serde_json::json!({
+        "categories": [
+            { "name": "Invoices", "description": "Bills and receipts" },
+            { "name": "invoices", "description": "Duplicate" },
+            { "name": "Work/Clients", "description": "Client projects" },
+            { "name": "Misc", "description": "Everything else" }
+        ]
+    });
+    let server = MockGemini::with_responses(vec![MockResponse::text(&response.to_string())]);
+    let client = client_for(&server);
+
+    let proposals = client
+        .propose_categories(&names(&["invoice_2024.pdf", "client_a.docx"]), 250)
+        .await
+        .unwrap();
+
+    let proposed: Vec<_> = proposals.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(proposed, ["Invoices", "Work-Clients", "Misc"]);
+    assert_eq!(proposals[0].description, "Bills and receipts");
+
+    let prompt = server.requests()[0].prompt();
+    assert!(prompt.contains("invoice_2024.pdf"));
+    assert!(prompt.contains("250 files"));
+}
+
+#[tokio::test]
+async fn test_custom_prompt_template_is_sent() {
+    let server = MockGemini::with_responses(vec![MockResponse::plan(&[("scan.pdf", "Legal")])]);
+    let mut client = client_for(&server);
+    let config = PromptConfig {
+        categorization: Some("Law firm intake. Files: {files} Folders: {categories}".to_string()),
+        ..Default::default()
+    };
+    client.set_prompt_templates(PromptTemplates::load(&config).unwrap());
+
+    client.organize_files(names(&["scan.pdf"])).await.unwrap();
+
+    let prompt = server.requests()[0].prompt();
+    assert!(prompt.starts_with("Law firm intake. Files: [scan.pdf]. Folders: 'Documents'"));
+    // The response format is always requested, whatever the template says
+    assert!(prompt.contains("Return ONLY a JSON object"));
+}
@@ -57,7 +57,7 @@ fn test_handle_offline_organization_empty_batch() {
         paths: vec![],
     };
 
-    let result = handle_offline_organization(batch, target_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, target_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
     assert!(result.unwrap().is_none());
@@ -73,7 +73,7 @@ fn test_handle_offline_organization_all_unknown_extensions() {
         &dir_path,
     );
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
     // Should return None when no files can be categorized
@@ -90,7 +90,7 @@ fn test_handle_offline_organization_dry_run_no_file_moves() {
         &dir_path,
     );
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
     // In dry run, files should NOT be moved
@@ -118,7 +118,7 @@ fn test_handle_offline_organization_mixed_files() {
     );
 
     // Dry run to verify categorization without moving
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
     // Files should still exist (dry run)
@@ -162,7 +162,7 @@ fn test_handle_offline_organization_various_extensions() {
 
     let batch = create_file_batch(files.iter().map(|s| s.to_string()).collect(), &dir_path);
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
 }
@@ -177,7 +177,7 @@ fn test_handle_offline_organization_case_insensitive() {
         &dir_path,
     );
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
 }
@@ -189,7 +189,7 @@ fn test_handle_offline_organization_undo_log_not_modified_in_dry_run() {
 
     let batch = create_file_batch(vec!["photo.jpg".to_string()], &dir_path);
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
     // Undo log should be empty in dry run mode
@@ -210,7 +210,7 @@ fn test_handle_offline_organization_files_without_extension() {
         &dir_path,
     );
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
     // All files have no/unknown extensions, should return None
@@ -229,18 +229,22 @@ fn test_organization_plan_structure() {
                 filename: "photo1.jpg".to_string(),
                 category: "Images".to_string(),
                 sub_category: String::new(),
+                ..Default::default()
             },
             FileCategory {
                 filename: "photo2.png".to_string(),
                 category: "Images".to_string(),
                 sub_category: String::new(),
+                ..Default::default()
             },
             FileCategory {
                 filename: "doc.pdf".to_string(),
                 category: "Documents".to_string(),
                 sub_category: String::new(),
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
 
     assert_eq!(plan.files.len(), 3);
@@ -250,7 +254,10 @@ fn test_organization_plan_structure() {
 
 #[test]
 fn test_organization_plan_empty() {
-    let plan = OrganizationPlan { files: vec![] };
+    let plan = OrganizationPlan {
+        files: vec![],
+        ..Default::default()
+    };
 
     assert!(plan.files.is_empty());
 }
@@ -261,6 +268,7 @@ fn test_file_category_with_subcategory() {
         filename: "project.rs".to_string(),
         category: "Code".to_string(),
         sub_category: "Rust".to_string(),
+        ..Default::default()
     };
 
     assert_eq!(file_category.filename, "project.rs");
@@ -282,7 +290,7 @@ fn test_handle_offline_organization_hidden_files() {
         &dir_path,
     );
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
 }
@@ -301,7 +309,7 @@ fn test_handle_offline_organization_multiple_dots_in_filename() {
         &dir_path,
     );
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
 }
@@ -313,7 +321,7 @@ fn test_handle_offline_organization_single_file() {
 
     let batch = create_file_batch(vec!["single.jpg".to_string()], &dir_path);
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
 }
@@ -321,7 +329,7 @@ fn test_handle_offline_organization_single_file() {
 #[test]
 fn test_handle_offline_organization_large_batch() {
     // Generate 100 files with various extensions
-    let extensions = vec!["jpg", "pdf", "rs", "mp3", "mp4", "zip"];
+    let extensions = ["jpg", "pdf", "rs", "mp3", "mp4", "zip"];
     let files: Vec<String> = (0..100)
         .map(|i| format!("file{}.{}", i, extensions[i % extensions.len()]))
         .collect();
@@ -332,7 +340,7 @@ fn test_handle_offline_organization_large_batch() {
 
     let batch = create_file_batch(files, &dir_path);
 
-    let result = handle_offline_organization(batch, &dir_path, true, &mut undo_log);
+    let result = handle_offline_organization(batch, &dir_path, true, false, &mut undo_log);
 
     assert!(result.is_ok());
 }
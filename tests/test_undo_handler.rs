@@ -6,10 +6,13 @@
 //! - Path validation
 //! - Dry run behavior
 //! - Successful undo operations
+//! - Sessions, filters and redo
 
 use noentropy::cli::args::Command;
 use noentropy::cli::handlers::handle_undo;
 use noentropy::cli::path_utils::validate_and_normalize_path;
+use noentropy::files::{UndoFilter, redo_moves_auto, undo_moves_auto};
+use noentropy::models::{MoveStatus, SessionMode};
 use noentropy::storage::UndoLog;
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
@@ -21,7 +24,17 @@ use tempfile::TempDir;
 
 /// Helper to create test Command::Undo
 fn create_test_undo_command(dry_run: bool, path: Option<PathBuf>) -> Command {
-    Command::Undo { dry_run, path }
+    Command::Undo {
+        dry_run,
+        path,
+        last: false,
+        session: None,
+        source: None,
+        destination: None,
+        category: None,
+        since: None,
+        until: None,
+    }
 }
 
 /// Helper to setup a temp directory with files and subdirectories for undo testing
@@ -265,7 +278,7 @@ async fn test_handle_undo_multiple_moves_dry_run() {
 
     // Create undo log
     let undo_log_path = dir_path.join("undo_log.json");
-    let moves: Vec<(PathBuf, PathBuf)> = files.iter().cloned().collect();
+    let moves: Vec<(PathBuf, PathBuf)> = files.to_vec();
     create_undo_log_with_moves(&undo_log_path, moves);
 
     // Dry run
@@ -303,7 +316,7 @@ async fn test_handle_undo_logs_saved() {
     fs::copy(&undo_log_path, target_path.join("undo_log.json")).unwrap();
 
     // Copy the file structure
-    fs::create_dir_all(&target_path.join("Images")).unwrap();
+    fs::create_dir_all(target_path.join("Images")).unwrap();
     fs::copy(&photo, target_path.join("Images").join("photo.jpg")).unwrap();
 
     // Run undo with --dry-run to test it doesn't fail on save
@@ -312,3 +325,193 @@ async fn test_handle_undo_logs_saved() {
 
     assert!(result.is_ok());
 }
+
+// ============================================================================
+// SESSION TESTS
+// ============================================================================
+
+#[test]
+fn test_undo_single_session_leaves_other_sessions() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path().to_path_buf();
+    let docs_dir = dir_path.join("Documents");
+    fs::create_dir_all(&docs_dir).unwrap();
+
+    let mut undo_log = UndoLog::new();
+    let mut sessions = Vec::new();
+    for name in ["first.pdf", "second.pdf"] {
+        File::create(docs_dir.join(name)).unwrap();
+        sessions.push(undo_log.begin_session(&dir_path, SessionMode::Offline));
+        undo_log.record_move(dir_path.join(name), docs_dir.join(name));
+        undo_log.finish_session();
+    }
+
+    let filter = UndoFilter::session(undo_log.last_session_id().unwrap());
+    let summary = undo_moves_auto(&dir_path, &mut undo_log, &filter, false).unwrap();
+
+    assert_eq!(summary.restored_count(), 1);
+    assert!(dir_path.join("second.pdf").exists());
+    assert!(docs_dir.join("first.pdf").exists());
+    assert_eq!(undo_log.last_session_id(), Some(sessions[0]));
+}
+
+#[tokio::test]
+async fn test_handle_undo_unknown_session() {
+    let temp_dir = TempDir::new().unwrap();
+    let command = Command::Undo {
+        dry_run: true,
+        path: None,
+        last: false,
+        session: Some(u64::MAX),
+        source: None,
+        destination: None,
+        category: None,
+        since: None,
+        until: None,
+    };
+
+    let result = handle_undo(&command, temp_dir.path().to_path_buf()).await;
+
+    assert!(result.is_ok());
+}
+
+// ============================================================================
+// FILTER TESTS
+// ============================================================================
+
+#[test]
+fn test_undo_filter_reverts_only_matching_moves() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path().to_path_buf();
+    let code_dir = dir_path.join("Code");
+    fs::create_dir_all(&code_dir).unwrap();
+
+    let mut undo_log = UndoLog::new();
+    for name in ["data.csv", "main.rs"] {
+        File::create(code_dir.join(name)).unwrap();
+        undo_log.record_move(dir_path.join(name), code_dir.join(name));
+    }
+
+    let filter = UndoFilter {
+        source: Some(noentropy::files::parse_glob("*.csv").unwrap()),
+        category: Some("Code".to_string()),
+        ..UndoFilter::default()
+    };
+    let summary = undo_moves_auto(&dir_path, &mut undo_log, &filter, false).unwrap();
+
+    assert_eq!(summary.restored_count(), 1);
+    assert!(dir_path.join("data.csv").exists());
+    assert!(code_dir.join("main.rs").exists());
+    assert_eq!(undo_log.get_completed_count(), 1);
+}
+
+// ============================================================================
+// REDO TESTS
+// ============================================================================
+
+/// Organizes `names` into `Documents/` in one session, then undoes it
+fn organize_and_undo(dir_path: &Path, names: &[&str]) -> (UndoLog, u64) {
+    let docs_dir = dir_path.join("Documents");
+    fs::create_dir_all(&docs_dir).unwrap();
+
+    let mut undo_log = UndoLog::new();
+    let session = undo_log.begin_session(dir_path, SessionMode::Offline);
+    for name in names {
+        fs::write(docs_dir.join(name), name).unwrap();
+        undo_log.record_move(dir_path.join(name), docs_dir.join(name));
+    }
+    undo_log.finish_session();
+
+    undo_moves_auto(dir_path, &mut undo_log, &UndoFilter::default(), false).unwrap();
+    (undo_log, session)
+}
+
+#[test]
+fn test_redo_reapplies_undone_moves_with_fresh_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path().to_path_buf();
+    let (mut undo_log, session) = organize_and_undo(&dir_path, &["a.pdf", "b.pdf"]);
+    assert!(dir_path.join("a.pdf").exists());
+    // Redo must recreate a category folder removed after the undo
+    fs::remove_dir(dir_path.join("Documents")).unwrap();
+
+    let filter = UndoFilter::session(session);
+    let summary = redo_moves_auto(&dir_path, &mut undo_log, &filter, false).unwrap();
+
+    assert_eq!(summary.restored_count(), 2);
+    assert!(dir_path.join("Documents").join("a.pdf").exists());
+    assert!(dir_path.join("Documents").join("b.pdf").exists());
+    assert!(undo_log.get_undone_moves().is_empty());
+
+    let records = undo_log.session_records(session);
+    let count = |status: MoveStatus| records.iter().filter(|r| r.status == status).count();
+    assert_eq!(count(MoveStatus::Completed), 2);
+    assert_eq!(count(MoveStatus::Redone), 2);
+    assert_eq!(undo_log.last_session_id(), Some(session));
+}
+
+#[test]
+fn test_redo_skips_changed_files_and_existing_destinations() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path().to_path_buf();
+    let (mut undo_log, _) = organize_and_undo(&dir_path, &["changed.txt", "taken.txt", "ok.txt"]);
+
+    fs::write(dir_path.join("changed.txt"), "edited after the undo").unwrap();
+    fs::create_dir_all(dir_path.join("Documents")).unwrap();
+    fs::write(dir_path.join("Documents").join("taken.txt"), "new file").unwrap();
+
+    let summary = redo_moves_auto(&dir_path, &mut undo_log, &UndoFilter::default(), false).unwrap();
+
+    assert_eq!(summary.restored_count(), 1);
+    assert_eq!(summary.skipped_count(), 2);
+    let skipped: Vec<_> = summary
+        .skipped_files()
+        .iter()
+        .map(|(path, reason)| (path.file_name().unwrap().to_str().unwrap(), reason.as_str()))
+        .collect();
+    assert!(skipped.contains(&("changed.txt", "changed since it was restored")));
+    assert!(
+        skipped
+            .iter()
+            .any(|(name, reason)| *name == "taken.txt" && reason.contains("another file"))
+    );
+    assert!(dir_path.join("changed.txt").exists());
+    assert!(dir_path.join("taken.txt").exists());
+    assert_eq!(
+        fs::read_to_string(dir_path.join("Documents").join("taken.txt")).unwrap(),
+        "new file"
+    );
+    assert!(dir_path.join("Documents").join("ok.txt").exists());
+    assert_eq!(undo_log.get_undone_moves().len(), 2);
+}
+
+#[test]
+fn test_redo_skips_moves_undone_without_a_stamp() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path().to_path_buf();
+    fs::write(dir_path.join("old.pdf"), "x").unwrap();
+
+    // Marked undone the way older versions did, without recording the file
+    let mut undo_log = UndoLog::new();
+    let destination = dir_path.join("Documents").join("old.pdf");
+    undo_log.record_move(dir_path.join("old.pdf"), destination.clone());
+    undo_log.mark_as_undone(&destination);
+
+    let summary = redo_moves_auto(&dir_path, &mut undo_log, &UndoFilter::default(), false).unwrap();
+
+    assert_eq!(summary.skipped_count(), 1);
+    assert!(dir_path.join("old.pdf").exists());
+    assert_eq!(undo_log.get_undone_moves().len(), 1);
+}
+
+#[test]
+fn test_redo_dry_run_moves_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path().to_path_buf();
+    let (mut undo_log, _) = organize_and_undo(&dir_path, &["a.pdf"]);
+
+    redo_moves_auto(&dir_path, &mut undo_log, &UndoFilter::default(), true).unwrap();
+
+    assert!(dir_path.join("a.pdf").exists());
+    assert_eq!(undo_log.get_undone_moves().len(), 1);
+}